@@ -0,0 +1,43 @@
+//! Times replaying a Zipf trace through each policy, and computing stack distances for traces of
+//! growing length.
+//!
+//! Run with `cargo bench`.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+
+use cache_sim::bench_support::{policies, replay, zipf_trace};
+
+fn replay_policies(c: &mut Criterion) {
+    let trace = zipf_trace(10_000, 0.9, 100_000, 0);
+
+    let mut group = c.benchmark_group("replay");
+    for (i, (name, _)) in policies().into_iter().enumerate() {
+        // every run needs a fresh policy, so look it up again each time
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || policies().swap_remove(i).1,
+                |policy| replay(&trace, 1000, policy),
+                BatchSize::SmallInput,
+            );
+        });
+    }
+    group.finish();
+}
+
+fn stack_distances(c: &mut Criterion) {
+    let mut group = c.benchmark_group("stack_distances");
+    group.sample_size(10);
+    for length in [1_000, 10_000, 100_000] {
+        let trace = zipf_trace(10_000, 0.9, length, 1);
+        group.bench_with_input(BenchmarkId::new("slow", length), &trace, |b, trace| {
+            b.iter(|| trace.stack_distances());
+        });
+        group.bench_with_input(BenchmarkId::new("fast", length), &trace, |b, trace| {
+            b.iter(|| trace.stack_distances_fast());
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, replay_policies, stack_distances);
+criterion_main!(benches);
@@ -42,6 +42,7 @@ impl From<OpRecord> for crate::GeneralModelItem {
             record.cost[0], // TODO: something better
             record.size,
         )
+        .with_write(record.optype == Operation::Write)
     }
 }
 
@@ -66,17 +67,120 @@ impl From<OpRecord> for crate::GeneralModelItem {
 /// # Ok(())}
 /// ````
 pub fn parse<R: std::io::Read>(input: R) -> Result<Vec<OpRecord>, csv::Error> {
-    let mut rdr = csv::ReaderBuilder::new()
-        .has_headers(false)
-        .comment(Some(b'#'))
-        .from_reader(input);
-
-    rdr.deserialize()
+    parse_iter(input)
         // `Result` implements fromiterator, so when we collect this it will give us the first
         // error if there are any errors, or else will give us the vector of [`OpRecord`]s.
         .collect()
 }
 
+/// Parse a gzip-compressed file-like object into a vector of oprecords.
+///
+/// # Errors
+/// If the input isn't valid gzip, or the decompressed csv does not conform to the `atf` standard.
+///
+/// # Example
+///
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use std::fs::File;
+///
+/// use cache_sim::atf::parse_gz;
+///
+/// let records = parse_gz(File::open("trace.atf.gz")?)?;
+/// # Ok(())}
+/// ```
+#[cfg(feature = "gzip")]
+pub fn parse_gz<R: std::io::Read>(input: R) -> Result<Vec<OpRecord>, csv::Error> {
+    parse(flate2::read::GzDecoder::new(input))
+}
+
+/// Parse a file-like object into an iterator of oprecords, reading it lazily.
+///
+/// This is useful for traces too big to fit in memory. Each record is a separate `Result`, since
+/// errors are only found when the bad row is reached.
+///
+/// # Example
+///
+/// Simulating a cache without ever holding the whole trace:
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use std::fs::File;
+/// use std::io::BufReader;
+///
+/// use cache_sim::{atf::parse_iter, Cache, GeneralModelItem, Lru};
+///
+/// let mut cache = Cache::<Lru<_>, (), _>::new(100);
+/// for record in parse_iter(BufReader::new(File::open("trace.atf")?)) {
+///     cache.access(GeneralModelItem::from(record?));
+/// }
+/// # Ok(())}
+/// ````
+pub fn parse_iter<R: std::io::Read>(input: R) -> csv::DeserializeRecordsIntoIter<R, OpRecord> {
+    csv::ReaderBuilder::new()
+        .has_headers(false)
+        .comment(Some(b'#'))
+        .from_reader(input)
+        .into_deserialize()
+}
+
+/// Write items to a file-like object as an atf trace, which [`parse`] reads back.
+///
+/// Items don't record when they were accessed, so each row's timestamp is its position in the
+/// trace instead. The optype is `W` for items marked with
+/// [`GeneralModelItem::with_write`](crate::GeneralModelItem::with_write) and `R` otherwise, and
+/// there's a single cost column, which is all converting back into items uses.
+///
+/// # Errors
+/// If writing fails.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), csv::Error> {
+/// use cache_sim::atf::{parse, write};
+/// use cache_sim::{GeneralModelItem, Trace};
+///
+/// let trace = Trace::from(vec![
+///     GeneralModelItem::new(3, 2.5, 4),
+///     GeneralModelItem::new(0, 1.0, 1).with_write(true),
+///     GeneralModelItem::new(3, 2.5, 4),
+/// ]);
+///
+/// let mut atf = Vec::new();
+/// write(trace.inner(), &mut atf)?;
+///
+/// let parsed: Trace<_> = parse(atf.as_slice())?
+///     .into_iter()
+///     .map(GeneralModelItem::from)
+///     .collect();
+/// assert_eq!(parsed, trace);
+/// # Ok(())}
+/// ```
+pub fn write<W: std::io::Write>(
+    trace: &[crate::GeneralModelItem],
+    mut writer: W,
+) -> Result<(), csv::Error> {
+    use crate::item::Item;
+
+    writeln!(writer, "# item id, timestamp, operation, size, cost")?;
+
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(writer);
+    for (timestamp, item) in trace.iter().enumerate() {
+        writer.write_record([
+            item.uid().to_string(),
+            timestamp.to_string(),
+            String::from(if item.is_write() { "W" } else { "R" }),
+            item.size().to_string(),
+            item.cost().to_string(),
+        ])?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -138,4 +242,158 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn streaming_cache_matches_trace() -> Result<(), csv::Error> {
+        use crate::{Cache, GeneralModelItem, Lru, Trace};
+
+        const DATA: &[u8] = b"# header
+0,1,R,1,1
+1,2,R,1,1
+2,3,R,1,1
+0,4,R,1,1
+3,5,W,1,1
+1,6,R,1,1";
+
+        let mut streamed = Cache::<Lru<_>, (), _>::new(2);
+        for record in parse_iter(DATA) {
+            streamed.access(GeneralModelItem::from(record?));
+        }
+
+        let trace: Trace<_> = parse(DATA)?
+            .into_iter()
+            .map(GeneralModelItem::from)
+            .collect();
+        let mut traced = Cache::<Lru<_>, (), _>::new(2);
+        traced.run_trace(&trace);
+
+        assert_eq!(streamed.hits(), traced.hits());
+        assert_eq!(streamed.misses(), traced.misses());
+        assert_eq!(streamed.set(), traced.set());
+
+        Ok(())
+    }
+
+    #[test]
+    fn write_flag() -> Result<(), csv::Error> {
+        use crate::item::Item;
+        use crate::{Cache, GeneralModelItem, Lru, WritePolicy};
+
+        const DATA: &[u8] = b"# header
+0,1,W,1,1
+1,2,R,1,1
+2,3,R,1,1
+0,4,R,1,1
+1,5,R,1,1";
+
+        let items: Vec<_> = parse(DATA)?
+            .into_iter()
+            .map(GeneralModelItem::from)
+            .collect();
+        assert_eq!(
+            items.iter().map(Item::is_write).collect::<Vec<_>>(),
+            vec![true, false, false, false, false]
+        );
+
+        // 0 and then 1 are evicted, but only 0 was written
+        let mut back = Cache::<Lru<_>, (), _>::new(2);
+        back.set_write_policy(WritePolicy::WriteBack);
+        back.access_all(items.iter().copied());
+        assert_eq!(back.writebacks(), 1);
+
+        let mut through = Cache::<Lru<_>, (), _>::new(2);
+        through.access_all(items);
+        assert_eq!(through.writebacks(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn write_round_trip() -> Result<(), csv::Error> {
+        use crate::item::Item;
+        use crate::GeneralModelItem;
+
+        fn fields(items: &[GeneralModelItem]) -> Vec<(String, f64, u32, bool)> {
+            items
+                .iter()
+                .map(|i| (i.to_string(), i.cost(), i.size(), i.is_write()))
+                .collect()
+        }
+
+        let items: Vec<_> = parse(include_bytes!("traces/ycsb-sample.atf").as_slice())?
+            .into_iter()
+            .map(GeneralModelItem::from)
+            .collect();
+
+        let mut atf = Vec::new();
+        write(&items, &mut atf)?;
+        let round_tripped: Vec<_> = parse(atf.as_slice())?
+            .into_iter()
+            .map(GeneralModelItem::from)
+            .collect();
+
+        assert_eq!(fields(&round_tripped), fields(&items));
+        Ok(())
+    }
+
+    #[test]
+    fn write_flags_and_costs() -> Result<(), csv::Error> {
+        use crate::GeneralModelItem;
+
+        let items = [
+            GeneralModelItem::new(7, 0.1, 2).with_write(true),
+            GeneralModelItem::new(1, 3.0, 1),
+        ];
+
+        let mut atf = Vec::new();
+        write(&items, &mut atf)?;
+
+        assert_eq!(
+            String::from_utf8(atf.clone()).expect("atf is ascii"),
+            "# item id, timestamp, operation, size, cost\n7,0,W,2,0.1\n1,1,R,1,3\n"
+        );
+        assert_eq!(
+            parse(atf.as_slice())?[0],
+            OpRecord {
+                accessed_item_id: 7,
+                nanos_since_zero: 0,
+                optype: Operation::Write,
+                size: 2,
+                cost: vec![0.1],
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn streaming_reports_bad_rows() {
+        const DATA: &[u8] = b"0,1,R,1,1
+0,1,X,1,1";
+
+        let mut records = parse_iter(DATA);
+        assert!(records.next().expect("There are two rows.").is_ok());
+        assert!(records.next().expect("There are two rows.").is_err());
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn gzip() -> Result<(), Box<dyn std::error::Error>> {
+        use std::io::Write;
+
+        const DATA: &[u8] = b"# this is my cool header!
+1,2,R,4,7,6
+0,16,W,3,4,2.5
+1,4,R,3,2,1.2";
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(DATA)?;
+        let compressed = encoder.finish()?;
+
+        assert_eq!(parse_gz(compressed.as_slice())?, parse(DATA)?);
+
+        // plain input isn't valid gzip
+        assert!(parse_gz(DATA).is_err());
+
+        Ok(())
+    }
 }
@@ -0,0 +1,80 @@
+//! Shared setup for the benchmarks, so they time the same workloads as each other and as any
+//! external harness built on the crate.
+
+use crate::item::Item;
+use crate::replacement_policy::ReplacementPolicy;
+use crate::{
+    Arc, Cache, Car, Clock, ClockPro, Fifo, Lfu, Lirs, Lru, Mq, S3Fifo, Sieve, Slru, TinyLfu,
+    Trace, TwoQ, WTinyLfu,
+};
+
+pub use crate::generate::{markov_trace, stack_model_trace, zipf_trace};
+
+/// Get a fresh instance of each policy worth benchmarking, with its name.
+///
+/// Policies which need the whole trace up front, like [`Belady`](crate::Belady), or a seed to be
+/// reproducible aren't included.
+#[must_use]
+pub fn policies() -> Vec<(&'static str, Box<dyn ReplacementPolicy<u32>>)> {
+    vec![
+        ("LRU", Box::new(Lru::default())),
+        ("FIFO", Box::new(Fifo::default())),
+        ("CLOCK", Box::new(Clock::default())),
+        ("SIEVE", Box::new(Sieve::default())),
+        ("LFU", Box::new(Lfu::<u32>::default())),
+        ("SLRU", Box::new(Slru::default())),
+        ("2Q", Box::new(<TwoQ>::default())),
+        ("ARC", Box::new(<Arc>::default())),
+        ("CAR", Box::new(<Car>::default())),
+        ("LIRS", Box::new(Lirs::default())),
+        ("CLOCK-Pro", Box::new(ClockPro::default())),
+        ("MQ", Box::new(Mq::default())),
+        ("S3-FIFO", Box::new(<S3Fifo>::default())),
+        ("TinyLFU", Box::new(TinyLfu::<Lru>::default())),
+        ("W-TinyLFU", Box::new(WTinyLfu::default())),
+    ]
+}
+
+/// Replay a trace through a fresh cache with the given policy and capacity, returning the number
+/// of hits.
+///
+/// There's no statistic to update, so this times the cache and policy alone.
+///
+/// ```
+/// use cache_sim::bench_support::replay;
+/// use cache_sim::{Lru, Trace};
+///
+/// assert_eq!(replay(&Trace::from(vec![0, 1, 0, 2, 0]), 2, Lru::default()), 2);
+/// ```
+pub fn replay<I: Item, R: ReplacementPolicy<I>>(trace: &Trace<I>, capacity: u32, policy: R) -> u64 {
+    let mut cache = Cache::<_, (), I>::with_replacement_policy(policy, capacity);
+    cache.run_trace(trace);
+    cache.hits()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replay_smoke() {
+        let trace = Trace::from(vec![0, 1, 0, 2, 0, 1, 3, 0]);
+
+        assert_eq!(replay(&trace, 2, Lru::default()), 2);
+        assert_eq!(replay(&trace, 4, Fifo::default()), 4);
+        assert_eq!(replay(&Trace::<u32>::default(), 2, Lru::default()), 0);
+    }
+
+    #[test]
+    fn policies_replay() {
+        let trace = zipf_trace(50, 0.9, 2000, 0);
+
+        for (name, policy) in policies() {
+            let hits = replay(&trace, 10, policy);
+            assert!(
+                hits > 0 && (hits as usize) < trace.len(),
+                "{name}: {hits} hits"
+            );
+        }
+    }
+}
@@ -1,15 +1,24 @@
 //! A simple demand cache simulator.
 
+use std::collections::hash_map::RandomState;
 use std::collections::HashSet;
 use std::fmt::Display;
+use std::hash::BuildHasher;
+use std::marker::PhantomData;
 
 use itertools::Itertools;
 
 use crate::item::Item;
-use crate::replacement_policy::ReplacementPolicy;
+use crate::replacement_policy::{ReplacementPolicy, Snapshot};
 use crate::stats::Stat;
 use crate::trace::Trace;
 
+/// A callback run on every eviction, from [`Cache::on_evict`].
+type EvictionListener<I> = Box<dyn FnMut(I, Option<I>)>;
+
+/// A callback run on every access, from [`Cache::set_access_observer`].
+type AccessObserver<I> = Box<dyn FnMut(&I, bool)>;
+
 /// A cache, generic over a replacement policy and set of statistics.
 ///
 /// ```
@@ -27,14 +36,43 @@ use crate::trace::Trace;
 /// assert_eq!(c.set(), &HashSet::from([0, 2, 3]));
 /// ```
 ///
-pub struct Cache<R: ReplacementPolicy<I>, S: Stat<I> = (), I: Item = u32> {
-    set: HashSet<I>,
+/// The last generic type is the hasher of the cache's set, which every replacement policy and
+/// statistic is also generic over, so a faster hasher can be swapped in for long simulations
+/// without changing the results:
+///
+/// ```
+/// # use std::collections::hash_map::DefaultHasher;
+/// # use std::hash::BuildHasherDefault;
+/// use cache_sim::{Cache, Lru};
+///
+/// let mut c = Cache::<Lru, (), u32, BuildHasherDefault<DefaultHasher>>::new(3);
+/// c.access_all([0, 1, 2, 0, 3]);
+///
+/// assert!(c.contains(&0) && !c.contains(&1));
+/// ```
+pub struct Cache<
+    R: ReplacementPolicy<I, H>,
+    S: Stat<I, H> = (),
+    I: Item = u32,
+    H: BuildHasher = RandomState,
+> {
+    set: HashSet<I, H>,
     replacement_policy: R,
     capacity: u32,
     stat: S,
+    hits: u64,
+    misses: u64,
+    miss_cost: f64,
+    write_policy: WritePolicy,
+    dirty: HashSet<I, H>,
+    writebacks: u64,
+    eviction_listeners: Vec<EvictionListener<I>>,
+    access_observer: Option<AccessObserver<I>>,
 }
 
-impl<R: ReplacementPolicy<I>, S: Stat<I>, I: Item> Cache<R, S, I> {
+impl<R: ReplacementPolicy<I, H>, S: Stat<I, H>, I: Item, H: BuildHasher + Default>
+    Cache<R, S, I, H>
+{
     /// Create an empty cache using an explicitly configured replacement policy.
     pub fn with_replacement_policy(policy: R, capacity: impl Into<u32>) -> Self {
         Self {
@@ -42,52 +80,382 @@ impl<R: ReplacementPolicy<I>, S: Stat<I>, I: Item> Cache<R, S, I> {
             replacement_policy: policy,
             capacity: capacity.into(),
             stat: S::default(),
+            hits: 0,
+            misses: 0,
+            miss_cost: 0.0,
+            write_policy: WritePolicy::default(),
+            dirty: HashSet::default(),
+            writebacks: 0,
+            eviction_listeners: Vec::new(),
+            access_observer: None,
         }
     }
 
-    /// Get the currently used capacity of the set of items.
-    fn used_capacity(&self) -> u32 {
+    /// Get the total size of the items in the cache.
+    ///
+    /// The capacity of the cache bounds this total, not the number of items, so a new item may
+    /// cause several evictions:
+    ///
+    /// ```
+    /// # use std::collections::HashSet;
+    /// use cache_sim::{Cache, GeneralModelGenerator, Lru};
+    ///
+    /// let mut cache = Cache::<Lru<_>, (), _>::new(6);
+    /// let mut g = GeneralModelGenerator::new();
+    ///
+    /// let a = g.item(1.0, 1);
+    /// let b = g.item(1.0, 3);
+    /// let c = g.item(1.0, 5);
+    ///
+    /// cache.access(a);
+    /// cache.access(b);
+    /// assert_eq!(cache.occupied_size(), 4);
+    ///
+    /// cache.access(c);
+    /// assert_eq!(cache.set(), &HashSet::from([c]));
+    /// assert_eq!(cache.occupied_size(), 5);
+    /// ```
+    pub fn occupied_size(&self) -> u32 {
         self.set.iter().map(Item::size).sum()
     }
 
     /// Check whether the cache has space for item.
     fn has_capacity_for(&self, item: I) -> bool {
-        self.used_capacity() + item.size() <= self.capacity
+        self.occupied_size() + item.size() <= self.capacity
     }
 
-    /// Update the cache after an access to item.
+    /// Update the cache after an access to item, returning whether it hit and what it evicted.
+    ///
+    /// Items larger than the capacity of the cache can never fit, so they bypass it: they count as
+    /// misses, but the cache and its replacement policy are left untouched, as the
+    /// [`ReplacementPolicy`] contract says.
+    ///
+    /// After a miss, any items the replacement policy [prefetches](ReplacementPolicy::prefetch)
+    /// are brought in like with [`Cache::prepopulate`], so they aren't counted as hits or misses,
+    /// and what they evict isn't in the returned [`AccessResult`]. Those evictions still reach
+    /// the statistic, through [`Stat::evict`], and the eviction listeners, and dirty items they
+    /// evict are still written back.
+    ///
+    /// # Panics
+    ///
+    /// If the replacement policy errors, and so we end up over capacity.
+    pub fn access(&mut self, item: I) -> AccessResult<I> {
+        let hit = self.set.contains(&item);
+        if let Some(observer) = &mut self.access_observer {
+            observer(&item, hit);
+        }
+
+        if hit {
+            self.hits += 1;
+            self.update(item, true);
+            AccessResult::Hit
+        } else {
+            self.misses += 1;
+            self.miss_cost += item.cost();
+
+            let evicted = self.update(item, true);
+            for prefetched in self.replacement_policy.prefetch(item) {
+                if !self.set.contains(&prefetched) {
+                    self.update(prefetched, false);
+                }
+            }
+
+            if evicted.is_empty() {
+                AccessResult::MissNoEvict
+            } else {
+                AccessResult::MissEvicted(evicted)
+            }
+        }
+    }
+
+    /// Access every item from an iterator to establish the initial state of the cache.
+    ///
+    /// The replacement policy sees these like any other accesses, evicting items as needed, but
+    /// they don't count as hits or misses, and the statistic doesn't see them as accesses. Any
+    /// evictions they cause are still reported, through [`Stat::evict`] and to the eviction
+    /// listeners, and still write back dirty items. Together with [`Cache::reset_stats`], this
+    /// lets you measure the steady state of a cache without its cold-start misses.
+    ///
+    /// ```
+    /// use cache_sim::{Cache, Lru};
+    ///
+    /// let mut c = Cache::<Lru>::new(2);
+    /// c.prepopulate([0, 1, 2]);
+    ///
+    /// assert_eq!(c.misses(), 0);
+    ///
+    /// c.access(2);
+    /// c.access(0); // evicted while prepopulating
+    /// assert_eq!((c.hits(), c.misses()), (1, 1));
+    /// ```
     ///
     /// # Panics
     ///
     /// If the replacement policy errors, and so we end up over capacity.
-    pub fn access(&mut self, item: I) {
+    pub fn prepopulate(&mut self, items: impl IntoIterator<Item = I>) {
+        for item in items {
+            self.update(item, false);
+        }
+    }
+
+    /// Update the cache and replacement policy after an access to item, returning the evicted
+    /// items.
+    ///
+    /// The statistic only sees the access if `record` is set; otherwise it just sees the
+    /// evictions, through [`Stat::evict`]. Either way, the eviction listeners are notified and
+    /// dirty items are written back.
+    fn update(&mut self, item: I, record: bool) -> HashSet<I> {
+        if item.size() > self.capacity {
+            if record {
+                self.stat.update(&self.set, item, &HashSet::new());
+            }
+            return HashSet::new();
+        }
+
         if self.set.contains(&item) || self.has_capacity_for(item) {
-            // we're assuming demand caching for now, so here we don't need to change anything in
-            // the cache, and we just update the state of the replacement policy and the statistics
+            // we're assuming demand caching for now, so here we don't need to evict anything, and
+            // we just update the state of the replacement policy and the statistics
             self.replacement_policy
                 .update_state(&self.set, self.capacity, item);
-            self.stat.update(&self.set, item, &HashSet::new());
+            if record {
+                self.stat.update(&self.set, item, &HashSet::new());
+            }
+
+            self.set.insert(item);
+            self.mark_if_dirty(item);
+            HashSet::new()
         } else {
             // here we actually need to evict something
-            let to_evict = self
+            let mut to_evict = self
                 .replacement_policy
                 .replace(&self.set, self.capacity, item);
 
-            self.stat.update(&self.set, item, &to_evict);
+            // the replacement policy can refuse to admit `item` by asking us to evict it, in which
+            // case it bypasses the cache
+            let admit = !to_evict.remove(&item);
+
+            if record {
+                self.stat.update(&self.set, item, &to_evict);
+            } else if !to_evict.is_empty() {
+                self.stat.evict(&self.set, &to_evict);
+            }
+
+            for &evicted in &to_evict {
+                self.set.remove(&evicted);
+                if self.dirty.remove(&evicted) {
+                    self.writebacks += 1;
+                }
+                for listener in &mut self.eviction_listeners {
+                    listener(evicted, Some(item));
+                }
+            }
 
-            // TODO: is there an easy restructuring of this that prevents us from evicting and then
-            // reinserting `item`, thus ending with an over capacity cache? This can happen now if
-            // the replacement policy is implemented incorrectly.
-            for item in to_evict {
+            if admit {
+                // again because we assume demand paging, we always have to put the last access
+                // into the cache
+                self.set.insert(item);
+                self.mark_if_dirty(item);
+                assert!(self.capacity >= self.occupied_size());
+            }
+            to_evict
+        }
+    }
+
+    /// Remember that a write-back cache now holds a modified copy of item, if it was written.
+    fn mark_if_dirty(&mut self, item: I) {
+        if self.write_policy == WritePolicy::WriteBack && item.is_write() {
+            self.dirty.insert(item);
+        }
+    }
+
+    /// Check whether `item` is in the cache.
+    pub fn contains(&self, item: &I) -> bool {
+        self.set.contains(item)
+    }
+
+    /// Iterate over the items currently in the cache, in no particular order.
+    ///
+    /// Like [`Cache::peek`], this doesn't touch the replacement policy, so it's safe to use for
+    /// inspecting the cache mid-simulation.
+    ///
+    /// ```
+    /// # use std::collections::HashSet;
+    /// use cache_sim::{Cache, Lru};
+    ///
+    /// let mut c = Cache::<Lru>::new(3);
+    /// c.access_all([0, 1, 2]);
+    ///
+    /// assert_eq!(c.iter().count(), 3);
+    /// assert_eq!(c.iter().copied().collect::<HashSet<_>>(), HashSet::from([0, 1, 2]));
+    ///
+    /// // iterating didn't count as a use, so 0 is still the least recently used item
+    /// c.access(3);
+    /// assert!(!c.contains(&0));
+    /// ```
+    pub fn iter(&self) -> std::collections::hash_set::Iter<'_, I> {
+        self.set.iter()
+    }
+
+    /// Check whether accessing `item` would be a hit, without actually accessing it.
+    ///
+    /// Unlike [`Cache::access`], this doesn't update the state of the replacement policy or the
+    /// statistics, so for example it doesn't count as a use of the item for LRU:
+    ///
+    /// ```
+    /// use cache_sim::{Cache, Lru};
+    ///
+    /// let mut c = Cache::<Lru>::new(2);
+    ///
+    /// c.access(0);
+    /// c.access(1);
+    ///
+    /// assert!(c.peek(&0));
+    ///
+    /// // 0 is still the least recently used item
+    /// c.access(2);
+    /// assert!(!c.contains(&0));
+    /// ```
+    pub fn peek(&self, item: &I) -> bool {
+        self.contains(item)
+    }
+
+    /// Remove `item` from the cache, returning whether it was there.
+    ///
+    /// This isn't an access, so it doesn't count as a hit or a miss; the replacement policy just
+    /// forgets about the item.
+    ///
+    /// ```
+    /// use cache_sim::{Cache, Lru};
+    ///
+    /// let mut c = Cache::<Lru>::new(2);
+    ///
+    /// c.access(0);
+    /// c.access(1);
+    ///
+    /// assert!(c.remove(&0));
+    /// assert!(!c.remove(&0));
+    ///
+    /// // there's room for 2 now, so 1 isn't evicted
+    /// c.access(2);
+    /// assert!(c.contains(&1));
+    /// ```
+    pub fn remove(&mut self, item: &I) -> bool {
+        let removed = self.set.remove(item);
+        if removed {
+            self.dirty.remove(item);
+            self.replacement_policy.remove(*item);
+        }
+        removed
+    }
+
+    /// Register a callback to run whenever an item is evicted.
+    ///
+    /// The callback receives the evicted item and the item whose access caused the eviction, which
+    /// may have been prefetched or prepopulated, or `None` if it was [shrinking](Cache::resize)
+    /// the cache. It runs once per evicted item, so
+    /// an access which evicts several items runs it several times, and hits or accesses which
+    /// don't evict anything don't run it at all.
+    ///
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    /// use cache_sim::{Cache, Lru, Trace};
+    ///
+    /// let evictions = Rc::new(RefCell::new(Vec::new()));
+    ///
+    /// let mut c = Cache::<Lru>::new(2);
+    /// let listener_evictions = Rc::clone(&evictions);
+    /// c.on_evict(move |evicted, cause| listener_evictions.borrow_mut().push((evicted, cause)));
+    ///
+    /// c.run_trace(&Trace::from(vec![0, 1, 0, 2, 3, 3, 0]));
+    ///
+    /// c.resize(1);
+    ///
+    /// assert_eq!(
+    ///     &*evictions.borrow(),
+    ///     &[(1, Some(2)), (0, Some(3)), (2, Some(0)), (3, None)]
+    /// );
+    /// ```
+    pub fn on_evict(&mut self, listener: impl FnMut(I, Option<I>) + 'static) {
+        self.eviction_listeners.push(Box::new(listener));
+    }
+
+    /// Set a callback to run on every access, replacing any previous one.
+    ///
+    /// The callback receives the accessed item and whether it hit, and runs before anything is
+    /// evicted, so it's a hook for application-defined costs like latency. Like the eviction
+    /// listeners, it doesn't see [prepopulating](Cache::prepopulate) accesses.
+    ///
+    /// ```
+    /// use std::cell::Cell;
+    /// use std::rc::Rc;
+    /// use cache_sim::{Cache, Lru};
+    ///
+    /// let misses = Rc::new(Cell::new(0));
+    ///
+    /// let mut c = Cache::<Lru>::new(2);
+    /// let observer_misses = Rc::clone(&misses);
+    /// c.set_access_observer(move |_, hit| observer_misses.set(observer_misses.get() + u64::from(!hit)));
+    ///
+    /// c.access_all([0, 1, 0, 2]);
+    /// assert_eq!(misses.get(), c.misses());
+    /// ```
+    pub fn set_access_observer(&mut self, observer: impl FnMut(&I, bool) + 'static) {
+        self.access_observer = Some(Box::new(observer));
+    }
+
+    /// Change the capacity of the cache.
+    ///
+    /// Growing the cache just leaves room for more items, but shrinking it immediately evicts
+    /// items, chosen by the replacement policy, until the rest fit. These evictions aren't caused by
+    /// an access, so the statistic sees them through [`Stat::evict`] rather than
+    /// [`Stat::update`], and the eviction listeners get no cause.
+    ///
+    /// ```
+    /// # use std::collections::HashSet;
+    /// use cache_sim::{Cache, Lru, Trace};
+    ///
+    /// let mut c = Cache::<Lru>::new(3);
+    /// c.run_trace(&Trace::from(vec![0, 1, 2, 0]));
+    ///
+    /// c.resize(2); // 1 is the least recently used item
+    /// assert_eq!(c.set(), &HashSet::from([0, 2]));
+    ///
+    /// c.resize(3);
+    /// c.access(3);
+    /// assert_eq!(c.set(), &HashSet::from([0, 2, 3]));
+    ///
+    /// c.resize(0);
+    /// assert!(c.set().is_empty());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If the replacement policy errors, and so we end up over capacity.
+    pub fn resize(&mut self, capacity: u32) {
+        if capacity < self.occupied_size() {
+            let to_evict = self.replacement_policy.shrink(&self.set, capacity);
+            self.stat.evict(&self.set, &to_evict);
+
+            for &item in &to_evict {
                 self.set.remove(&item);
+                if self.dirty.remove(&item) {
+                    self.writebacks += 1;
+                }
+                for listener in &mut self.eviction_listeners {
+                    listener(item, None);
+                }
             }
         }
 
-        // finally, again because we assume demand paging, we always have to put the last access
-        // into the cache
-        self.set.insert(item);
+        self.capacity = capacity;
 
-        assert!(self.capacity >= self.used_capacity());
+        assert!(self.capacity >= self.occupied_size());
+    }
+
+    /// Get the capacity of the cache.
+    pub const fn capacity(&self) -> u32 {
+        self.capacity
     }
 
     /// Update the cache after accessing all items in the trace.
@@ -104,23 +472,299 @@ impl<R: ReplacementPolicy<I>, S: Stat<I>, I: Item> Cache<R, S, I> {
     /// assert_eq!(c.set(), &HashSet::from([0, 2, 3]));
     /// ```
     pub fn run_trace(&mut self, trace: &Trace<I>) {
-        for item in trace {
-            self.access(*item);
+        self.access_all(trace.iter().copied());
+    }
+
+    /// Update the cache after accessing every item from an iterator.
+    ///
+    /// Unlike [`Cache::run_trace`], this doesn't need the whole trace in memory, so it works with
+    /// streams of accesses, like those from [`crate::atf::parse_iter`].
+    ///
+    /// ```
+    /// use cache_sim::{Cache, Lru, Trace};
+    ///
+    /// let accesses = || (0..1000).map(|i| i % 7 + i % 3);
+    ///
+    /// let mut streamed = Cache::<Lru>::new(4);
+    /// streamed.access_all(accesses());
+    ///
+    /// let mut traced = Cache::<Lru>::new(4);
+    /// traced.run_trace(&accesses().collect::<Trace>());
+    ///
+    /// assert_eq!(streamed.hits(), traced.hits());
+    /// assert_eq!(streamed.misses(), traced.misses());
+    /// assert_eq!(streamed.set(), traced.set());
+    /// ```
+    pub fn access_all(&mut self, items: impl IntoIterator<Item = I>) {
+        for item in items {
+            self.access(item);
         }
     }
 
+    /// Get the number of hits since the cache was created or its stats were reset.
+    ///
+    /// ```
+    /// use cache_sim::{Cache, Lru, Trace};
+    ///
+    /// let mut c = Cache::<Lru>::new(2);
+    /// c.run_trace(&Trace::from(vec![1, 2, 1, 3, 1]));
+    ///
+    /// assert_eq!(c.hits(), 2);
+    /// assert_eq!(c.misses(), 3);
+    /// ```
+    pub const fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Get the number of misses since the cache was created or its stats were reset.
+    pub const fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Get the summed cost of every missed item.
+    ///
+    /// For cost-aware policies like [`crate::Landlord`], this is the metric that matters: two
+    /// policies with the same number of misses can have very different miss costs.
+    ///
+    /// ```
+    /// use cache_sim::{Cache, GeneralModelGenerator, Landlord};
+    ///
+    /// let mut g = GeneralModelGenerator::new();
+    /// let (cheap, other_cheap, expensive) = (g.item(1.0, 1), g.item(1.0, 1), g.item(10.0, 1));
+    ///
+    /// let mut a = Cache::<Landlord, (), _>::new(1);
+    /// a.access_all([cheap, other_cheap, cheap]);
+    ///
+    /// let mut b = Cache::<Landlord, (), _>::new(1);
+    /// b.access_all([cheap, expensive, cheap]);
+    ///
+    /// assert_eq!(a.misses(), b.misses());
+    /// assert_eq!(a.total_miss_cost(), 3.0);
+    /// assert_eq!(b.total_miss_cost(), 12.0);
+    /// ```
+    #[must_use]
+    pub const fn total_miss_cost(&self) -> f64 {
+        self.miss_cost
+    }
+
+    /// Get the number of dirty items written back since the cache was created or its stats were
+    /// reset.
+    ///
+    /// Only a [`WritePolicy::WriteBack`] cache has dirty items: one which was written to while
+    /// cached is written back when it's evicted, whether by an access or by [`Cache::resize`].
+    /// Clean evictions, and evictions while prepopulating, aren't counted. A write-through cache
+    /// never writes back anything, since its writes all go straight through.
+    ///
+    /// ```
+    /// use cache_sim::{Cache, GeneralModelGenerator, Lru, WritePolicy};
+    ///
+    /// let mut g = GeneralModelGenerator::new();
+    /// let (a, b, c) = (g.item(1.0, 1), g.item(1.0, 1), g.item(1.0, 1));
+    /// let trace = [a.with_write(true), b, c, a, b.with_write(true), c, a];
+    ///
+    /// let mut back = Cache::<Lru<_>, (), _>::new(2);
+    /// back.set_write_policy(WritePolicy::WriteBack);
+    /// back.access_all(trace);
+    ///
+    /// // every access misses, evicting a, b, c, a, and b, but a and b were each only dirty once
+    /// assert_eq!(back.misses(), 7);
+    /// assert_eq!(back.writebacks(), 2);
+    ///
+    /// let mut through = Cache::<Lru<_>, (), _>::new(2);
+    /// through.access_all(trace);
+    /// assert_eq!(through.writebacks(), 0);
+    /// ```
+    pub const fn writebacks(&self) -> u64 {
+        self.writebacks
+    }
+
+    /// Get the cache's write policy.
+    pub const fn write_policy(&self) -> WritePolicy {
+        self.write_policy
+    }
+
+    /// Change the cache's write policy.
+    ///
+    /// Switching to write-through forgets which items are dirty, as if they'd all been written
+    /// back for free.
+    pub fn set_write_policy(&mut self, write_policy: WritePolicy) {
+        self.write_policy = write_policy;
+        if write_policy == WritePolicy::WriteThrough {
+            self.dirty.clear();
+        }
+    }
+
+    /// Get the fraction of accesses which were hits, or zero if there haven't been any accesses.
+    ///
+    /// ```
+    /// use cache_sim::{Cache, Lru, Trace};
+    ///
+    /// let mut c = Cache::<Lru>::new(2);
+    /// assert_eq!(c.hit_rate(), 0.0);
+    ///
+    /// c.run_trace(&Trace::from(vec![1, 2, 1, 3, 1]));
+    /// assert!((c.hit_rate() - 0.4).abs() < f64::EPSILON);
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // caches see much fewer than 2^52 accesses
+    pub fn hit_rate(&self) -> f64 {
+        let accesses = self.hits + self.misses;
+        if accesses == 0 {
+            0.0
+        } else {
+            self.hits as f64 / accesses as f64
+        }
+    }
+
+    /// Reset the hit and miss counts, the miss cost, the writeback count, and the statistic, without
+    /// changing the contents of the cache.
+    ///
+    /// ```
+    /// use cache_sim::{Cache, Lru, Trace};
+    /// use cache_sim::stats::HitCount;
+    ///
+    /// let mut c = Cache::<Lru, HitCount>::new(2);
+    /// c.run_trace(&Trace::from(vec![1, 2, 1]));
+    /// c.reset_stats();
+    ///
+    /// assert_eq!(c.hits(), 0);
+    /// assert_eq!(c.stat().0, 0);
+    ///
+    /// c.access(2);
+    /// assert_eq!(c.hits(), 1);
+    /// ```
+    pub fn reset_stats(&mut self) {
+        self.hits = 0;
+        self.misses = 0;
+        self.miss_cost = 0.0;
+        self.writebacks = 0;
+        self.stat = S::default();
+    }
+
+    /// Empty the cache, and reset the replacement policy to its freshly constructed state.
+    ///
+    /// The capacity, the policy's parameters, and the eviction listeners are kept, so the cache
+    /// can be reused for another trace without being rebuilt. The statistics aren't touched; call
+    /// [`Cache::reset_stats`] too for a completely fresh cache.
+    ///
+    /// ```
+    /// use cache_sim::{Arc, Cache, Trace};
+    ///
+    /// let trace = Trace::from(vec![0, 1, 2, 0, 3, 1, 4, 0, 5, 2, 1, 3]);
+    ///
+    /// let mut reused = Cache::<Arc>::new(3);
+    /// reused.run_trace(&trace);
+    /// reused.run_trace(&trace); // the ghost lists are warm now
+    /// reused.clear();
+    /// reused.reset_stats();
+    /// reused.run_trace(&trace);
+    ///
+    /// let mut fresh = Cache::<Arc>::new(3);
+    /// fresh.run_trace(&trace);
+    ///
+    /// assert_eq!(reused.set(), fresh.set());
+    /// assert_eq!(reused.hits(), fresh.hits());
+    /// assert_eq!(reused.replacement_policy().p(), fresh.replacement_policy().p());
+    /// ```
+    pub fn clear(&mut self) {
+        self.set.clear();
+        self.dirty.clear();
+        self.replacement_policy.clear();
+    }
+
     /// Get a reference to cache's statistic.
     pub const fn stat(&self) -> &S {
         &self.stat
     }
 
+    /// Get a reference to cache's replacement policy.
+    pub const fn replacement_policy(&self) -> &R {
+        &self.replacement_policy
+    }
+
     /// Get a reference to cache's set of items.
-    pub const fn set(&self) -> &HashSet<I> {
+    pub const fn set(&self) -> &HashSet<I, H> {
         &self.set
     }
 }
 
-impl<R: ReplacementPolicy<I> + Default, S: Stat<I>, I: Item> Cache<R, S, I> {
+impl<
+        R: ReplacementPolicy<I, H> + Snapshot,
+        S: Stat<I, H>,
+        I: Item,
+        H: BuildHasher + Default + Clone,
+    > Cache<R, S, I, H>
+{
+    /// Save the state of the cache, to [`Cache::restore`] later.
+    ///
+    /// This covers everything which decides how the cache behaves from here on: its items and
+    /// capacity, the replacement policy's state, and which items are dirty, along with the hit,
+    /// miss, and writeback counts. The statistic isn't saved, since it only observes the cache,
+    /// and neither are the eviction listeners.
+    ///
+    /// ```
+    /// use cache_sim::{Cache, Lru};
+    ///
+    /// let mut c = Cache::<Lru>::new(2);
+    /// c.access_all([0, 1, 0]);
+    /// let state = c.snapshot();
+    ///
+    /// c.access_all([2, 3]);
+    /// assert!(!c.contains(&0));
+    ///
+    /// c.restore(state);
+    /// assert!(c.contains(&0));
+    /// assert_eq!(c.hits(), 1);
+    /// ```
+    pub fn snapshot(&self) -> CacheState<R::State, I, H> {
+        CacheState {
+            set: self.set.clone(),
+            policy: self.replacement_policy.snapshot(),
+            capacity: self.capacity,
+            hits: self.hits,
+            misses: self.misses,
+            miss_cost: self.miss_cost,
+            write_policy: self.write_policy,
+            dirty: self.dirty.clone(),
+            writebacks: self.writebacks,
+        }
+    }
+
+    /// Return to a state saved with [`Cache::snapshot`], so the cache behaves exactly as it would
+    /// have from that point.
+    pub fn restore(&mut self, state: CacheState<R::State, I, H>) {
+        self.set = state.set;
+        self.replacement_policy.restore(state.policy);
+        self.capacity = state.capacity;
+        self.hits = state.hits;
+        self.misses = state.misses;
+        self.miss_cost = state.miss_cost;
+        self.write_policy = state.write_policy;
+        self.dirty = state.dirty;
+        self.writebacks = state.writebacks;
+    }
+}
+
+/// The saved state of a [`Cache`], from [`Cache::snapshot`].
+///
+/// `P` is the state of the replacement policy, which for policies which are [`Clone`] is just a
+/// copy of the policy.
+#[derive(Clone)]
+pub struct CacheState<P, I: Item = u32, H = RandomState> {
+    set: HashSet<I, H>,
+    policy: P,
+    capacity: u32,
+    hits: u64,
+    misses: u64,
+    miss_cost: f64,
+    write_policy: WritePolicy,
+    dirty: HashSet<I, H>,
+    writebacks: u64,
+}
+
+impl<R: ReplacementPolicy<I, H> + Default, S: Stat<I, H>, I: Item, H: BuildHasher + Default>
+    Cache<R, S, I, H>
+{
     /// Create an empty cache using the default parameters for the replacement policy.
     #[must_use]
     pub fn new(capacity: u32) -> Self {
@@ -129,11 +773,187 @@ impl<R: ReplacementPolicy<I> + Default, S: Stat<I>, I: Item> Cache<R, S, I> {
             replacement_policy: R::default(),
             capacity,
             stat: S::default(),
+            hits: 0,
+            misses: 0,
+            miss_cost: 0.0,
+            write_policy: WritePolicy::default(),
+            dirty: HashSet::default(),
+            writebacks: 0,
+            eviction_listeners: Vec::new(),
+            access_observer: None,
         }
     }
 }
 
-impl<R: ReplacementPolicy<u32>, S: Stat<u32>> Cache<R, S> {
+/// The outcome of a single [`Cache::access`].
+///
+/// A miss which evicts nothing either found room in the cache, or bypassed it, because the item
+/// was too big or the replacement policy refused to admit it. Since items can have different
+/// sizes, one miss can evict several items.
+///
+/// ```
+/// # use std::collections::HashSet;
+/// use cache_sim::{AccessResult, Cache, Lru};
+///
+/// let mut c = Cache::<Lru>::new(1);
+///
+/// assert_eq!(c.access(0), AccessResult::MissNoEvict);
+/// assert_eq!(c.access(0), AccessResult::Hit);
+/// assert_eq!(c.access(1), AccessResult::MissEvicted(HashSet::from([0])));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccessResult<I: Item> {
+    /// The item was already in the cache.
+    Hit,
+    /// The item wasn't in the cache, and nothing was evicted.
+    MissNoEvict,
+    /// The item wasn't in the cache, and these items were evicted.
+    MissEvicted(HashSet<I>),
+}
+
+impl<I: Item> AccessResult<I> {
+    /// Check whether the access was a hit.
+    #[must_use]
+    pub const fn is_hit(&self) -> bool {
+        matches!(self, Self::Hit)
+    }
+}
+
+/// How a [`Cache`] handles writes, i.e. accesses whose [`Item::is_write`] is set.
+///
+/// Write policies don't change which items are cached, only whether evictions cost a writeback;
+/// see [`Cache::writebacks`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WritePolicy {
+    /// Every write goes straight through to the backing store, so cached items are never dirty.
+    #[default]
+    WriteThrough,
+    /// Writes only update the cached copy, which is written back when it's evicted.
+    WriteBack,
+}
+
+/// A builder for [`Cache`]s, for when [`Cache::new`] isn't enough.
+///
+/// Start with [`CacheBuilder::new`], set the policy with [`CacheBuilder::policy`], and optionally
+/// the capacity (zero by default), the write policy, and some eviction listeners, then call [`CacheBuilder::build`].
+/// The statistic and item type are inferred from how the cache is used, as with the cache's own
+/// constructors.
+///
+/// Caches are always size-aware: the capacity bounds the total [`Item::size`] of the items, so
+/// there's nothing to configure there; use unit-sized items for a cache which counts items.
+///
+/// ```
+/// use std::cell::RefCell;
+/// use std::rc::Rc;
+/// use cache_sim::{Cache, CacheBuilder, GeneralModelGenerator, Lru, Trace};
+///
+/// let mut g = GeneralModelGenerator::new();
+/// let trace = Trace::from(vec![
+///     g.item(1.0, 2),
+///     g.item(1.0, 3),
+///     g.item(1.0, 1),
+///     g.item(1.0, 2),
+///     g.item(1.0, 3),
+/// ]);
+///
+/// let built_evictions = Rc::new(RefCell::new(Vec::new()));
+/// let listener_evictions = Rc::clone(&built_evictions);
+/// let mut built: Cache<Lru<_>, (), _> = CacheBuilder::new()
+///     .capacity(6)
+///     .policy(Lru::default())
+///     .on_evict(move |evicted, _| listener_evictions.borrow_mut().push(evicted))
+///     .build();
+///
+/// let manual_evictions = Rc::new(RefCell::new(Vec::new()));
+/// let listener_evictions = Rc::clone(&manual_evictions);
+/// let mut manual = Cache::<Lru<_>, (), _>::new(6);
+/// manual.on_evict(move |evicted, _| listener_evictions.borrow_mut().push(evicted));
+///
+/// built.run_trace(&trace);
+/// manual.run_trace(&trace);
+///
+/// assert_eq!(built.set(), manual.set());
+/// assert_eq!(built.misses(), manual.misses());
+/// assert_eq!(built_evictions, manual_evictions);
+/// assert!(!built_evictions.borrow().is_empty());
+/// ```
+pub struct CacheBuilder<R = (), S = (), I = u32, H = RandomState> {
+    policy: R,
+    capacity: u32,
+    write_policy: WritePolicy,
+    eviction_listeners: Vec<EvictionListener<I>>,
+    stat: PhantomData<(S, H)>,
+}
+
+impl<S, I, H> CacheBuilder<(), S, I, H> {
+    /// Start building a cache, with no policy yet and zero capacity.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            policy: (),
+            capacity: 0,
+            write_policy: WritePolicy::default(),
+            eviction_listeners: Vec::new(),
+            stat: PhantomData,
+        }
+    }
+}
+
+impl<S, I, H> Default for CacheBuilder<(), S, I, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R, S, I, H> CacheBuilder<R, S, I, H> {
+    /// Set the capacity of the cache.
+    #[must_use]
+    pub const fn capacity(mut self, capacity: u32) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Set the replacement policy of the cache, replacing any previously set policy.
+    #[must_use]
+    pub fn policy<P>(self, policy: P) -> CacheBuilder<P, S, I, H> {
+        CacheBuilder {
+            policy,
+            capacity: self.capacity,
+            write_policy: self.write_policy,
+            eviction_listeners: self.eviction_listeners,
+            stat: PhantomData,
+        }
+    }
+
+    /// Set the write policy of the cache, which is write-through by default.
+    #[must_use]
+    pub const fn write_policy(mut self, write_policy: WritePolicy) -> Self {
+        self.write_policy = write_policy;
+        self
+    }
+
+    /// Register an eviction listener, like [`Cache::on_evict`].
+    #[must_use]
+    pub fn on_evict(mut self, listener: impl FnMut(I, Option<I>) + 'static) -> Self {
+        self.eviction_listeners.push(Box::new(listener));
+        self
+    }
+}
+
+impl<R: ReplacementPolicy<I, H>, S: Stat<I, H>, I: Item, H: BuildHasher + Default>
+    CacheBuilder<R, S, I, H>
+{
+    /// Build the empty cache.
+    #[must_use]
+    pub fn build(self) -> Cache<R, S, I, H> {
+        let mut cache = Cache::with_replacement_policy(self.policy, self.capacity);
+        cache.write_policy = self.write_policy;
+        cache.eviction_listeners = self.eviction_listeners;
+        cache
+    }
+}
+
+impl<R: ReplacementPolicy<u32, H>, S: Stat<u32, H>, H: BuildHasher + Default> Cache<R, S, u32, H> {
     /// If the elements in the cache are all smaller than 26, display them as letters instead.
     ///
     /// ```
@@ -200,7 +1020,21 @@ impl<R: ReplacementPolicy<u32>, S: Stat<u32>> Cache<R, S> {
     }
 }
 
-impl<R: ReplacementPolicy<I>, S: Stat<I>, I: Item> Display for Cache<R, S, I> {
+impl<'c, R: ReplacementPolicy<I, H>, S: Stat<I, H>, I: Item, H: BuildHasher + Default> IntoIterator
+    for &'c Cache<R, S, I, H>
+{
+    type Item = &'c I;
+
+    type IntoIter = std::collections::hash_set::Iter<'c, I>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<R: ReplacementPolicy<I, H>, S: Stat<I, H>, I: Item, H: BuildHasher> Display
+    for Cache<R, S, I, H>
+{
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for (i, item) in self.set.iter().enumerate() {
             // prints the number associated with each item in the stack, in order
@@ -7,6 +7,33 @@ pub trait Condition<I: Item> {
     /// Check whether a certain element should be counted by the histogram.
     /// Needs the trace and the index of the element to check.
     fn check(&self, trace: &Trace<I>, index: usize) -> bool;
+
+    /// Combine with another condition, matching where both match.
+    fn and<C: Condition<I>>(self, other: C) -> And<Self, C>
+    where
+        Self: Sized,
+    {
+        And(self, other)
+    }
+
+    /// Combine with another condition, matching where either matches.
+    fn or<C: Condition<I>>(self, other: C) -> Or<Self, C>
+    where
+        Self: Sized,
+    {
+        Or(self, other)
+    }
+
+    /// Invert the condition, matching where it doesn't.
+    ///
+    /// For conditions which hold for any item type, like [`EqualsPrevious`], the item type can't
+    /// be inferred from the method call, so use [`Not`] directly.
+    fn not(self) -> Not<Self>
+    where
+        Self: Sized,
+    {
+        Not(self)
+    }
 }
 
 /// No condition on the trace, includes all elements for a full frequency histogram
@@ -46,6 +73,93 @@ impl<I: Item> Condition<I> for LastNItems<I> {
     }
 }
 
+/// A condition matching the accesses immediately preceded by an exact, ordered prefix.
+///
+/// With a prefix of length `k`, position `i` matches when `trace[i - k..i]` equals the prefix, and
+/// positions with fewer than `k` predecessors never match, so a one-item prefix behaves like
+/// [`LastNItems`] with that item.
+///
+/// ```
+/// use cache_sim::{condition::Condition, PrefixCondition, Trace};
+///
+/// let trace = Trace::from(vec![0, 1, 2, 0, 1, 3]);
+/// let after_0_1 = PrefixCondition::new(vec![0, 1]);
+///
+/// assert!(after_0_1.check(&trace, 2));
+/// assert!(after_0_1.check(&trace, 5));
+/// assert!(!after_0_1.check(&trace, 1));
+/// ```
+#[derive(Default, Debug)]
+pub struct PrefixCondition<I: Item> {
+    prefix: Vec<I>,
+}
+
+impl<I: Item> PrefixCondition<I> {
+    #[must_use]
+    pub const fn new(prefix: Vec<I>) -> Self {
+        Self { prefix }
+    }
+}
+
+impl<I: Item> Condition<I> for PrefixCondition<I> {
+    fn check(&self, trace: &Trace<I>, index: usize) -> bool {
+        index
+            .checked_sub(self.prefix.len())
+            .is_some_and(|start| trace[start..index] == self.prefix)
+    }
+}
+
+/// Matches accesses to the same item as the access before.
+#[derive(Default, Debug)]
+pub struct EqualsPrevious;
+
+impl<I: Item> Condition<I> for EqualsPrevious {
+    fn check(&self, trace: &Trace<I>, index: usize) -> bool {
+        index > 0 && trace[index - 1] == trace[index]
+    }
+}
+
+/// Matches where both conditions match; see [`Condition::and`].
+///
+/// ```
+/// use cache_sim::condition::{Condition, EqualsPrevious};
+/// use cache_sim::{LastNItems, Trace};
+///
+/// let trace = Trace::from(vec![0, 0, 1, 1, 0]);
+/// let repeated_zero = EqualsPrevious.and(LastNItems::new(vec![0]));
+///
+/// assert!(repeated_zero.check(&trace, 1));
+/// assert!(!repeated_zero.check(&trace, 3));
+/// ```
+#[derive(Default, Debug)]
+pub struct And<A, B>(pub A, pub B);
+
+impl<I: Item, A: Condition<I>, B: Condition<I>> Condition<I> for And<A, B> {
+    fn check(&self, trace: &Trace<I>, index: usize) -> bool {
+        self.0.check(trace, index) && self.1.check(trace, index)
+    }
+}
+
+/// Matches where either condition matches; see [`Condition::or`].
+#[derive(Default, Debug)]
+pub struct Or<A, B>(pub A, pub B);
+
+impl<I: Item, A: Condition<I>, B: Condition<I>> Condition<I> for Or<A, B> {
+    fn check(&self, trace: &Trace<I>, index: usize) -> bool {
+        self.0.check(trace, index) || self.1.check(trace, index)
+    }
+}
+
+/// Matches where the inner condition doesn't; see [`Condition::not`].
+#[derive(Default, Debug)]
+pub struct Not<C>(pub C);
+
+impl<I: Item, C: Condition<I>> Condition<I> for Not<C> {
+    fn check(&self, trace: &Trace<I>, index: usize) -> bool {
+        !self.0.check(trace, index)
+    }
+}
+
 impl<I: Item, F: Fn(&Trace<I>, usize) -> bool> Condition<I> for F {
     fn check(&self, trace: &Trace<I>, index: usize) -> bool {
         self(trace, index)
@@ -120,4 +234,62 @@ mod tests {
         test_case!(repeated: 1; on 1, 2, 1, 0; 3 => true);
         test_case!(wrong_condition: 3; on 1, 2, 0, 1; 2 => false);
     }
+
+    mod prefix_condition {
+        use super::*;
+
+        #[test]
+        fn single_item() {
+            let trace = Trace::from(vec![1, 0, 2, 0, 0, 3]);
+            let prefix = PrefixCondition::new(vec![0]);
+            let matches: Vec<usize> = (0..trace.len())
+                .filter(|&i| prefix.check(&trace, i))
+                .collect();
+
+            assert_eq!(matches, vec![2, 4, 5]);
+        }
+
+        macro_rules! test_case {
+            ( $name:ident: $($seq:expr),*; on $($in:expr),*; $index:expr => $out:expr ) => {
+                #[test]
+                fn $name() {
+                    let condition = PrefixCondition::new(vec![$($seq),*]);
+                    assert_eq!(condition.check(&Trace::from(vec![$($in),*]), $index), $out);
+                }
+            }
+        }
+
+        test_case!(pair: 0, 1; on 0, 1, 2, 0, 1; 2 => true);
+        test_case!(pair_at_end: 0, 1; on 0, 1, 2, 0, 1, 3; 5 => true);
+        test_case!(pair_out_of_order: 1, 0; on 0, 1, 2, 0, 1; 2 => false);
+        test_case!(too_few_predecessors: 0, 1; on 0, 1, 2, 0, 1; 1 => false);
+        test_case!(first_position: 0; on 0, 0; 0 => false);
+    }
+
+    mod combinators {
+        use super::*;
+
+        const TRACE: [u32; 7] = [0, 1, 1, 2, 2, 1, 0];
+
+        macro_rules! test_case {
+            ( $name:ident: $condition:expr => $($out:expr),* ) => {
+                #[test]
+                fn $name() {
+                    let trace = Trace::from(TRACE.to_vec());
+                    let condition = $condition;
+                    let matches: Vec<usize> = (0..trace.len()).filter(|&i| condition.check(&trace, i)).collect();
+                    assert_eq!(matches, vec![$($out),*]);
+                }
+            }
+        }
+
+        test_case!(equals_previous: EqualsPrevious => 2, 4);
+        test_case!(and: EqualsPrevious.and(LastNItems::new(vec![1])) => 2);
+        test_case!(or: EqualsPrevious.or(LastNItems::new(vec![1])) => 2, 3, 4, 6);
+        test_case!(not: Not(EqualsPrevious) => 0, 1, 3, 5, 6);
+        test_case!(not_method: LastNItems::new(vec![1]).not() => 0, 1, 4, 5);
+        test_case!(and_not: LastNItems::new(vec![1]).and(Not(EqualsPrevious)) => 3, 6);
+        test_case!(structs: Or(And(EqualsPrevious, LastNItems::new(vec![2])), Not(NoCondition)) => 4);
+        test_case!(closure: (|t: &Trace, i| t[i] == 0).or(EqualsPrevious) => 0, 2, 4, 6);
+    }
 }
@@ -0,0 +1,203 @@
+//! Utilities for parsing plain csv traces.
+//!
+//! Unlike `atf` files, these only need to contain the identifier of each accessed item, in a column
+//! of your choice; any other columns, like timestamps, are ignored. Items get unit cost and size.
+
+use anyhow::{anyhow, Context};
+
+use crate::GeneralModelItem;
+
+/// The layout of a csv trace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Format {
+    delimiter: u8,
+    id_column: usize,
+    has_header: bool,
+}
+
+impl Default for Format {
+    /// Comma-separated, with the identifier in the first column, and no header row.
+    fn default() -> Self {
+        Self::new(b',', 0, false)
+    }
+}
+
+impl Format {
+    /// Describe a csv trace whose fields are separated by `delimiter`, with the identifier in the
+    /// column with (zero-based) index `id_column`.
+    ///
+    /// If `has_header` is set, the first row is skipped.
+    #[must_use]
+    pub const fn new(delimiter: u8, id_column: usize, has_header: bool) -> Self {
+        Self {
+            delimiter,
+            id_column,
+            has_header,
+        }
+    }
+}
+
+/// Parse a file-like object with one identifier per line into a vector of items.
+///
+/// # Errors
+/// If any line isn't a valid identifier, or reading fails.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> anyhow::Result<()> {
+/// use cache_sim::{csv::parse, Trace};
+///
+/// let trace = Trace::from(parse("0\n1\n0\n".as_bytes())?);
+/// assert_eq!(trace.len(), 3);
+/// # Ok(())}
+/// ```
+pub fn parse<R: std::io::Read>(input: R) -> anyhow::Result<Vec<GeneralModelItem>> {
+    parse_with(input, Format::default())
+}
+
+/// Parse a gzip-compressed file-like object in the given format into a vector of items.
+///
+/// # Errors
+/// If the input isn't valid gzip, or the decompressed trace can't be parsed by [`parse_with`].
+#[cfg(feature = "gzip")]
+pub fn parse_gz_with<R: std::io::Read>(
+    input: R,
+    format: Format,
+) -> anyhow::Result<Vec<GeneralModelItem>> {
+    parse_with(flate2::read::GzDecoder::new(input), format)
+}
+
+/// Parse a file-like object in the given format into a vector of items.
+///
+/// # Errors
+/// If any row doesn't have a valid identifier in the identifier column, or reading fails. The
+/// error says which line was malformed.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> anyhow::Result<()> {
+/// use cache_sim::csv::{parse_with, Format};
+/// use cache_sim::GeneralModelItem;
+///
+/// const DATA: &[u8] = b"timestamp;id
+/// 100;7
+/// 250;3";
+///
+/// let items = parse_with(DATA, Format::new(b';', 1, true))?;
+/// assert_eq!(items, vec![GeneralModelItem::new(7, 1.0, 1), GeneralModelItem::new(3, 1.0, 1)]);
+/// # Ok(())}
+/// ```
+pub fn parse_with<R: std::io::Read>(
+    input: R,
+    format: Format,
+) -> anyhow::Result<Vec<GeneralModelItem>> {
+    let mut rdr = ::csv::ReaderBuilder::new()
+        .delimiter(format.delimiter)
+        .has_headers(format.has_header)
+        .flexible(true)
+        .trim(::csv::Trim::All)
+        .from_reader(input);
+
+    rdr.records()
+        .map(|record| {
+            let record = record.context("couldn't read the trace")?;
+            let line = record.position().map_or(0, ::csv::Position::line);
+
+            let field = record.get(format.id_column).ok_or_else(|| {
+                anyhow!(
+                    "line {line}: expected an identifier in column {}, but there are only {} columns",
+                    format.id_column,
+                    record.len()
+                )
+            })?;
+            let uid = field
+                .parse()
+                .with_context(|| format!("line {line}: `{field}` is not a valid identifier"))?;
+
+            Ok(GeneralModelItem::new(uid, 1.0, 1))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::Item;
+
+    fn uids(items: &[GeneralModelItem]) -> Vec<String> {
+        items.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn single_column() -> anyhow::Result<()> {
+        let items = parse("1\n2\n1\n3".as_bytes())?;
+
+        assert_eq!(uids(&items), vec!["1", "2", "1", "3"]);
+        assert!(items
+            .iter()
+            .all(|item| item.size() == 1 && (item.cost() - 1.0).abs() < f64::EPSILON));
+        Ok(())
+    }
+
+    #[test]
+    fn multi_column() -> anyhow::Result<()> {
+        const DATA: &[u8] = b"10, 4
+20, 5
+30, 4";
+
+        let items = parse_with(DATA, Format::new(b',', 1, false))?;
+        assert_eq!(uids(&items), vec!["4", "5", "4"]);
+        Ok(())
+    }
+
+    #[test]
+    fn header() -> anyhow::Result<()> {
+        const DATA: &[u8] = b"id\ttimestamp
+4\t10
+5\t20";
+
+        let items = parse_with(DATA, Format::new(b'\t', 0, true))?;
+        assert_eq!(uids(&items), vec!["4", "5"]);
+
+        // without skipping it, the header is a malformed row
+        assert!(parse_with(DATA, Format::new(b'\t', 0, false)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn malformed_id() {
+        let err = parse("1\n2\nthree\n4".as_bytes()).expect_err("`three` isn't an identifier");
+        assert!(err.to_string().contains("line 3"), "{err}");
+    }
+
+    #[test]
+    fn missing_column() {
+        let err = parse_with("1,2\n3".as_bytes(), Format::new(b',', 1, false))
+            .expect_err("the second row has no second column");
+        assert!(err.to_string().contains("line 2"), "{err}");
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn gzip() -> anyhow::Result<()> {
+        use std::io::Write;
+
+        const DATA: &[u8] = b"timestamp,id
+10,4
+20,5
+30,4";
+        let format = Format::new(b',', 1, true);
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(DATA)?;
+        let compressed = encoder.finish()?;
+
+        assert_eq!(
+            parse_gz_with(compressed.as_slice(), format)?,
+            parse_with(DATA, format)?
+        );
+        Ok(())
+    }
+}
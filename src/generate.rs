@@ -0,0 +1,334 @@
+//! Generators for synthetic traces.
+//!
+//! Every generator takes a seed, so the same arguments always give the same trace.
+
+use anyhow::{ensure, Context};
+use approx::abs_diff_eq;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::Trace;
+
+/// Generate a trace of `length` accesses to items drawn from a Zipf distribution.
+///
+/// The items are `0..n_items`, and item `k` is accessed with probability proportional to
+/// `1 / (k + 1)^alpha`, so lower items are more popular; an `alpha` of zero is uniform.
+///
+/// ```
+/// use cache_sim::generate::zipf_trace;
+///
+/// let trace = zipf_trace(100, 1.0, 1000, 0);
+///
+/// assert_eq!(trace.len(), 1000);
+/// assert!(trace.iter().all(|&i| i < 100));
+/// assert_eq!(trace, zipf_trace(100, 1.0, 1000, 0));
+/// ```
+///
+/// # Panics
+///
+/// If `n_items` is zero, or `alpha` is negative or not finite.
+#[must_use]
+#[allow(clippy::cast_possible_truncation)] // the sampled indices are less than `n_items`
+pub fn zipf_trace(n_items: u32, alpha: f64, length: usize, seed: u64) -> Trace<u32> {
+    assert!(n_items > 0, "There must be at least one item.");
+    assert!(
+        alpha.is_finite() && alpha >= 0.0,
+        "The exponent must be finite and non-negative."
+    );
+
+    let weights = (1..=n_items).map(|rank| f64::from(rank).powf(-alpha));
+    let distribution = WeightedIndex::new(weights).expect("The weights are positive.");
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    (0..length)
+        .map(|_| distribution.sample(&mut rng) as u32)
+        .collect()
+}
+
+/// Generate a trace of `length` accesses from the LRU stack model.
+///
+/// The items `0..n_items` start in an LRU stack, with 0 on top. Each access draws a depth `d` from
+/// a geometric distribution, going one level deeper with probability `p`, so depth `d` has
+/// probability proportional to `p^d` (truncated at the bottom of the stack). The item at depth
+/// `d` is accessed and moved to the top, so once every item has been accessed, its stack distance
+/// is exactly `d`. Smaller values of `p` give more locality, and a `p` of one cycles through the
+/// items in order.
+///
+/// ```
+/// use cache_sim::generate::stack_model_trace;
+///
+/// let trace = stack_model_trace(10, 0.0, 5, 0);
+/// assert_eq!(trace, cache_sim::Trace::from(vec![0, 0, 0, 0, 0]));
+///
+/// let trace = stack_model_trace(3, 1.0, 5, 0);
+/// assert_eq!(trace, cache_sim::Trace::from(vec![2, 1, 0, 2, 1]));
+/// ```
+///
+/// # Panics
+///
+/// If `n_items` is zero, or `p` isn't in `[0, 1]`.
+#[must_use]
+pub fn stack_model_trace(n_items: u32, p: f64, length: usize, seed: u64) -> Trace<u32> {
+    assert!(n_items > 0, "There must be at least one item.");
+    assert!(
+        (0.0..=1.0).contains(&p),
+        "The probability must be in [0, 1]."
+    );
+
+    let mut stack = (0..n_items).collect::<Vec<_>>();
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    (0..length)
+        .map(|_| {
+            let mut depth = 0;
+            while depth + 1 < stack.len() && rng.gen_bool(p) {
+                depth += 1;
+            }
+
+            let item = stack.remove(depth);
+            stack.insert(0, item);
+            item
+        })
+        .collect()
+}
+
+/// Generate a trace of `length` accesses by walking a Markov chain over the items.
+///
+/// The items are the states of the chain, `0..transition.len()`, and `transition[i][j]` is the
+/// probability that an access to `i` is followed by an access to `j`. The trace begins with an
+/// access to `start`.
+///
+/// ```
+/// use cache_sim::generate::markov_trace;
+///
+/// // mostly stays put, but sometimes switches
+/// let transition = [vec![0.9, 0.1], vec![0.2, 0.8]];
+/// let trace = markov_trace(&transition, 0, 1000, 0)?;
+///
+/// assert_eq!(trace[0], 0);
+/// assert!(trace.iter().all(|&i| i < 2));
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+///
+/// # Errors
+///
+/// If the transition matrix isn't square, any probability is negative, any row doesn't sum to one,
+/// or `start` isn't one of the states.
+pub fn markov_trace(
+    transition: &[Vec<f64>],
+    start: usize,
+    length: usize,
+    seed: u64,
+) -> anyhow::Result<Trace<u32>> {
+    let n_states = transition.len();
+    ensure!(
+        start < n_states,
+        "start state {start} is not one of the {n_states} states"
+    );
+    ensure!(
+        u32::try_from(n_states).is_ok(),
+        "there are more states than items"
+    );
+
+    let rows = transition
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            ensure!(
+                row.len() == n_states,
+                "row {i} has {} entries, but there are {n_states} states",
+                row.len()
+            );
+            ensure!(
+                row.iter().all(|p| p.is_finite() && *p >= 0.0),
+                "row {i} has a probability which is negative or not finite"
+            );
+
+            let sum = row.iter().sum::<f64>();
+            ensure!(
+                abs_diff_eq!(sum, 1.0, epsilon = 1e-6),
+                "row {i} sums to {sum}, not 1"
+            );
+
+            WeightedIndex::new(row).with_context(|| format!("row {i} is not a distribution"))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut state = start;
+
+    Ok((0..length)
+        .map(|_| {
+            // we checked above that the states fit in a u32
+            #[allow(clippy::cast_possible_truncation)]
+            let item = state as u32;
+            state = rows[state].sample(&mut rng);
+            item
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NoCondition;
+
+    mod zipf {
+        use super::*;
+
+        #[test]
+        fn rank_frequency() {
+            let alpha = 0.8;
+            let frequencies = zipf_trace(50, alpha, 200_000, 1).frequency_histogram(&NoCondition);
+
+            // frequency should fall off as a power of the rank, relative to the most popular item
+            let top = f64::from(frequencies[&0]);
+            for item in [1, 2, 4, 9, 19] {
+                let expected = f64::from(item + 1).powf(-alpha);
+                let actual = f64::from(frequencies[&item]) / top;
+                assert!(
+                    (actual - expected).abs() < 0.05,
+                    "item {item}: {actual} vs {expected}"
+                );
+            }
+        }
+
+        #[test]
+        fn skewed() {
+            let frequencies = zipf_trace(10, 1.5, 10_000, 2).frequency_histogram(&NoCondition);
+            let most_popular = frequencies
+                .iter()
+                .max_by_key(|(_, &count)| count)
+                .map(|(&item, _)| item);
+
+            assert_eq!(most_popular, Some(0));
+        }
+
+        #[test]
+        fn seeded() {
+            assert_eq!(zipf_trace(10, 1.0, 100, 3), zipf_trace(10, 1.0, 100, 3));
+            assert_ne!(zipf_trace(10, 1.0, 100, 3), zipf_trace(10, 1.0, 100, 4));
+        }
+
+        #[test]
+        fn uniform() {
+            let frequencies = zipf_trace(4, 0.0, 40_000, 5).frequency_histogram(&NoCondition);
+            for item in 0..4 {
+                assert!((9_000..11_000).contains(&frequencies[&item]));
+            }
+        }
+    }
+
+    mod markov {
+        use super::*;
+
+        #[test]
+        fn alternating() -> anyhow::Result<()> {
+            let transition = [vec![0.0, 1.0], vec![1.0, 0.0]];
+            let trace = markov_trace(&transition, 0, 6, 0)?;
+
+            assert_eq!(trace, Trace::from(vec![0, 1, 0, 1, 0, 1]));
+            Ok(())
+        }
+
+        #[test]
+        fn absorbing() -> anyhow::Result<()> {
+            let transition = [
+                vec![0.0, 0.5, 0.5],
+                vec![0.0, 1.0, 0.0],
+                vec![0.0, 1.0, 0.0],
+            ];
+            let trace = markov_trace(&transition, 0, 10, 1)?;
+
+            assert_eq!(trace[0], 0);
+            assert!(trace.iter().skip(2).all(|&i| i == 1));
+            Ok(())
+        }
+
+        #[test]
+        fn empty() -> anyhow::Result<()> {
+            assert!(markov_trace(&[vec![1.0]], 0, 0, 0)?.is_empty());
+            Ok(())
+        }
+
+        macro_rules! invalid_test {
+            ($name:ident: $transition:expr, $start:expr) => {
+                #[test]
+                fn $name() {
+                    assert!(markov_trace(&$transition, $start, 10, 0).is_err());
+                }
+            };
+        }
+
+        invalid_test!(row_sum: [vec![0.5, 0.4], vec![0.5, 0.5]], 0);
+        invalid_test!(not_square: [vec![0.5, 0.5], vec![1.0]], 0);
+        invalid_test!(negative: [vec![1.5, -0.5], vec![0.5, 0.5]], 0);
+        invalid_test!(bad_start: [vec![0.5, 0.5], vec![0.5, 0.5]], 2);
+        invalid_test!(no_states: Vec::<Vec<f64>>::new(), 0);
+    }
+
+    mod stack_model {
+        use super::*;
+
+        /// The fraction of accesses with a finite stack distance less than 2.
+        fn short_fraction(trace: &Trace) -> f64 {
+            let distances = trace.stack_distances();
+            let finite = distances.inner().iter().flatten().collect::<Vec<_>>();
+            let short = finite.iter().filter(|&&&d| d < 2).count();
+
+            #[allow(clippy::cast_precision_loss)]
+            let fraction = short as f64 / finite.len() as f64;
+            fraction
+        }
+
+        #[test]
+        fn lower_p_more_locality() {
+            let fractions = [0.9, 0.6, 0.3, 0.1]
+                .into_iter()
+                .map(|p| short_fraction(&stack_model_trace(50, p, 20_000, 6)))
+                .collect::<Vec<_>>();
+
+            assert!(fractions.windows(2).all(|w| w[0] < w[1]), "{fractions:?}");
+        }
+
+        #[test]
+        fn geometric_depths() {
+            let p = 0.5;
+            let trace = stack_model_trace(100, p, 50_000, 7);
+            let (histogram, _) = trace.stack_distances().histogram();
+
+            // after the warm-up, depth d has probability (1 - p) p^d
+            let total = histogram.iter().sum::<usize>();
+            for (depth, &count) in histogram.iter().enumerate().take(4) {
+                #[allow(clippy::cast_precision_loss, clippy::cast_possible_wrap)]
+                let (actual, expected) = (
+                    count as f64 / total as f64,
+                    (1.0 - p) * p.powi(depth as i32),
+                );
+                assert!(
+                    (actual - expected).abs() < 0.02,
+                    "depth {depth}: {actual} vs {expected}"
+                );
+            }
+        }
+
+        #[test]
+        fn seeded() {
+            assert_eq!(
+                stack_model_trace(10, 0.5, 100, 3),
+                stack_model_trace(10, 0.5, 100, 3)
+            );
+            assert_ne!(
+                stack_model_trace(10, 0.5, 100, 3),
+                stack_model_trace(10, 0.5, 100, 4)
+            );
+        }
+
+        #[test]
+        #[should_panic(expected = "The probability must be in [0, 1].")]
+        fn bad_probability() {
+            let _ = stack_model_trace(10, 1.5, 10, 0);
+        }
+    }
+}
@@ -0,0 +1,408 @@
+//! Stores for the ghost lists of replacement policies like [`TwoQ`](crate::TwoQ),
+//! [`Arc`](crate::Arc), [`Car`](crate::Car), and [`S3Fifo`](crate::S3Fifo).
+//!
+//! A ghost list remembers the keys of recently evicted items, so a policy can tell when it evicted
+//! something too soon. Only keys are stored, but for huge key spaces even that adds up; a
+//! [`Bloom`] store bounds the memory used, at the cost of occasionally remembering a key which was
+//! never evicted.
+
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+
+use crate::hash::stable_hash;
+use crate::item::Item;
+
+/// A ghost list, remembering the keys of evicted items from oldest to newest.
+pub trait GhostStore<I: Item> {
+    /// Remember `item` as the newest ghost.
+    fn insert(&mut self, item: I);
+
+    /// Check whether `item` is a ghost.
+    fn contains(&self, item: &I) -> bool;
+
+    /// Forget `item`, returning whether it was a ghost.
+    fn remove(&mut self, item: &I) -> bool;
+
+    /// Get the number of ghosts.
+    fn len(&self) -> usize;
+
+    /// Check whether there are no ghosts.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forget the oldest ghosts until there are at most `len` left.
+    fn truncate(&mut self, len: usize);
+
+    /// Forget every ghost.
+    fn clear(&mut self);
+}
+
+/// A ghost list which remembers exactly the keys it's given, in a queue.
+#[derive(Debug, Clone)]
+pub struct Exact<I: Item> {
+    keys: VecDeque<I>,
+}
+
+impl<I: Item> Default for Exact<I> {
+    fn default() -> Self {
+        Self {
+            keys: VecDeque::new(),
+        }
+    }
+}
+
+impl<I: Item> Exact<I> {
+    /// Get the remembered keys, oldest first.
+    #[must_use]
+    pub const fn keys(&self) -> &VecDeque<I> {
+        &self.keys
+    }
+}
+
+impl<I: Item> GhostStore<I> for Exact<I> {
+    fn insert(&mut self, item: I) {
+        self.keys.push_back(item);
+    }
+
+    fn contains(&self, item: &I) -> bool {
+        self.keys.contains(item)
+    }
+
+    fn remove(&mut self, item: &I) -> bool {
+        self.keys
+            .iter()
+            .position(|i| i == item)
+            .and_then(|index| self.keys.remove(index))
+            .is_some()
+    }
+
+    fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    fn truncate(&mut self, len: usize) {
+        while self.keys.len() > len {
+            self.keys.pop_front();
+        }
+    }
+
+    fn clear(&mut self) {
+        self.keys.clear();
+    }
+}
+
+/// A single bloom filter, counting how many keys were inserted into it.
+#[derive(Debug, Clone)]
+struct BloomFilter {
+    bits: Vec<u64>,
+    len: usize,
+}
+
+impl BloomFilter {
+    fn new(bits: usize) -> Self {
+        Self {
+            bits: vec![0; bits.div_ceil(64)],
+            len: 0,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.bits.fill(0);
+        self.len = 0;
+    }
+}
+
+/// A ghost list backed by bloom filters, which uses a fixed amount of memory however many keys
+/// it's given.
+///
+/// Keys are inserted into the current generation's filter, and once it holds `expected` keys it
+/// becomes the previous generation, replacing the older one. So at least the `expected` newest
+/// ghosts are always remembered.
+///
+/// Bloom filters can't forget individual keys, so truncating only counts the oldest keys of the
+/// previous generation as forgotten. Once all of them are, it's dropped, and the current
+/// generation takes its place early. So after truncating to `len`, the store's length is `len`, but
+/// it can still find the forgotten keys of the previous generation. For a policy which forgets the
+/// oldest ghost as it inserts each new one, the generations end up about as large as its bound.
+///
+/// For the same reason, [`GhostStore::remove`] only decrements the length. This means a ghost can
+/// be found again after it's been removed, and a key which was never a ghost can be found with
+/// probability at most the false positive rate. Either way, the policy may spuriously promote an
+/// item it has no history of, so results are slightly different from an [`Exact`] store's.
+///
+/// ```
+/// use cache_sim::ghost::{Bloom, GhostStore};
+///
+/// let mut ghosts = Bloom::new(100, 0.01);
+/// for i in 0..100 {
+///     ghosts.insert(i);
+/// }
+///
+/// assert!((0..100).all(|i| ghosts.contains(&i)));
+/// let false_positives = (100..10_100).filter(|i| ghosts.contains(i)).count();
+/// assert!(false_positives < 100);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Bloom<I: Item> {
+    current: BloomFilter,
+    previous: BloomFilter,
+    /// How many of the oldest keys in `previous` have been truncated.
+    forgotten: usize,
+    expected: usize,
+    hashes: usize,
+    false_positive_rate: f64,
+    items: PhantomData<I>,
+}
+
+impl<I: Item> Default for Bloom<I> {
+    fn default() -> Self {
+        Self::new(1024, 0.01)
+    }
+}
+
+impl<I: Item> Bloom<I> {
+    /// Create an empty store whose generations hold `expected` keys each, with a false positive
+    /// rate of at most `false_positive_rate` for keys never inserted.
+    ///
+    /// Both generations are checked, so each filter is sized for a little under half the rate.
+    ///
+    /// # Panics
+    ///
+    /// If `expected` is zero, or `false_positive_rate` isn't strictly between 0 and 1.
+    #[must_use]
+    pub fn new(expected: usize, false_positive_rate: f64) -> Self {
+        assert!(expected > 0, "The filters must hold at least one key.");
+        assert!(
+            false_positive_rate > 0.0 && false_positive_rate < 1.0,
+            "The false positive rate must be in (0, 1)."
+        );
+
+        // a false positive in either filter is a false positive overall
+        let per_filter = 1.0 - (1.0 - false_positive_rate).sqrt();
+
+        #[allow(clippy::cast_precision_loss)] // the expected number of keys is small
+        let expected_f64 = expected as f64;
+        let ln2 = std::f64::consts::LN_2;
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // both are positive
+        let bits = (-expected_f64 * per_filter.ln() / (ln2 * ln2)).ceil() as usize;
+        #[allow(
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss,
+            clippy::cast_precision_loss
+        )]
+        let hashes = ((bits as f64 / expected_f64) * ln2).round().max(1.0) as usize;
+
+        Self {
+            current: BloomFilter::new(bits),
+            previous: BloomFilter::new(bits),
+            forgotten: 0,
+            expected,
+            hashes,
+            false_positive_rate,
+            items: PhantomData,
+        }
+    }
+
+    /// Get the configured bound on the false positive rate.
+    #[must_use]
+    pub const fn false_positive_rate(&self) -> f64 {
+        self.false_positive_rate
+    }
+
+    /// Get the bit of `filter` which the `hash`th hash function maps `item` to, as a word index
+    /// and mask.
+    fn bit(filter: &BloomFilter, hash: usize, item: &I) -> (usize, u64) {
+        #[allow(clippy::cast_possible_truncation)] // the result is less than the number of bits
+        let bit = (stable_hash(&(hash, item)) % (filter.bits.len() as u64 * 64)) as usize;
+        (bit / 64, 1 << (bit % 64))
+    }
+
+    fn filter_contains(&self, filter: &BloomFilter, item: &I) -> bool {
+        (0..self.hashes).all(|hash| {
+            let (word, mask) = Self::bit(filter, hash, item);
+            filter.bits[word] & mask != 0
+        })
+    }
+}
+
+impl<I: Item> GhostStore<I> for Bloom<I> {
+    fn insert(&mut self, item: I) {
+        if self.current.len >= self.expected {
+            std::mem::swap(&mut self.current, &mut self.previous);
+            self.current.clear();
+            self.forgotten = 0;
+        }
+
+        for hash in 0..self.hashes {
+            let (word, mask) = Self::bit(&self.current, hash, &item);
+            self.current.bits[word] |= mask;
+        }
+        self.current.len += 1;
+    }
+
+    fn contains(&self, item: &I) -> bool {
+        self.filter_contains(&self.current, item) || self.filter_contains(&self.previous, item)
+    }
+
+    fn remove(&mut self, item: &I) -> bool {
+        if self.filter_contains(&self.current, item) {
+            self.current.len = self.current.len.saturating_sub(1);
+            true
+        } else if self.filter_contains(&self.previous, item) {
+            self.previous.len = self.previous.len.saturating_sub(1);
+            self.forgotten = self.forgotten.min(self.previous.len);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.current.len + self.previous.len - self.forgotten
+    }
+
+    fn truncate(&mut self, len: usize) {
+        if self.len() <= len {
+            return;
+        }
+
+        self.forgotten += self.len() - len;
+        // the forgotten keys can spill over into the current generation, which then becomes the
+        // previous one
+        while self.forgotten > 0 && self.forgotten >= self.previous.len {
+            self.forgotten -= self.previous.len;
+            self.previous.clear();
+            std::mem::swap(&mut self.current, &mut self.previous);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.current.clear();
+        self.previous.clear();
+        self.forgotten = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod exact {
+        use super::*;
+
+        #[test]
+        fn queue() {
+            let mut ghosts = Exact::default();
+            for i in 0..5 {
+                ghosts.insert(i);
+            }
+
+            assert!(ghosts.remove(&2));
+            assert!(!ghosts.remove(&2));
+            assert_eq!(ghosts.len(), 4);
+
+            // the oldest go first
+            ghosts.truncate(2);
+            assert!(!ghosts.contains(&0) && !ghosts.contains(&1));
+            assert!(ghosts.contains(&3) && ghosts.contains(&4));
+        }
+    }
+
+    mod bloom {
+        use super::*;
+
+        #[allow(clippy::cast_precision_loss)]
+        fn false_positive_fraction(
+            ghosts: &Bloom<u32>,
+            never_inserted: std::ops::Range<u32>,
+        ) -> f64 {
+            let len = never_inserted.len() as f64;
+            never_inserted.filter(|i| ghosts.contains(i)).count() as f64 / len
+        }
+
+        #[test]
+        fn false_positive_rate_bounded() {
+            for (expected, rate) in [(100, 0.05), (1000, 0.01), (5000, 0.001)] {
+                let mut ghosts = Bloom::new(expected, rate);
+
+                // fill both generations
+                for i in 0..2 * expected as u32 {
+                    ghosts.insert(i);
+                }
+
+                let fraction = false_positive_fraction(&ghosts, 1_000_000..1_200_000);
+                assert!(fraction <= rate, "{fraction} > {rate}");
+            }
+        }
+
+        #[test]
+        fn remembers_newest_generation() {
+            let mut ghosts = Bloom::new(50, 0.01);
+            for i in 0..175 {
+                ghosts.insert(i);
+            }
+
+            // generations rotate every 50 keys, so the last 75 are all remembered
+            assert!((100..175).all(|i| ghosts.contains(&i)));
+            assert_eq!(ghosts.len(), 75);
+        }
+
+        #[test]
+        fn truncation() {
+            let mut ghosts = Bloom::new(10, 0.01);
+            for i in 0..15 {
+                ghosts.insert(i);
+            }
+
+            // the previous generation is only partly forgotten, so all of it is still found
+            ghosts.truncate(12);
+            assert_eq!(ghosts.len(), 12);
+            assert!((0..15).all(|i| ghosts.contains(&i)));
+
+            // and then all of it
+            ghosts.truncate(5);
+            assert_eq!(ghosts.len(), 5);
+            assert!((10..15).all(|i| ghosts.contains(&i)));
+
+            ghosts.truncate(4);
+            assert_eq!(ghosts.len(), 4);
+            assert!((10..15).all(|i| ghosts.contains(&i)));
+
+            ghosts.truncate(0);
+            assert!(ghosts.is_empty());
+            assert!(false_positive_fraction(&ghosts, 0..15) == 0.0);
+        }
+
+        #[test]
+        fn truncating_one_at_a_time() {
+            // like a policy bounding its ghosts, which forgets the oldest whenever there are too
+            // many, well before a generation fills up
+            let mut ghosts = Bloom::new(1000, 0.01);
+            for i in 0..200_u32 {
+                ghosts.insert(i);
+                if ghosts.len() > 20 {
+                    ghosts.truncate(ghosts.len() - 1);
+                }
+
+                assert_eq!(ghosts.len(), 20.min(i as usize + 1));
+                assert!((i.saturating_sub(19)..=i).all(|j| ghosts.contains(&j)));
+            }
+
+            // the generations are sized by the bound, so the older keys are forgotten
+            assert!(false_positive_fraction(&ghosts, 0..150) < 0.05);
+        }
+
+        #[test]
+        fn removal_only_counts() {
+            let mut ghosts = Bloom::new(10, 0.01);
+            ghosts.insert(3);
+
+            assert!(ghosts.remove(&3));
+            assert!(ghosts.is_empty());
+            // the filter can't forget it
+            assert!(ghosts.contains(&3));
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! A hash function with a fixed algorithm, for results which have to be reproducible.
+//!
+//! The standard library's `DefaultHasher` is deterministic, but its algorithm is unspecified and
+//! may change between Rust releases, so anything which depends on the actual hash values, like
+//! which items a sketch confuses, uses [`StableHasher`] instead.
+
+use std::hash::{Hash, Hasher};
+
+/// FNV-1a, with the result passed through the `SplitMix64` finalizer so that every bit of it is
+/// well mixed, even for short keys.
+///
+/// Integers are hashed as little-endian bytes, so the results are the same on every platform.
+#[derive(Debug, Clone, Copy)]
+pub struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        self.write(&[i]);
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// Hash `value` with a [`StableHasher`].
+pub fn stable_hash<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = StableHasher::default();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_values() {
+        // these pin the algorithm, so changing it by accident breaks reproducibility loudly
+        assert_eq!(stable_hash(&0_u32), 12_440_867_094_808_227_077);
+        assert_eq!(stable_hash(&(1_usize, 7_u32)), 17_380_239_434_728_566_975);
+    }
+
+    #[test]
+    fn spreads_low_bits() {
+        let buckets = (0..1000_u32).map(|i| stable_hash(&i) % 16);
+        let mut counts = [0; 16];
+        for bucket in buckets {
+            counts[bucket as usize] += 1;
+        }
+
+        // 62.5 expected per bucket
+        assert!(
+            counts.iter().all(|&count| (35..90).contains(&count)),
+            "{counts:?}"
+        );
+    }
+}
@@ -0,0 +1,249 @@
+//! A hierarchy of caches, e.x. L1 and L2 caches in front of memory.
+
+use std::collections::HashSet;
+
+use crate::item::Item;
+use crate::replacement_policy::ReplacementPolicy;
+use crate::stats::Stat;
+use crate::Cache;
+
+/// How the contents of the levels of a [`CacheHierarchy`] relate to each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Inclusion {
+    /// Every item in a level is also in all the levels below it.
+    ///
+    /// Items are inserted into every level they missed in, and evicting an item from a level
+    /// invalidates it in all the levels above.
+    Inclusive,
+    /// Each item is in at most one level.
+    ///
+    /// Items are only inserted into the first level; hits in lower levels move the item up to the
+    /// first level, and items evicted from a level move down to the next.
+    Exclusive,
+    /// Non-inclusive, non-exclusive.
+    ///
+    /// Items are inserted into every level they missed in, like [`Inclusion::Inclusive`], but
+    /// evictions don't invalidate anything.
+    Nine,
+}
+
+/// The items evicted by the most recent access to a cache.
+#[derive(Default)]
+struct LastEvictions<I: Item>(HashSet<I>);
+
+impl<I: Item> Stat<I> for LastEvictions<I> {
+    fn update(&mut self, _: &HashSet<I>, _: I, to_be_evicted: &HashSet<I>) {
+        self.0.clone_from(to_be_evicted);
+    }
+}
+
+type Level<I> = Cache<Box<dyn ReplacementPolicy<I>>, LastEvictions<I>, I>;
+
+/// A hierarchy of caches, each of which is consulted in order until one of them hits.
+///
+/// Each level can have its own replacement policy and capacity.
+///
+/// ```
+/// use cache_sim::{CacheHierarchy, Inclusion, Lru};
+///
+/// let mut h = CacheHierarchy::new(Inclusion::Inclusive);
+/// h.push_level(Lru::default(), 1);
+/// h.push_level(Lru::default(), 2);
+///
+/// assert_eq!(h.access(0), None); // missed everywhere, so it went to memory
+/// assert_eq!(h.access(1), None);
+/// assert_eq!(h.access(0), Some(1)); // 0 was evicted from L1, but it's still in L2
+/// assert_eq!(h.access(0), Some(0));
+///
+/// assert_eq!(h.hits(), &[1, 1]);
+/// assert_eq!(h.misses(), 2);
+/// ```
+pub struct CacheHierarchy<I: Item = u32> {
+    levels: Vec<Level<I>>,
+    inclusion: Inclusion,
+    hits: Vec<u64>,
+    misses: u64,
+}
+
+impl<I: Item> CacheHierarchy<I> {
+    /// Create a hierarchy with no levels.
+    #[must_use]
+    pub const fn new(inclusion: Inclusion) -> Self {
+        Self {
+            levels: Vec::new(),
+            inclusion,
+            hits: Vec::new(),
+            misses: 0,
+        }
+    }
+
+    /// Add a level below all the existing levels.
+    pub fn push_level(&mut self, policy: impl ReplacementPolicy<I> + 'static, capacity: u32) {
+        let policy: Box<dyn ReplacementPolicy<I>> = Box::new(policy);
+        self.levels
+            .push(Cache::with_replacement_policy(policy, capacity));
+        self.hits.push(0);
+    }
+
+    /// Access `item`, returning the index of the level which served it, or `None` if it missed in
+    /// every level.
+    pub fn access(&mut self, item: I) -> Option<usize> {
+        let served = self.levels.iter().position(|level| level.contains(&item));
+
+        if let Some(level) = served {
+            self.hits[level] += 1;
+        } else {
+            self.misses += 1;
+        }
+
+        match self.inclusion {
+            Inclusion::Inclusive | Inclusion::Nine => {
+                // fill from the bottom up, so that invalidations from lower levels happen before
+                // the item is inserted into the levels above
+                let filled = served.map_or(self.levels.len(), |level| level + 1);
+                for level in (0..filled).rev() {
+                    self.levels[level].access(item);
+
+                    if self.inclusion == Inclusion::Inclusive {
+                        let evicted = self.levels[level].stat().0.clone();
+                        for above in &mut self.levels[..level] {
+                            for evicted in &evicted {
+                                above.remove(evicted);
+                            }
+                        }
+                    }
+                }
+            }
+            Inclusion::Exclusive => {
+                if let Some(level) = served.filter(|&level| level > 0) {
+                    self.levels[level].remove(&item);
+                }
+
+                // cascade evictions down the hierarchy
+                let mut incoming = HashSet::from([item]);
+                for level in &mut self.levels {
+                    let mut evicted = HashSet::new();
+                    for item in incoming {
+                        level.access(item);
+                        evicted.extend(level.stat().0.iter().copied());
+                    }
+                    incoming = evicted;
+                }
+            }
+        }
+
+        served
+    }
+
+    /// Get the number of hits served by each level.
+    #[must_use]
+    pub fn hits(&self) -> &[u64] {
+        &self.hits
+    }
+
+    /// Get the number of accesses which missed in every level.
+    #[must_use]
+    pub const fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Get a reference to the set of items in a level.
+    ///
+    /// # Panics
+    ///
+    /// If there is no such level.
+    #[must_use]
+    pub fn level(&self, index: usize) -> &HashSet<I> {
+        self.levels[index].set()
+    }
+
+    /// Get the number of levels in the hierarchy.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Check whether the hierarchy has no levels.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.levels.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Lru;
+
+    fn hierarchy(inclusion: Inclusion) -> CacheHierarchy {
+        let mut h = CacheHierarchy::new(inclusion);
+        h.push_level(Lru::default(), 2);
+        h.push_level(Lru::default(), 3);
+        h
+    }
+
+    #[test]
+    fn inclusive_l2_hit_populates_l1() {
+        let mut h = hierarchy(Inclusion::Inclusive);
+
+        for i in [0, 1, 2] {
+            assert_eq!(h.access(i), None);
+        }
+
+        // 0 was evicted from L1 but not L2
+        assert_eq!(h.level(0), &HashSet::from([1, 2]));
+        assert_eq!(h.access(0), Some(1));
+        assert_eq!(h.level(0), &HashSet::from([0, 2]));
+        assert_eq!(h.level(1), &HashSet::from([0, 1, 2]));
+
+        assert_eq!(h.access(0), Some(0));
+        assert_eq!(h.hits(), &[1, 1]);
+        assert_eq!(h.misses(), 3);
+    }
+
+    #[test]
+    fn inclusive_back_invalidation() {
+        let mut h = hierarchy(Inclusion::Inclusive);
+
+        for i in [0, 1, 2, 0, 0, 3] {
+            h.access(i);
+        }
+
+        // L2 evicted 1, which had to be invalidated in L1 even though L1 would have kept it
+        for i in [0, 1, 2, 3] {
+            h.access(i);
+            assert!(h.level(0).is_subset(h.level(1)));
+        }
+    }
+
+    #[test]
+    fn exclusive() {
+        let mut h = hierarchy(Inclusion::Exclusive);
+
+        for i in [0, 1, 2] {
+            assert_eq!(h.access(i), None);
+        }
+
+        // 0 was moved down to L2 when it was evicted from L1
+        assert_eq!(h.level(0), &HashSet::from([1, 2]));
+        assert_eq!(h.level(1), &HashSet::from([0]));
+
+        // and a hit in L2 moves it back up, pushing 1 down
+        assert_eq!(h.access(0), Some(1));
+        assert_eq!(h.level(0), &HashSet::from([0, 2]));
+        assert_eq!(h.level(1), &HashSet::from([1]));
+    }
+
+    #[test]
+    fn nine() {
+        let mut h = hierarchy(Inclusion::Nine);
+
+        // hits in L1 don't touch L2, so L2 evicts 0 while L1 keeps it
+        for i in [0, 1, 0, 2, 0, 3] {
+            h.access(i);
+        }
+
+        assert!(h.level(0).contains(&0));
+        assert!(!h.level(1).contains(&0));
+    }
+}
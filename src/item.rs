@@ -1,6 +1,53 @@
 //! An abstracted cacheable item.
 
 /// Abstracts over a single item in a cache.
+///
+/// Caches, traces, and statistics are generic over their items, so you can simulate with your own
+/// item type. It needs:
+///
+/// - `Copy`, since caches and replacement policies pass items around by value;
+/// - `Eq` and `Hash`, since caches keep their items in hash sets, so items must be equal exactly
+///   when they're the same cached object;
+/// - `Display` and `Debug`, for printing traces, caches, and histograms;
+/// - `Default`, for statistics which need a placeholder item;
+///
+/// as well as a [`cost`](Item::cost) and [`size`](Item::size). Items with unit cost and size,
+/// like `u32`, can implement [`UnitItem`] instead, which provides those.
+///
+/// ```
+/// # use std::collections::HashSet;
+/// use std::fmt;
+///
+/// use cache_sim::item::UnitItem;
+/// use cache_sim::stats::HitCount;
+/// use cache_sim::{Cache, Lru, Trace};
+///
+/// #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+/// struct Access {
+///     address: u64,
+///     tenant: u16,
+/// }
+///
+/// impl fmt::Display for Access {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         write!(f, "{}@{:#x}", self.tenant, self.address)
+///     }
+/// }
+///
+/// impl UnitItem for Access {}
+///
+/// let a = Access { address: 0x10, tenant: 0 };
+/// let b = Access { address: 0x10, tenant: 1 }; // same address, but a different tenant
+/// let c = Access { address: 0x20, tenant: 0 };
+///
+/// let trace = Trace::from(vec![a, b, a, c, a]);
+/// let mut cache = Cache::<Lru<Access>, HitCount, Access>::new(2);
+/// cache.run_trace(&trace);
+///
+/// assert_eq!(cache.set(), &HashSet::from([a, c]));
+/// assert_eq!(cache.stat().0, 2);
+/// assert_eq!(trace.stack_distances().histogram(), (vec![0, 2], 3));
+/// ```
 pub trait Item:
     Default + std::fmt::Debug + std::fmt::Display + PartialEq + Eq + Copy + Clone + std::hash::Hash
 {
@@ -12,17 +59,69 @@ pub trait Item:
     // allow floats
     /// The size of the item in the cache.
     fn size(&self) -> u32;
+
+    /// Whether this access to the item is a write, rather than a read.
+    ///
+    /// Only a write-back cache cares, since it has to write dirty items back when it evicts them;
+    /// see [`WritePolicy`](crate::WritePolicy). By default every access is a read.
+    fn is_write(&self) -> bool {
+        false
+    }
+}
+
+/// An item with unit cost and size, as in the paging model.
+///
+/// Implementing this is the easiest way to make your own [`Item`]: there's a blanket
+/// implementation of `Item` for every `UnitItem`, so with the bounds derived all you need is an
+/// empty impl.
+#[allow(clippy::module_name_repetitions)]
+pub trait UnitItem:
+    Default + std::fmt::Debug + std::fmt::Display + PartialEq + Eq + Copy + Clone + std::hash::Hash
+{
 }
 
-impl Item for u32 {
+impl<T: UnitItem> Item for T {
     fn cost(&self) -> f64 {
         1.0
     }
+
     fn size(&self) -> u32 {
         1
     }
 }
 
+impl UnitItem for u32 {}
+
+/// An item with a natural successor, like the next block of a file or of memory.
+///
+/// This is what sequential prefetching with [`Prefetch`](crate::Prefetch) brings in.
+pub trait Sequential: Item {
+    /// The item after this one, if there is one.
+    fn next(&self) -> Option<Self>;
+}
+
+impl Sequential for u32 {
+    fn next(&self) -> Option<Self> {
+        self.checked_add(1)
+    }
+}
+
+/// An item which can be placed in one set of a
+/// [`SetAssociativeCache`](crate::SetAssociativeCache).
+pub trait SetIndex: Item {
+    /// The set this item belongs in, out of `sets`.
+    ///
+    /// This must be less than `sets`, and the same every time for the same item.
+    fn set_index(&self, sets: usize) -> usize;
+}
+
+// like a hardware cache indexing by the low bits of the address
+impl SetIndex for u32 {
+    fn set_index(&self, sets: usize) -> usize {
+        *self as usize % sets
+    }
+}
+
 /// A cacheable item with arbitrary const cost and size.
 ///
 /// We implement Hash and Eq by hand to allow floating point costs and sizes. They are simple,
@@ -31,10 +130,13 @@ impl Item for u32 {
 /// will not work correctly.
 #[allow(clippy::module_name_repetitions)]
 #[derive(Default, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GeneralModelItem {
     uid: u32,
     cost: f64,
     size: u32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    write: bool,
 }
 
 impl GeneralModelItem {
@@ -43,7 +145,27 @@ impl GeneralModelItem {
     /// If you don't care about the unique identifier, prefer using a [`GeneralModelGenerator`].
     #[must_use]
     pub const fn new(uid: u32, cost: f64, size: u32) -> Self {
-        Self { uid, cost, size }
+        Self {
+            uid,
+            cost,
+            size,
+            write: false,
+        }
+    }
+
+    /// Mark this access to the item as a write, or as a read.
+    ///
+    /// The item is still equal to any other access with the same identifier.
+    #[must_use]
+    pub const fn with_write(mut self, write: bool) -> Self {
+        self.write = write;
+        self
+    }
+
+    /// Get the unique identifier.
+    #[must_use]
+    pub const fn uid(&self) -> u32 {
+        self.uid
     }
 }
 
@@ -61,6 +183,19 @@ impl std::cmp::PartialEq for GeneralModelItem {
 
 impl std::cmp::Eq for GeneralModelItem {}
 
+// items are ordered by identifier, for consistency with equality
+impl std::cmp::PartialOrd for GeneralModelItem {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::cmp::Ord for GeneralModelItem {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.uid.cmp(&other.uid)
+    }
+}
+
 impl std::fmt::Display for GeneralModelItem {
 	
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -84,6 +219,44 @@ impl Item for GeneralModelItem {
     fn size(&self) -> u32 {
         self.size
     }
+
+    fn is_write(&self) -> bool {
+        self.write
+    }
+}
+
+impl SetIndex for GeneralModelItem {
+    fn set_index(&self, sets: usize) -> usize {
+        self.uid.set_index(sets)
+    }
+}
+
+/// An item's cost, as a key for histograms.
+///
+/// Costs are floats, so they can't be hashed directly; this compares them bitwise instead, which
+/// buckets together exactly the costs which came from the same value. Computed costs may not
+/// match: `Cost(0.1 + 0.2)` and `Cost(0.3)` are different buckets.
+#[derive(Debug, Clone, Copy)]
+pub struct Cost(pub f64);
+
+impl std::hash::Hash for Cost {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state);
+    }
+}
+
+impl std::cmp::PartialEq for Cost {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_bits() == other.0.to_bits()
+    }
+}
+
+impl std::cmp::Eq for Cost {}
+
+impl std::fmt::Display for Cost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
 }
 
 /// A generator for general model items.
@@ -96,11 +269,7 @@ pub struct GeneralModelGenerator {
 
 impl GeneralModelGenerator {
     pub fn item(&mut self, cost: f64, size: u32) -> GeneralModelItem {
-        let ret = GeneralModelItem {
-            uid: self.counter,
-            cost,
-            size,
-        };
+        let ret = GeneralModelItem::new(self.counter, cost, size);
         self.counter += 1;
         ret
     }
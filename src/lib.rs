@@ -1,17 +1,33 @@
 #![doc = include_str!("../README.md")]
 
 pub mod atf;
+pub mod bench_support;
 mod cache;
 pub mod condition;
+pub mod csv;
+pub mod generate;
+pub mod ghost;
+mod hash;
+pub mod hierarchy;
 pub mod item;
 pub mod output;
 pub mod replacement_policy;
+pub mod set_associative;
+pub mod sim;
 pub mod stats;
+pub mod test_utils;
 pub mod trace;
+pub mod vscsi;
 
-pub use cache::Cache;
-pub use condition::{LastNItems, NoCondition};
+pub use cache::{AccessResult, Cache, CacheBuilder, CacheState, WritePolicy};
+pub use condition::{LastNItems, NoCondition, PrefixCondition};
+pub use hierarchy::{CacheHierarchy, Inclusion};
 pub use item::{GeneralModelGenerator, GeneralModelItem};
+pub use set_associative::SetAssociativeCache;
 pub use trace::Trace;
 
-pub use replacement_policy::{Fifo, Landlord, Lfu, Lru, Mru, Rand};
+pub use replacement_policy::{
+    Arc, Belady, Car, Clock, ClockPro, Fbr, Fifo, Gdsf, Hyperbolic, Landlord, Lfu, Lfuda, Lirs,
+    Lrfu, Lru, LruK, Mq, Mru, Prefetch, Rand, S3Fifo, ScanResistantLru, Sieve, Slru, SmallestId,
+    TinyLfu, TwoQ, WTinyLfu,
+};
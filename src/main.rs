@@ -29,7 +29,7 @@ fn main() -> anyhow::Result<()> {
     );
     
     for item in trace.iter().unique().copied().collect::<Vec<_>>(){
-		let name = format!("After{}",item.to_string());
+		let name = format!("After{}",item);
 		conditions.insert(
         name,
         Box::new(LastNItems::new(vec![item])),
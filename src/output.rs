@@ -60,6 +60,65 @@ pub fn to_csv(
     wtr.serialize(output)
 }
 
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+struct OutputJson<'a, T> {
+    name: &'a str,
+    stats: &'a [T],
+    stack_distances: StackDistanceJson,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+struct StackDistanceJson {
+    histogram: Vec<usize>,
+    infinities: usize,
+}
+
+/// Write a set of statistics to a writer as a json object.
+///
+/// The object has the `name`, the `stats` array, and the `stack_distances` histogram, as an
+/// object with the `histogram` array of frequencies and the count of `infinities`.
+///
+/// ```
+/// # fn main() -> serde_json::Result<()> {
+/// use cache_sim::output::to_json;
+/// use cache_sim::Trace;
+///
+/// let distances = Trace::from(vec![0, 0, 1, 0]).stack_distances();
+///
+/// let mut out = Vec::new();
+/// to_json("example", &[3, 1], &distances, &mut out)?;
+///
+/// assert_eq!(
+///     String::from_utf8(out).unwrap(),
+///     r#"{"name":"example","stats":[3,1],"stack_distances":{"histogram":[1,1],"infinities":2}}"#
+/// );
+/// # Ok(())}
+/// ```
+///
+/// # Errors
+/// If writing fails.
+#[cfg(feature = "serde")]
+pub fn to_json<T: Serialize, W: Write>(
+    name: &str,
+    stats: &[T],
+    stack_distances: &StackDistance,
+    writer: W,
+) -> Result<(), serde_json::Error> {
+    let (histogram, infinities) = stack_distances.histogram();
+    let output = OutputJson {
+        name,
+        stats,
+        stack_distances: StackDistanceJson {
+            histogram,
+            infinities,
+        },
+    };
+
+    serde_json::to_writer(writer, &output)
+}
+
 struct FreqHistRow<'a, I: Item, H: std::hash::BuildHasher> {
     // TODO: does this need to be owned
     name: &'a str,
@@ -145,3 +204,41 @@ pub fn write_header<W: Write>(
 
     wtr.serialize(output)
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    mod json {
+        use super::*;
+        use crate::Trace;
+
+        #[test]
+        fn schema() -> serde_json::Result<()> {
+            let distances = Trace::from(vec![0, 0, 1, 0, 3, 0, 1]).stack_distances();
+            let stats = [0.1 + 0.2, 1.0 / 3.0, 1e300];
+
+            let mut out = Vec::new();
+            to_json("doc example", &stats, &distances, &mut out)?;
+            let json: serde_json::Value = serde_json::from_slice(&out)?;
+
+            assert_eq!(json["name"], "doc example");
+
+            // floats have to round trip exactly
+            let parsed_stats = json["stats"]
+                .as_array()
+                .expect("stats is an array")
+                .iter()
+                .map(|stat| stat.as_f64().expect("stats are numbers"))
+                .collect::<Vec<_>>();
+            assert_eq!(parsed_stats, stats);
+
+            assert_eq!(
+                json["stack_distances"]["histogram"],
+                serde_json::json!([1, 2, 1])
+            );
+            assert_eq!(json["stack_distances"]["infinities"], 3);
+            Ok(())
+        }
+    }
+}
@@ -1,25 +1,162 @@
 //! Implementations of cache replacement policies.
+//!
+//! Since items can have different sizes, every policy keeps evicting until the new item fits.
+//! ARC, CAR, CLOCK-Pro, 2Q, LIRS, and S3-FIFO are defined in terms of numbers of items, so their
+//! queue and ghost list bounds count items whatever their sizes, but they still evict as many
+//! items as it takes to fit a large one.
 
-use crate::item::{GeneralModelItem, Item};
+use crate::ghost::{Exact, GhostStore};
+use crate::hash::stable_hash;
+use crate::item::{GeneralModelItem, Item, Sequential};
+use crate::trace::Trace;
+use std::collections::hash_map::RandomState;
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::BuildHasher;
 
 use approx::abs_diff_eq;
-use rand::seq::IteratorRandom;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
 /// An abstracted cache replacement policy.
-pub trait ReplacementPolicy<I: Item> {
+///
+/// The cache calls [`update_state`](ReplacementPolicy::update_state) or
+/// [`replace`](ReplacementPolicy::replace) once for every access, except to items larger than its
+/// capacity. Those bypass the cache without the policy seeing them, apart from being asked what to
+/// [`prefetch`](ReplacementPolicy::prefetch), so a policy which follows the trace, like [`Belady`],
+/// can't count on seeing all of it.
+pub trait ReplacementPolicy<I: Item, H: BuildHasher = RandomState> {
     /// Update the replacement policy's state, without evicting an item.
-    fn update_state(&mut self, set: &HashSet<I>, capacity: u32, next: I);
+    fn update_state(&mut self, set: &HashSet<I, H>, capacity: u32, next: I);
+
+    /// Return the items to be evicted. This should _not_ contain `next`, unless the policy refuses
+    /// to admit `next` to the cache, in which case `next` bypasses the cache; the rest of the
+    /// returned items are still evicted.
+    fn replace(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I>;
+
+    /// Return the items [`replace`](ReplacementPolicy::replace) would evict, without changing
+    /// the policy's state, so an admission policy like [`TinyLfu`] can decide whether to go
+    /// through with it.
+    ///
+    /// By default this replaces on a copy of the policy; policies which can find their victims
+    /// directly should override it.
+    fn victims(&self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I>
+    where
+        Self: Clone,
+    {
+        self.clone().replace(set, capacity, next)
+    }
+
+    /// Return the items to be evicted so that the rest of `set` fits in `capacity`, because the
+    /// cache is shrinking.
+    fn shrink(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I>;
+
+    /// Forget about `item`, which has been removed from the cache without being evicted.
+    fn remove(&mut self, item: I);
+
+    /// Forget every item, returning to the state the policy was constructed in, but keeping its
+    /// parameters.
+    fn clear(&mut self);
+
+    /// Return the items to bring into the cache after a miss on `next`, as if they were accessed
+    /// right after it.
+    ///
+    /// Most policies don't prefetch anything; wrap one in [`Prefetch`] to prefetch sequentially.
+    fn prefetch(&mut self, _next: I) -> Vec<I> {
+        Vec::new()
+    }
+}
+
+/// A replacement policy whose state can be saved and restored, for
+/// [`Cache::snapshot`](crate::Cache::snapshot).
+///
+/// Every [`Clone`] type is its own snapshot, which covers all the policies here; type-erased
+/// policies can't be cloned, so they'd need to implement this by hand.
+pub trait Snapshot {
+    /// The saved state.
+    type State;
+
+    /// Save the current state.
+    fn snapshot(&self) -> Self::State;
+
+    /// Return to a saved state, so the policy makes the same decisions it would have made then.
+    fn restore(&mut self, state: Self::State);
+}
+
+impl<T: Clone> Snapshot for T {
+    type State = Self;
+
+    fn snapshot(&self) -> Self {
+        self.clone()
+    }
 
-    /// Return the item to be evicted. This should _not_ be `next`.
-    fn replace(&mut self, set: &HashSet<I>, capacity: u32, next: I) -> HashSet<I>;
+    fn restore(&mut self, state: Self) {
+        *self = state;
+    }
 }
 
-pub trait Tiebreaker<I: Item>: ReplacementPolicy<I> {
+pub trait Tiebreaker<I: Item, H: BuildHasher = RandomState>: ReplacementPolicy<I, H> {
     /// Pick a single item to evict.
     fn tiebreak(&mut self, from: &HashSet<I>, size_to_free: u32) -> HashSet<I>;
 }
 
+/// Get the total size of the items in `set` which aren't in `to_evict`.
+///
+/// Replacement policies have to keep evicting until this fits, since a single eviction might not
+/// free enough space for a large item. On a miss, that means fitting in the capacity minus the
+/// size of the new item, which the cache guarantees is no larger than the capacity.
+fn remaining_size<I: Item, H: BuildHasher>(set: &HashSet<I, H>, to_evict: &HashSet<I>) -> u32 {
+    set.iter()
+        .filter(|i| !to_evict.contains(*i))
+        .map(Item::size)
+        .sum()
+}
+
+/// Add the items chosen by `evict` to `to_evict` until `next` fits alongside the rest of `set`.
+///
+/// ARC, CAR, CLOCK-Pro, 2Q, LIRS, and S3-FIFO choose one victim per miss, since their algorithms
+/// count items, but that might not free enough space for a large item; this keeps going in the
+/// same way. If `next` itself is chosen, it bypasses the cache, so nothing more is needed.
+fn evict_until_fits<I: Item, H: BuildHasher>(
+    set: &HashSet<I, H>,
+    capacity: u32,
+    next: I,
+    mut to_evict: HashSet<I>,
+    mut evict: impl FnMut() -> Option<I>,
+) -> HashSet<I> {
+    while remaining_size(set, &to_evict) + next.size() > capacity && !to_evict.contains(&next) {
+        to_evict.extend(evict());
+    }
+    to_evict
+}
+
+// we need to implement this so that caches can be type-erased over their replacement policy; see
+// `CacheHierarchy` for a motivating example.
+impl<I: Item, H: BuildHasher> ReplacementPolicy<I, H> for Box<dyn ReplacementPolicy<I, H>> {
+    fn update_state(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) {
+        self.as_mut().update_state(set, capacity, next);
+    }
+
+    fn replace(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I> {
+        self.as_mut().replace(set, capacity, next)
+    }
+
+    fn shrink(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        self.as_mut().shrink(set, capacity)
+    }
+
+    fn remove(&mut self, item: I) {
+        self.as_mut().remove(item);
+    }
+
+    fn clear(&mut self) {
+        self.as_mut().clear();
+    }
+
+    fn prefetch(&mut self, next: I) -> Vec<I> {
+        self.as_mut().prefetch(next)
+    }
+}
+
 /// The LRU replacement policy, which evicts the least recently used item.
 ///
 /// ```
@@ -36,13 +173,13 @@ pub trait Tiebreaker<I: Item>: ReplacementPolicy<I> {
 ///
 /// assert_eq!(c.set(), &HashSet::from([0, 2, 3]));
 /// ```
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct Lru<I: Item = u32> {
     stack: Vec<I>,
 }
 
-impl<I: Item> ReplacementPolicy<I> for Lru<I> {
-    fn update_state(&mut self, _: &HashSet<I>, _: u32, next: I) {
+impl<I: Item, H: BuildHasher> ReplacementPolicy<I, H> for Lru<I> {
+    fn update_state(&mut self, _: &HashSet<I, H>, _: u32, next: I) {
         if let Some(index) = self.stack.iter().position(|&i| i == next) {
             self.stack.remove(index);
         }
@@ -50,13 +187,45 @@ impl<I: Item> ReplacementPolicy<I> for Lru<I> {
         self.stack.push(next);
     }
 
-    fn replace(&mut self, set: &HashSet<I>, capacity: u32, next: I) -> HashSet<I> {
+    fn replace(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I> {
         self.update_state(set, capacity, next);
-        HashSet::from([self.stack.remove(0)])
+        self.shrink(set, capacity - next.size())
+    }
+
+    fn victims(&self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I> {
+        // replacing moves `next` to the top of the stack first, so it's the last candidate
+        let mut candidates = self
+            .stack
+            .iter()
+            .copied()
+            .filter(|&i| i != next)
+            .chain([next]);
+
+        let mut to_evict = HashSet::new();
+        while remaining_size(set, &to_evict) > capacity - next.size() {
+            to_evict.insert(candidates.next().expect("The cache is non-empty."));
+        }
+        to_evict
+    }
+
+    fn shrink(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        let mut to_evict = HashSet::new();
+        while remaining_size(set, &to_evict) > capacity {
+            to_evict.insert(self.stack.remove(0));
+        }
+        to_evict
+    }
+
+    fn remove(&mut self, item: I) {
+        self.stack.retain(|&i| i != item);
+    }
+
+    fn clear(&mut self) {
+        self.stack.clear();
     }
 }
 
-impl<I: Item> Tiebreaker<I> for Lru<I> {
+impl<I: Item, H: BuildHasher> Tiebreaker<I, H> for Lru<I> {
     fn tiebreak(&mut self, from: &HashSet<I>, size_to_free: u32) -> HashSet<I> {
         let mut ret = HashSet::new();
 
@@ -90,38 +259,357 @@ impl<I: Item> Tiebreaker<I> for Lru<I> {
 ///
 /// assert_eq!(c.set(), &HashSet::from([1, 2, 3]));
 /// ```
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct Fifo<I: Item = u32> {
     stack: VecDeque<I>,
 }
 
-impl<I: Item> ReplacementPolicy<I> for Fifo<I> {
-    fn update_state(&mut self, _: &HashSet<I>, _: u32, next: I) {
+impl<I: Item, H: BuildHasher> ReplacementPolicy<I, H> for Fifo<I> {
+    fn update_state(&mut self, _: &HashSet<I, H>, _: u32, next: I) {
         if !self.stack.contains(&next) {
             self.stack.push_back(next);
         }
     }
 
-    fn replace(&mut self, set: &HashSet<I>, capacity: u32, next: I) -> HashSet<I> {
+    fn replace(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I> {
         self.update_state(set, capacity, next);
-        HashSet::from([self.stack.pop_front().expect("The cache is non-empty.")])
+        self.shrink(set, capacity - next.size())
+    }
+
+    fn victims(&self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I> {
+        // replacing appends `next` if it's new, so it's the last candidate
+        let mut candidates = self
+            .stack
+            .iter()
+            .copied()
+            .chain((!self.stack.contains(&next)).then_some(next));
+
+        let mut to_evict = HashSet::new();
+        while remaining_size(set, &to_evict) > capacity - next.size() {
+            to_evict.insert(candidates.next().expect("The cache is non-empty."));
+        }
+        to_evict
+    }
+
+    fn shrink(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        let mut to_evict = HashSet::new();
+        while remaining_size(set, &to_evict) > capacity {
+            to_evict.insert(self.stack.pop_front().expect("The cache is non-empty."));
+        }
+        to_evict
+    }
+
+    fn remove(&mut self, item: I) {
+        self.stack.retain(|&i| i != item);
+    }
+
+    fn clear(&mut self) {
+        self.stack.clear();
+    }
+}
+
+impl<I: Item, H: BuildHasher> Tiebreaker<I, H> for Fifo<I> {
+    fn tiebreak(&mut self, from: &HashSet<I>, size_to_free: u32) -> HashSet<I> {
+        let mut ret = HashSet::new();
+
+        // the stack is in insertion order, so this takes the first-inserted candidates
+        for &item in self.stack.iter().filter(|i| from.contains(i)) {
+            if size_to_free <= ret.iter().map(Item::size).sum() {
+                break;
+            }
+            ret.insert(item);
+        }
+
+        assert!(!ret.is_empty());
+        ret
+    }
+}
+
+/// A replacement policy which evicts the item with the smallest ID, for items which are ordered.
+///
+/// This is mostly useful as a [`Tiebreaker`] for frequency-based policies like [`Lfu`], since
+/// unlike [`Lru`] and [`Fifo`] its choices don't depend on the order of accesses at all.
+///
+/// ```
+/// # use std::collections::HashSet;
+/// use cache_sim::{Cache, SmallestId};
+///
+/// let mut c = Cache::<SmallestId>::new(2);
+///
+/// c.access_all([4, 2, 2, 9]);
+///
+/// assert_eq!(c.set(), &HashSet::from([4, 9]));
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SmallestId;
+
+impl<I: Item + Ord, H: BuildHasher> ReplacementPolicy<I, H> for SmallestId {
+    fn update_state(&mut self, _: &HashSet<I, H>, _: u32, _: I) {}
+
+    fn replace(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I> {
+        ReplacementPolicy::<I, H>::shrink(self, set, capacity - next.size())
+    }
+
+    fn shrink(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        let mut items: Vec<I> = set.iter().copied().collect();
+        items.sort_unstable();
+
+        let mut to_evict = HashSet::new();
+        for item in items {
+            if remaining_size(set, &to_evict) <= capacity {
+                break;
+            }
+            to_evict.insert(item);
+        }
+        to_evict
+    }
+
+    fn remove(&mut self, _: I) {}
+
+    fn clear(&mut self) {}
+}
+
+impl<I: Item + Ord, H: BuildHasher> Tiebreaker<I, H> for SmallestId {
+    fn tiebreak(&mut self, from: &HashSet<I>, size_to_free: u32) -> HashSet<I> {
+        let mut candidates: Vec<I> = from.iter().copied().collect();
+        candidates.sort_unstable();
+
+        let mut ret = HashSet::new();
+        for item in candidates {
+            if size_to_free <= ret.iter().map(Item::size).sum() {
+                break;
+            }
+            ret.insert(item);
+        }
+
+        assert!(!ret.is_empty());
+        ret
     }
 }
 
 /// The RAND replacement policy, which evicts a random item.
-#[derive(Default)]
-pub struct Rand {
-    rng: rand::rngs::ThreadRng,
+///
+/// By default the choices are seeded from system entropy, so every run evicts differently. Use
+/// [`Rand::with_seed`] for reproducible simulations.
+#[derive(Clone)]
+pub struct Rand<I: Item = u32> {
+    /// The seed, so clearing can restart the generator.
+    seed: u64,
+    rng: StdRng,
+    // the resident items, in a deterministic order so seeded choices don't depend on hashing
+    items: Vec<I>,
+}
+
+impl<I: Item> Rand<I> {
+    /// Create a RAND policy whose choices are determined by `seed`.
+    ///
+    /// Two caches built with the same seed and driven by the same accesses evict identically.
+    ///
+    /// ```
+    /// use cache_sim::{Cache, Rand};
+    ///
+    /// let mut a = Cache::<Rand>::with_replacement_policy(Rand::with_seed(7), 2u32);
+    /// let mut b = Cache::<Rand>::with_replacement_policy(Rand::with_seed(7), 2u32);
+    ///
+    /// a.access_all([0, 1, 2, 3, 0, 4]);
+    /// b.access_all([0, 1, 2, 3, 0, 4]);
+    ///
+    /// assert_eq!(a.set(), b.set());
+    /// ```
+    #[must_use]
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            items: Vec::new(),
+        }
+    }
 }
 
-impl<I: Item> ReplacementPolicy<I> for Rand {
-    fn update_state(&mut self, _: &HashSet<I>, _: u32, _: I) {}
+impl<I: Item> Default for Rand<I> {
+    fn default() -> Self {
+        Self::with_seed(StdRng::from_entropy().gen())
+    }
+}
 
-    fn replace(&mut self, set: &HashSet<I>, _: u32, _: I) -> HashSet<I> {
-        HashSet::from([*set
-            .iter()
-            .choose(&mut self.rng)
-            .expect("The set is non-empty.")])
+impl<I: Item, H: BuildHasher> ReplacementPolicy<I, H> for Rand<I> {
+    fn update_state(&mut self, set: &HashSet<I, H>, _: u32, next: I) {
+        if !set.contains(&next) {
+            self.items.push(next);
+        }
+    }
+
+    fn replace(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I> {
+        // choose the victims before `next` joins the candidates, so it's always admitted
+        let to_evict = self.shrink(set, capacity - next.size());
+        self.update_state(set, capacity, next);
+        to_evict
+    }
+
+    fn shrink(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        let mut to_evict = HashSet::new();
+        while remaining_size(set, &to_evict) > capacity {
+            let victim = self.rng.gen_range(0..self.items.len());
+            to_evict.insert(self.items.swap_remove(victim));
+        }
+        to_evict
+    }
+
+    fn remove(&mut self, item: I) {
+        self.items.retain(|&i| i != item);
+    }
+
+    fn clear(&mut self) {
+        self.rng = StdRng::seed_from_u64(self.seed);
+        self.items.clear();
+    }
+}
+
+/// The hyperbolic caching replacement policy.
+///
+/// Detailed in this paper: <https://www.usenix.org/conference/atc17/technical-sessions/presentation/blankstein>
+///
+/// Each item's priority is its access frequency divided by the time since it was inserted, in
+/// accesses. Rather than keeping the items ordered by priority, each eviction samples
+/// `sample_size` (default 64) resident items at random and evicts the one with the lowest
+/// priority. Frequencies are forgotten on eviction.
+///
+/// Like [`Rand`], the choices are seeded from system entropy by default; use
+/// [`Hyperbolic::with_seed`] for reproducible simulations.
+///
+/// ```
+/// # use std::collections::HashSet;
+/// use cache_sim::{Cache, Hyperbolic};
+///
+/// let mut c = Cache::<Hyperbolic>::new(2);
+///
+/// c.access_all([0, 1, 0, 0]);
+/// c.access(2); // the sample covers the whole cache, and 1 has the lower priority
+///
+/// assert_eq!(c.set(), &HashSet::from([0, 2]));
+/// ```
+#[derive(Clone)]
+pub struct Hyperbolic<I: Item = u32> {
+    /// The seed, so clearing can restart the generator.
+    seed: u64,
+    rng: StdRng,
+    sample_size: usize,
+    /// The number of accesses so far.
+    time: u64,
+    /// The access frequency and insertion time of each resident item.
+    entries: HashMap<I, (u32, u64)>,
+    // the resident items, in a deterministic order so seeded samples don't depend on hashing
+    items: Vec<I>,
+}
+
+impl<I: Item> Default for Hyperbolic<I> {
+    fn default() -> Self {
+        Self::new(64)
+    }
+}
+
+impl<I: Item> Hyperbolic<I> {
+    /// Instantiate a new hyperbolic replacement policy, sampling `sample_size` items per eviction.
+    ///
+    /// # Panics
+    ///
+    /// If `sample_size` is zero.
+    #[must_use]
+    pub fn new(sample_size: usize) -> Self {
+        Self::with_seed(sample_size, StdRng::from_entropy().gen())
+    }
+
+    /// Instantiate a new hyperbolic replacement policy whose samples are determined by `seed`.
+    ///
+    /// Two caches built with the same parameters and driven by the same accesses evict
+    /// identically.
+    ///
+    /// # Panics
+    ///
+    /// If `sample_size` is zero.
+    #[must_use]
+    pub fn with_seed(sample_size: usize, seed: u64) -> Self {
+        assert!(sample_size > 0, "The sample size must be positive.");
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            sample_size,
+            time: 0,
+            entries: HashMap::default(),
+            items: Vec::new(),
+        }
+    }
+
+    /// Get the priority of a resident item.
+    #[allow(clippy::cast_precision_loss)] // traces are much shorter than 2^52 accesses
+    fn priority(&self, item: &I) -> f64 {
+        let (frequency, inserted) = self.entries[item];
+        // items inserted since the last access have no age yet, so count them as one access old
+        f64::from(frequency) / (self.time - inserted).max(1) as f64
+    }
+
+    /// Record an access to `next` at the current time.
+    fn record(&mut self, next: I) {
+        let time = self.time;
+        let (frequency, _) = self.entries.entry(next).or_insert_with(|| (0, time));
+        if *frequency == 0 {
+            self.items.push(next);
+        }
+        *frequency += 1;
+    }
+}
+
+impl<I: Item, H: BuildHasher> ReplacementPolicy<I, H> for Hyperbolic<I> {
+    fn update_state(&mut self, _: &HashSet<I, H>, _: u32, next: I) {
+        self.time += 1;
+        self.record(next);
+    }
+
+    fn replace(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I> {
+        // evict before recording `next`, so it can't be sampled
+        self.time += 1;
+        let to_evict = self.shrink(set, capacity - next.size());
+        self.record(next);
+        to_evict
+    }
+
+    fn shrink(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        let mut to_evict = HashSet::new();
+        while remaining_size(set, &to_evict) > capacity {
+            let sample = rand::seq::index::sample(
+                &mut self.rng,
+                self.items.len(),
+                self.sample_size.min(self.items.len()),
+            );
+            // ties go to the older item; insertion times are unique, so the order of the sample
+            // never matters
+            let victim = sample
+                .into_iter()
+                .min_by(|&a, &b| {
+                    let (a, b) = (&self.items[a], &self.items[b]);
+                    self.priority(a)
+                        .total_cmp(&self.priority(b))
+                        .then(self.entries[a].1.cmp(&self.entries[b].1))
+                })
+                .expect("The cache is non-empty.");
+
+            let item = self.items.swap_remove(victim);
+            self.entries.remove(&item);
+            to_evict.insert(item);
+        }
+        to_evict
+    }
+
+    fn remove(&mut self, item: I) {
+        self.entries.remove(&item);
+        self.items.retain(|&i| i != item);
+    }
+
+    fn clear(&mut self) {
+        self.rng = StdRng::seed_from_u64(self.seed);
+        self.time = 0;
+        self.entries.clear();
+        self.items.clear();
     }
 }
 
@@ -140,13 +628,13 @@ impl<I: Item> ReplacementPolicy<I> for Rand {
 ///
 /// assert_eq!(c.set(), &HashSet::from([0, 1, 3]));
 /// ```
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct Mru<I: Item = u32> {
     stack: Vec<I>,
 }
 
-impl<I: Item> ReplacementPolicy<I> for Mru<I> {
-    fn update_state(&mut self, _: &HashSet<I>, _: u32, next: I) {
+impl<I: Item, H: BuildHasher> ReplacementPolicy<I, H> for Mru<I> {
+    fn update_state(&mut self, _: &HashSet<I, H>, _: u32, next: I) {
         if let Some(index) = self.stack.iter().position(|&i| i == next) {
             self.stack.remove(index);
         }
@@ -154,284 +642,5115 @@ impl<I: Item> ReplacementPolicy<I> for Mru<I> {
         self.stack.push(next);
     }
 
-    fn replace(&mut self, set: &HashSet<I>, capacity: u32, next: I) -> HashSet<I> {
+    fn replace(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I> {
+        // we want the most recently used item _other_ than the next item, so we have to evict
+        // before update_state pushes the next item to the top of the stack
+        let to_evict = self.shrink(set, capacity - next.size());
         self.update_state(set, capacity, next);
+        to_evict
+    }
+
+    fn shrink(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        let mut to_evict = HashSet::new();
+        while remaining_size(set, &to_evict) > capacity {
+            to_evict.insert(self.stack.pop().expect("The cache is non-empty."));
+        }
+        to_evict
+    }
+
+    fn remove(&mut self, item: I) {
+        self.stack.retain(|&i| i != item);
+    }
 
-        // update_state just pushed the next item to the top of the stack, and we can't evict that
-        // item (we want the most recently used item _other_ than it), so we get the second-to-last
-        // item from the stack.
-        HashSet::from([self.stack.remove(self.stack.len() - 2)])
+    fn clear(&mut self) {
+        self.stack.clear();
     }
 }
 
-/// The LFU replacement policy, which evicts the least frequently used item.
+/// The CLOCK replacement policy, a cheap approximation of LRU.
 ///
-/// The tiebreaker defaults to Lru.
+/// Items sit in a circular buffer of slots, each with a reference bit that is set whenever the
+/// item is accessed. To evict, a hand sweeps around the buffer clearing set bits, and evicts the
+/// first item whose bit was already clear. If every bit is set, the hand makes a full revolution
+/// and evicts the item it started on.
 ///
 /// ```
 /// # use std::collections::HashSet;
-/// use cache_sim::{Cache, Lfu};
+/// use cache_sim::{Cache, Clock};
 ///
-/// let mut c = Cache::<Lfu>::new(3);
+/// let mut c = Cache::<Clock>::new(3);
 ///
 /// c.access(0);
-/// c.access(0);
 /// c.access(1);
 /// c.access(2);
-/// c.access(2);
-/// c.access(3);
+/// c.access(3); // every bit is set, so the hand evicts 0 and clears the rest
+/// c.access(1);
+/// c.access(4); // 1's bit is set again, so it gets a second chance
 ///
-/// assert_eq!(c.set(), &HashSet::from([0, 2, 3]));
+/// assert_eq!(c.set(), &HashSet::from([1, 3, 4]));
 /// ```
-#[derive(Default)]
-pub struct Lfu<I: Item = u32, T: Tiebreaker<I> = Lru> {
-    counts: HashMap<I, u32>,
-    tiebreaker: T,
+#[derive(Default, Clone)]
+pub struct Clock<I: Item = u32> {
+    slots: Vec<(I, bool)>,
+    hand: usize,
 }
 
-impl<I: Item, T: Tiebreaker<I>> ReplacementPolicy<I> for Lfu<I, T> {
-    fn update_state(&mut self, set: &HashSet<I>, capacity: u32, next: I) {
-        *self.counts.entry(next).or_insert(0) += 1;
-        self.tiebreaker.update_state(set, capacity, next);
+impl<I: Item, H: BuildHasher> ReplacementPolicy<I, H> for Clock<I> {
+    fn update_state(&mut self, _: &HashSet<I, H>, _: u32, next: I) {
+        if let Some(slot) = self.slots.iter_mut().find(|(i, _)| *i == next) {
+            slot.1 = true;
+        } else {
+            self.slots.push((next, true));
+        }
     }
 
-    fn replace(&mut self, set: &HashSet<I>, capacity: u32, next: I) -> HashSet<I> {
-        self.update_state(set, capacity, next);
-        let min = self
-            .counts
-            .iter()
-            .filter(|&(i, _)| set.contains(i)) // we have to evict something that's in the cache
-            .map(|(_, &count)| count)
-            .min()
-            .expect("The set is non-empty.");
+    fn replace(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I> {
+        let to_evict = self.shrink(set, capacity - next.size());
 
-        self.tiebreaker.tiebreak(
-            &self
-                .counts
-                .iter()
-                .filter(|&(_, &count)| count == min)
-                .map(|(&i, _)| i)
-                .collect(),
-            1,
-        )
+        // the new item takes the (last) evicted item's slot, and the hand moves past it
+        self.slots.insert(self.hand, (next, true));
+        self.hand = (self.hand + 1) % self.slots.len();
+
+        to_evict
+    }
+
+    fn shrink(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        let mut to_evict = HashSet::new();
+
+        while remaining_size(set, &to_evict) > capacity {
+            assert!(!self.slots.is_empty(), "The cache is non-empty.");
+
+            if self.hand >= self.slots.len() {
+                self.hand = 0;
+            }
+
+            let (item, referenced) = &mut self.slots[self.hand];
+            if *referenced {
+                *referenced = false;
+                self.hand += 1;
+            } else {
+                to_evict.insert(*item);
+                self.slots.remove(self.hand);
+            }
+        }
+
+        to_evict
+    }
+
+    fn remove(&mut self, item: I) {
+        if let Some(index) = self.slots.iter().position(|(i, _)| *i == item) {
+            self.slots.remove(index);
+            if index < self.hand {
+                self.hand -= 1;
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.slots.clear();
+        self.hand = 0;
     }
 }
 
-/// The landlord replacement algotihm.
-///
-/// Detailed in this paper: <https://arxiv.org/abs/cs/0205033>
+/// Belady's optimal offline replacement policy, which evicts the item next used furthest in the
+/// future.
 ///
-/// The tiebreaker (for evicting multiple zero-credit items) defaults to Lru.
+/// Unlike the other policies, this needs to know the whole trace ahead of time, so it can only be
+/// constructed from a [`Trace`]. The cache must then be driven with that trace, one access at a
+/// time. The policy counts how many times it's seen each item, and takes the nth access to an item
+/// to be its nth occurrence in the trace, so accesses the cache bypasses don't throw it off.
+/// Items which are never used again are treated as being next used at infinity, so they are
+/// evicted first, and so are items accessed more often than they occur in the trace. To
+/// [prepopulate](crate::Cache::prepopulate) the cache, construct the policy from the warm-up
+/// accesses followed by the trace.
 ///
 /// ```
-/// # use std::collections::HashSet;
-/// use cache_sim::{Cache, Landlord, GeneralModelGenerator};
-///
-/// let mut cache = Cache::<Landlord, (), _>::new(3);
-/// let mut g = GeneralModelGenerator::new();
+/// use cache_sim::{Belady, Cache, Trace};
+/// use cache_sim::stats::MissCount;
 ///
-/// let a = g.item(1.0, 1);
-/// let b = g.item(0.5, 2);
-/// let c = g.item(100.0, 2);
-/// let d = g.item(1.0, 1);
+/// let trace = Trace::from(vec![1, 2, 3, 4, 1, 2, 5, 1, 2, 3, 4, 5]);
+/// let mut c = Cache::<_, MissCount>::with_replacement_policy(Belady::new(&trace), 3u32);
 ///
-/// cache.access(a);
-/// cache.access(b);
-/// cache.access(c);
-/// cache.access(d);
+/// c.run_trace(&trace);
 ///
-/// assert_eq!(cache.set(), &HashSet::from([c, d]));
+/// assert_eq!(c.stat().0, 7);
 /// ```
-pub struct Landlord<I: Item = GeneralModelItem, T: Tiebreaker<I> = Lru<GeneralModelItem>> {
-    credit: HashMap<I, f64>,
-    credit_increase: f64,
-    tiebreaker: T,
+#[derive(Clone)]
+pub struct Belady<I: Item = u32> {
+    /// The positions of each item's occurrences in the trace, in order.
+    uses: HashMap<I, Vec<usize>>,
+    /// The number of accesses to each item we've seen so far.
+    seen: HashMap<I, usize>,
+    /// The position of the next access to each item we've seen so far.
+    next_access: HashMap<I, usize>,
 }
 
-impl<I: Item, T: Tiebreaker<I> + Default> Default for Landlord<I, T> {
+impl<I: Item> Belady<I> {
+    /// Instantiate the policy for a particular trace.
+    #[must_use]
+    pub fn new(trace: &Trace<I>) -> Self {
+        let mut uses: HashMap<I, Vec<usize>> = HashMap::new();
+        for (i, &item) in trace.iter().enumerate() {
+            uses.entry(item).or_default().push(i);
+        }
+
+        Self {
+            uses,
+            seen: HashMap::default(),
+            next_access: HashMap::default(),
+        }
+    }
+}
+
+impl<I: Item, H: BuildHasher> ReplacementPolicy<I, H> for Belady<I> {
+    fn update_state(&mut self, _: &HashSet<I, H>, _: u32, next: I) {
+        let seen = self.seen.entry(next).or_insert(0);
+        // this is the occurrence at index `seen`, so the next one is at `seen + 1`
+        let next_use = self
+            .uses
+            .get(&next)
+            .and_then(|uses| uses.get(*seen + 1))
+            .copied()
+            .unwrap_or(usize::MAX);
+        *seen += 1;
+        self.next_access.insert(next, next_use);
+    }
+
+    fn replace(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I> {
+        let to_evict = self.shrink(set, capacity - next.size());
+        self.update_state(set, capacity, next);
+        to_evict
+    }
+
+    fn shrink(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        let mut to_evict = HashSet::new();
+        while remaining_size(set, &to_evict) > capacity {
+            to_evict.insert(
+                *set.iter()
+                    .filter(|i| !to_evict.contains(*i))
+                    .max_by_key(|i| self.next_access.get(i).copied().unwrap_or(usize::MAX))
+                    .expect("The set is non-empty."),
+            );
+        }
+        to_evict
+    }
+
+    fn remove(&mut self, item: I) {
+        self.next_access.remove(&item);
+    }
+
+    fn clear(&mut self) {
+        self.seen.clear();
+        self.next_access.clear();
+    }
+}
+
+/// The full 2Q replacement policy.
+///
+/// Detailed in this paper: <https://www.vldb.org/conf/1994/P439.PDF>
+///
+/// New items enter `a1in`, a FIFO queue. Items evicted from `a1in` are remembered in the ghost
+/// queue `a1out`, and if they're accessed again while there they're promoted to `am`, an LRU
+/// queue for items which have proven themselves hot. Hits in `a1in` don't move anything, since
+/// they're likely correlated references.
+///
+/// The sizes of `a1in` and `a1out` are bounded by the `kin` and `kout` ratios of the capacity,
+/// which default to 0.25 and 0.5. `a1out` is an [`Exact`] ghost list by default, but any
+/// [`GhostStore`] will do.
+///
+/// ```
+/// # use std::collections::HashSet;
+/// use cache_sim::{Cache, TwoQ};
+///
+/// let mut c = Cache::<TwoQ>::new(3);
+///
+/// c.access(0);
+/// c.access(1);
+/// c.access(2);
+/// c.access(3); // evicts 0 from a1in, remembering it in a1out
+/// c.access(0); // promotes 0 to am
+///
+/// assert_eq!(c.set(), &HashSet::from([0, 2, 3]));
+/// assert_eq!(c.replacement_policy().am_len(), 1);
+/// ```
+#[derive(Clone)]
+pub struct TwoQ<I: Item = u32, G = Exact<I>> {
+    a1in: VecDeque<I>,
+    a1out: G,
+    am: VecDeque<I>,
+    kin: f64,
+    kout: f64,
+}
+
+impl<I: Item, G: GhostStore<I> + Default> Default for TwoQ<I, G> {
     fn default() -> Self {
+        Self::with_ghosts(0.25, 0.5, G::default())
+    }
+}
+
+impl<I: Item> TwoQ<I> {
+    /// Instantiate a new 2Q replacement policy.
+    ///
+    /// The `kin` and `kout` parameters are the maximum sizes of `a1in` and `a1out`, as a ratio of
+    /// the capacity of the cache.
+    #[must_use]
+    pub fn new(kin: f64, kout: f64) -> Self {
+        Self::with_ghosts(kin, kout, Exact::default())
+    }
+}
+
+impl<I: Item, G: GhostStore<I>> TwoQ<I, G> {
+    /// Instantiate a new 2Q replacement policy, like [`TwoQ::new`], keeping `a1out` in the given
+    /// ghost store.
+    #[must_use]
+    pub fn with_ghosts(kin: f64, kout: f64, a1out: G) -> Self {
         Self {
-            credit: HashMap::default(),
-            credit_increase: 1.0,
-            tiebreaker: T::default(),
+            a1in: VecDeque::default(),
+            a1out,
+            am: VecDeque::default(),
+            kin,
+            kout,
+        }
+    }
+
+    /// Get the number of items in `a1in`.
+    #[must_use]
+    pub fn a1in_len(&self) -> usize {
+        self.a1in.len()
+    }
+
+    /// Get the number of keys in `a1out`.
+    #[must_use]
+    pub fn a1out_len(&self) -> usize {
+        self.a1out.len()
+    }
+
+    /// Get the number of items in `am`.
+    #[must_use]
+    pub fn am_len(&self) -> usize {
+        self.am.len()
+    }
+
+    /// Make room for a new item, returning the evicted item.
+    fn reclaim(&mut self, capacity: u32) -> I {
+        #[allow(clippy::cast_precision_loss)] // queue lengths are bounded by the capacity
+        if self.a1in.len() as f64 > self.kin * f64::from(capacity) || self.am.is_empty() {
+            let item = self.a1in.pop_front().expect("The cache is non-empty.");
+            self.a1out.insert(item);
+
+            #[allow(clippy::cast_precision_loss)] // queue lengths are bounded by the capacity
+            if self.a1out.len() as f64 > self.kout * f64::from(capacity) {
+                self.a1out.truncate(self.a1out.len() - 1);
+            }
+
+            item
+        } else {
+            self.am.pop_front().expect("am is non-empty.")
+        }
+    }
+
+    /// Update the queues after an access to `next`, evicting an item if the cache is `full`.
+    fn access(&mut self, capacity: u32, next: I, full: bool) -> Option<I> {
+        if let Some(index) = self.am.iter().position(|&i| i == next) {
+            self.am.remove(index);
+            self.am.push_back(next);
+            None
+        } else if self.a1out.remove(&next) {
+            let evicted = full.then(|| self.reclaim(capacity));
+            self.am.push_back(next);
+            evicted
+        } else if self.a1in.contains(&next) {
+            None
+        } else {
+            let evicted = full.then(|| self.reclaim(capacity));
+            self.a1in.push_back(next);
+            evicted
         }
     }
 }
 
-impl<I: Item, T: Tiebreaker<I> + Default> Landlord<I, T> {
-    /// Instantiate a new landlord replacement policy.
+impl<I: Item, H: BuildHasher, G: GhostStore<I>> ReplacementPolicy<I, H> for TwoQ<I, G> {
+    fn update_state(&mut self, _: &HashSet<I, H>, capacity: u32, next: I) {
+        self.access(capacity, next, false);
+    }
+
+    fn replace(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I> {
+        let evicted = self
+            .access(capacity, next, true)
+            .expect("The cache is full.");
+        evict_until_fits(set, capacity, next, HashSet::from([evicted]), || {
+            Some(self.reclaim(capacity))
+        })
+    }
+
+    fn shrink(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        let mut to_evict = HashSet::new();
+        while remaining_size(set, &to_evict) > capacity {
+            to_evict.insert(self.reclaim(capacity));
+        }
+        to_evict
+    }
+
+    fn remove(&mut self, item: I) {
+        self.a1in.retain(|&i| i != item);
+        self.am.retain(|&i| i != item);
+    }
+
+    fn clear(&mut self) {
+        self.a1in.clear();
+        self.a1out.clear();
+        self.am.clear();
+    }
+}
+
+/// The segmented LRU (SLRU) replacement policy.
+///
+/// The cache is split into a probationary and a protected segment, both LRU queues. New items
+/// enter the probationary segment, and a hit there promotes them to the protected segment. When
+/// the protected segment outgrows its share of the capacity, its least recently used item is
+/// demoted to the most recently used end of the probationary segment rather than evicted. Items
+/// are only evicted from the probationary segment, unless it's empty.
+///
+/// The protected segment's share is the `protected_ratio` (default 0.8) of the capacity, measured
+/// in item sizes.
+///
+/// ```
+/// # use std::collections::HashSet;
+/// use cache_sim::{Cache, Slru};
+///
+/// let mut c = Cache::<Slru>::new(3);
+///
+/// c.access(0);
+/// c.access(0); // promotes 0 to the protected segment
+/// c.access(1);
+/// c.access(2);
+/// c.access(3); // evicts 1, the least recently used probationary item
+///
+/// assert_eq!(c.set(), &HashSet::from([0, 2, 3]));
+/// assert!(c.replacement_policy().is_protected(&0));
+/// ```
+#[derive(Clone)]
+pub struct Slru<I: Item = u32> {
+    /// The probationary segment, least recently used first.
+    probationary: VecDeque<I>,
+    /// The protected segment, least recently used first.
+    protected: VecDeque<I>,
+    protected_ratio: f64,
+}
+
+impl<I: Item> Default for Slru<I> {
+    fn default() -> Self {
+        Self::new(0.8)
+    }
+}
+
+impl<I: Item> Slru<I> {
+    /// Instantiate a new SLRU replacement policy.
     ///
-    /// The `credit_increase` parameter represents the percentage of the gap between the current credit
-    /// and maximum credit (cost) to increase an item's credit when it is hit. It should not be above
-    /// one. Higher values are closer to LRU, lower values are closer to FIFO. This defaults to 1,
-    /// and should generally be between 0 and 1.
+    /// The `protected_ratio` parameter is the maximum size of the protected segment, as a ratio of
+    /// the capacity of the cache.
     #[must_use]
-    pub fn new(credit_increase: f64) -> Self {
+    pub fn new(protected_ratio: f64) -> Self {
         Self {
-            credit: HashMap::default(),
-            credit_increase,
-            tiebreaker: T::default(),
+            probationary: VecDeque::default(),
+            protected: VecDeque::default(),
+            protected_ratio,
+        }
+    }
+
+    /// Check whether an item is in the protected segment.
+    #[must_use]
+    pub fn is_protected(&self, item: &I) -> bool {
+        self.protected.contains(item)
+    }
+
+    /// Get the number of items in the probationary segment.
+    #[must_use]
+    pub fn probationary_len(&self) -> usize {
+        self.probationary.len()
+    }
+
+    /// Get the number of items in the protected segment.
+    #[must_use]
+    pub fn protected_len(&self) -> usize {
+        self.protected.len()
+    }
+
+    /// Demote protected items until the segment fits in its share of `capacity`.
+    fn demote(&mut self, capacity: u32) {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let protected_capacity = (self.protected_ratio * f64::from(capacity)).round() as u32;
+
+        while self.protected.iter().map(Item::size).sum::<u32>() > protected_capacity {
+            let item = self
+                .protected
+                .pop_front()
+                .expect("The segment is over capacity.");
+            self.probationary.push_back(item);
+        }
+    }
+
+    /// Evict items until the rest fit in `capacity`, starting from the probationary segment.
+    fn evict<H: BuildHasher>(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        let mut to_evict = HashSet::new();
+        while remaining_size(set, &to_evict) > capacity {
+            to_evict.insert(
+                self.probationary
+                    .pop_front()
+                    .or_else(|| self.protected.pop_front())
+                    .expect("The cache is non-empty."),
+            );
         }
+        to_evict
     }
 }
 
-impl<I: Item, T: Tiebreaker<I>> Landlord<I, T> {
-    /// Instantiate a new landlord replacement policy, with a specifically configured tiebreaker.
+impl<I: Item, H: BuildHasher> ReplacementPolicy<I, H> for Slru<I> {
+    fn update_state(&mut self, _: &HashSet<I, H>, capacity: u32, next: I) {
+        if let Some(index) = self.protected.iter().position(|&i| i == next) {
+            self.protected.remove(index);
+            self.protected.push_back(next);
+        } else if let Some(index) = self.probationary.iter().position(|&i| i == next) {
+            self.probationary.remove(index);
+            self.protected.push_back(next);
+            self.demote(capacity);
+        } else {
+            self.probationary.push_back(next);
+        }
+    }
+
+    fn replace(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I> {
+        // evict before inserting, so the new item can't be chosen from the probationary segment
+        let to_evict = self.evict(set, capacity - next.size());
+        self.update_state(set, capacity, next);
+        to_evict
+    }
+
+    fn shrink(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        self.demote(capacity);
+        self.evict(set, capacity)
+    }
+
+    fn remove(&mut self, item: I) {
+        self.probationary.retain(|&i| i != item);
+        self.protected.retain(|&i| i != item);
+    }
+
+    fn clear(&mut self) {
+        self.probationary.clear();
+        self.protected.clear();
+    }
+}
+
+/// An LRU variant which resists scans, by keeping long runs of new items out of the main LRU
+/// queue.
+///
+/// A run of more than `scan_threshold` consecutive misses on new items, meaning ones which weren't
+/// among the last `capacity` items evicted, is treated as a scan. Until the run is broken by a hit
+/// or a miss on a recently evicted item, each new item goes to a probationary queue instead, and
+/// the probationary queue is evicted from first, so a scan's items mostly replace each other rather
+/// than the working set. A probationary item which is accessed again is promoted to the main queue.
+///
+/// The probationary queue holds at most `probation_ratio` of the capacity, which defaults to 0.1
+/// (but there's always room for at least one). If it overflows, as when a scan fills an empty
+/// cache, its oldest item moves to the least recently used end of the main queue, to be evicted
+/// once the probationary queue is empty. The recently evicted items are remembered in an [`Exact`]
+/// ghost list by default, but any [`GhostStore`] will do.
+///
+/// ```
+/// # use std::collections::HashSet;
+/// use cache_sim::{Cache, Lru, ScanResistantLru};
+///
+/// let accesses = [0, 0, 1, 1, 2, 3, 4];
+///
+/// let mut lru = Cache::<Lru>::new(3);
+/// lru.access_all(accesses);
+/// assert_eq!(lru.set(), &HashSet::from([2, 3, 4]));
+///
+/// // 3 and 4 are part of a scan, so only 3 displaced the working set
+/// let mut c =
+///     Cache::<ScanResistantLru>::with_replacement_policy(ScanResistantLru::new(1, 0.1), 3u32);
+/// c.access_all(accesses);
+/// assert_eq!(c.set(), &HashSet::from([1, 2, 4]));
+/// assert!(c.replacement_policy().is_probationary(&4));
+/// ```
+#[derive(Clone)]
+pub struct ScanResistantLru<I: Item = u32, G = Exact<I>> {
+    /// The main queue, least recently used first.
+    main: VecDeque<I>,
+    /// The probationary queue, oldest first.
+    probationary: VecDeque<I>,
+    /// The recently evicted items, to tell which misses are on new items.
+    evicted: G,
+    /// The length of the current run of misses on new items.
+    run: usize,
+    scan_threshold: usize,
+    probation_ratio: f64,
+}
+
+impl<I: Item, G: GhostStore<I> + Default> Default for ScanResistantLru<I, G> {
+    fn default() -> Self {
+        Self::with_ghosts(16, 0.1, G::default())
+    }
+}
+
+impl<I: Item> ScanResistantLru<I> {
+    /// Instantiate a new scan-resistant LRU replacement policy, which treats a run of more than
+    /// `scan_threshold` misses on new items as a scan.
     ///
-    /// The `credit_increase` parameter represents the percentage of the gap between the current credit
-    /// and maximum credit (cost) to increase an item's credit when it is hit. It should not be above
-    /// one. Higher values are closer to LRU, lower values are closer to FIFO. This defaults to 1,
-    /// and should generally be between 0 and 1.
+    /// The `probation_ratio` parameter is the maximum size of the probationary queue, as a ratio
+    /// of the capacity of the cache.
     #[must_use]
-    pub fn with_tiebreaker(tiebreaker: T, credit_increase: f64) -> Self {
+    pub fn new(scan_threshold: usize, probation_ratio: f64) -> Self {
+        Self::with_ghosts(scan_threshold, probation_ratio, Exact::default())
+    }
+}
+
+impl<I: Item, G: GhostStore<I>> ScanResistantLru<I, G> {
+    /// Instantiate a new scan-resistant LRU replacement policy, like [`ScanResistantLru::new`],
+    /// remembering recently evicted items in the given ghost store.
+    #[must_use]
+    pub const fn with_ghosts(scan_threshold: usize, probation_ratio: f64, evicted: G) -> Self {
         Self {
-            credit: HashMap::default(),
-            credit_increase,
-            tiebreaker,
+            main: VecDeque::new(),
+            probationary: VecDeque::new(),
+            evicted,
+            run: 0,
+            scan_threshold,
+            probation_ratio,
+        }
+    }
+
+    /// Get the length of a run of compulsory misses after which new items are probationary.
+    #[must_use]
+    pub const fn scan_threshold(&self) -> usize {
+        self.scan_threshold
+    }
+
+    /// Check whether an item is in the probationary queue.
+    #[must_use]
+    pub fn is_probationary(&self, item: &I) -> bool {
+        self.probationary.contains(item)
+    }
+
+    /// Get the number of items in the probationary queue.
+    #[must_use]
+    pub fn probationary_len(&self) -> usize {
+        self.probationary.len()
+    }
+
+    /// Get the maximum size of the probationary queue for a given capacity.
+    fn probation_capacity(&self, capacity: u32) -> usize {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let probation_capacity = (self.probation_ratio * f64::from(capacity)) as usize;
+        probation_capacity.max(1)
+    }
+
+    /// Move the oldest probationary items to the least recently used end of the main queue, until
+    /// the probationary queue fits in its share of `capacity`.
+    fn trim_probationary(&mut self, capacity: u32) {
+        let excess = self
+            .probationary
+            .len()
+            .saturating_sub(self.probation_capacity(capacity));
+        for item in self.probationary.drain(..excess).rev() {
+            self.main.push_front(item);
+        }
+    }
+
+    /// Evict items until the rest fit in `capacity`, starting from the probationary queue, and
+    /// remember them as recently evicted.
+    fn evict<H: BuildHasher>(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        let mut to_evict = HashSet::new();
+        while remaining_size(set, &to_evict) > capacity {
+            let victim = self
+                .probationary
+                .pop_front()
+                .or_else(|| self.main.pop_front())
+                .expect("The cache is non-empty.");
+            self.evicted.insert(victim);
+            to_evict.insert(victim);
         }
+        to_evict
     }
 }
 
-impl<I: Item, T: Tiebreaker<I>> ReplacementPolicy<I> for Landlord<I, T> {
-    fn update_state(&mut self, set: &HashSet<I>, capacity: u32, next: I) {
-        // here we know that there is room in the cache, so we don't need to do the while loop in
-        // the algorithm
-        if set.contains(&next) {
-            if let Some(current_credit) = self.credit.get_mut(&next) {
-                *current_credit += (next.cost() - *current_credit) * self.credit_increase;
+impl<I: Item, H: BuildHasher, G: GhostStore<I>> ReplacementPolicy<I, H> for ScanResistantLru<I, G> {
+    fn update_state(&mut self, _: &HashSet<I, H>, capacity: u32, next: I) {
+        if let Some(index) = self.main.iter().position(|&i| i == next) {
+            self.main.remove(index);
+            self.main.push_back(next);
+            self.run = 0;
+        } else if let Some(index) = self.probationary.iter().position(|&i| i == next) {
+            self.probationary.remove(index);
+            self.main.push_back(next);
+            self.run = 0;
+        } else {
+            self.run = if self.evicted.remove(&next) {
+                0
             } else {
-                // should be impossible, because we know `next` is in the set.
-                self.credit.insert(next, next.cost());
+                self.run + 1
+            };
+
+            if self.run > self.scan_threshold {
+                self.probationary.push_back(next);
+                self.trim_probationary(capacity);
+            } else {
+                self.main.push_back(next);
             }
-        } else {
-            self.credit.insert(next, next.cost());
+        }
+    }
+
+    fn replace(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I> {
+        // evict before inserting, so a scan's new item doesn't just replace itself
+        let to_evict = self.evict(set, capacity - next.size());
+        self.evicted.truncate(capacity as usize);
+        self.update_state(set, capacity, next);
+        to_evict
+    }
+
+    fn shrink(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        let to_evict = self.evict(set, capacity);
+        self.evicted.truncate(capacity as usize);
+        self.trim_probationary(capacity);
+        to_evict
+    }
+
+    fn remove(&mut self, item: I) {
+        self.main.retain(|&i| i != item);
+        self.probationary.retain(|&i| i != item);
+    }
+
+    fn clear(&mut self) {
+        self.main.clear();
+        self.probationary.clear();
+        self.evicted.clear();
+        self.run = 0;
+    }
+}
+
+/// The status of a block in [`Lirs`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LirsStatus {
+    Lir,
+    ResidentHir,
+    NonResidentHir,
+}
+
+/// The LIRS replacement policy, which evicts based on inter-reference recency.
+///
+/// Detailed in this paper: <https://dl.acm.org/doi/10.1145/511334.511340>
+///
+/// Blocks are split into LIR blocks, which have been re-referenced recently and so are always
+/// resident, and HIR blocks, only the most recent of which are resident. The stack `s` tracks the
+/// recency of LIR blocks and any HIR blocks more recent than the least recent LIR block, and the
+/// queue `q` holds the resident HIR blocks in eviction order. A HIR block re-referenced while in
+/// `s` has a smaller inter-reference recency than the bottom LIR block, so they swap statuses.
+///
+/// The `hir_ratio` parameter is the fraction of the capacity reserved for resident HIR blocks,
+/// which defaults to 0.01 (but there's always room for at least one).
+///
+/// ```
+/// # use std::collections::HashSet;
+/// use cache_sim::{Cache, Lirs};
+///
+/// let mut c = Cache::<Lirs>::new(3);
+///
+/// c.access(0);
+/// c.access(1);
+/// c.access(2);
+/// c.access(0);
+/// c.access(3);
+///
+/// // 0 and 1 are LIR blocks; 2 was the only resident HIR block
+/// assert_eq!(c.set(), &HashSet::from([0, 1, 3]));
+/// ```
+#[derive(Clone)]
+pub struct Lirs<I: Item = u32> {
+    status: HashMap<I, LirsStatus>,
+    s: VecDeque<I>,
+    q: VecDeque<I>,
+    lir_count: u32,
+    hir_ratio: f64,
+}
+
+impl<I: Item> Default for Lirs<I> {
+    fn default() -> Self {
+        Self::new(0.01)
+    }
+}
+
+impl<I: Item> Lirs<I> {
+    /// Instantiate a new LIRS replacement policy.
+    ///
+    /// The `hir_ratio` parameter is the fraction of the capacity reserved for resident HIR blocks.
+    #[must_use]
+    pub fn new(hir_ratio: f64) -> Self {
+        Self {
+            status: HashMap::default(),
+            s: VecDeque::default(),
+            q: VecDeque::default(),
+            lir_count: 0,
+            hir_ratio,
+        }
+    }
+
+    /// Get the number of LIR blocks allowed for a given capacity.
+    fn lir_capacity(&self, capacity: u32) -> u32 {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let hir_capacity = ((self.hir_ratio * f64::from(capacity)) as u32).max(1);
+        capacity.saturating_sub(hir_capacity)
+    }
+
+    /// Move `item` to the top of the stack.
+    fn push_top(&mut self, item: I) {
+        if let Some(index) = self.s.iter().position(|&i| i == item) {
+            self.s.remove(index);
+        }
+        self.s.push_back(item);
+    }
+
+    /// Remove HIR blocks from the bottom of the stack until the bottom block is LIR.
+    fn prune(&mut self) {
+        while let Some(&bottom) = self.s.front() {
+            match self.status[&bottom] {
+                LirsStatus::Lir => break,
+                LirsStatus::ResidentHir => {}
+                LirsStatus::NonResidentHir => {
+                    // without a stack entry, there's nothing left to remember about the block
+                    self.status.remove(&bottom);
+                }
+            }
+            self.s.pop_front();
+        }
+    }
+
+    /// Make `item` a LIR block, demoting the bottom LIR block if there are too many.
+    fn promote(&mut self, capacity: u32, item: I) {
+        self.status.insert(item, LirsStatus::Lir);
+        self.lir_count += 1;
+        self.push_top(item);
+
+        if self.lir_count > self.lir_capacity(capacity) {
+            self.demote();
+        }
+    }
+
+    /// Make the bottom LIR block a resident HIR block.
+    fn demote(&mut self) {
+        // this is normally the bottom of the stack, unless there's no room for LIR blocks
+        let index = self
+            .s
+            .iter()
+            .position(|i| self.status[i] == LirsStatus::Lir)
+            .expect("The stack has a LIR block.");
+        let bottom = self.s.remove(index).expect("The index is in the stack.");
+        self.status.insert(bottom, LirsStatus::ResidentHir);
+        self.q.push_back(bottom);
+        self.lir_count -= 1;
+        self.prune();
+    }
+
+    /// Evict the resident HIR block at the front of the queue.
+    fn evict(&mut self) -> I {
+        self.evict_at(0)
+    }
+
+    /// Evict the first resident HIR block in the queue other than `keep`, demoting LIR blocks
+    /// until there is one.
+    fn evict_other(&mut self, keep: I) -> I {
+        loop {
+            if let Some(index) = self.q.iter().position(|&i| i != keep) {
+                return self.evict_at(index);
+            }
+            self.demote();
+        }
+    }
+
+    /// Evict the resident HIR block at `index` in the queue.
+    fn evict_at(&mut self, index: usize) -> I {
+        let evicted = self.q.remove(index).expect("The index is in the queue.");
+        if self.s.contains(&evicted) {
+            self.status.insert(evicted, LirsStatus::NonResidentHir);
+        } else {
+            self.status.remove(&evicted);
+        }
+        evicted
+    }
+
+    /// Update the stack and queue after an access to `next`, evicting an item if the cache is
+    /// `full`.
+    fn access(&mut self, capacity: u32, next: I, full: bool) -> Option<I> {
+        match self.status.get(&next) {
+            Some(LirsStatus::Lir) => {
+                self.push_top(next);
+                self.prune();
+                None
+            }
+            Some(LirsStatus::ResidentHir) => {
+                let index = self.q.iter().position(|&i| i == next);
+                self.q
+                    .remove(index.expect("Resident HIR blocks are in the queue."));
+
+                if self.s.contains(&next) {
+                    self.promote(capacity, next);
+                } else {
+                    self.push_top(next);
+                    self.q.push_back(next);
+                }
+                None
+            }
+            status => {
+                let in_stack = status.is_some();
+
+                let evicted = full.then(|| {
+                    // the LIR blocks are bounded by numbers of items, so large enough items can
+                    // leave no resident HIR block to evict
+                    if self.q.is_empty() {
+                        self.demote();
+                    }
+                    self.evict()
+                });
+
+                if in_stack || self.lir_count < self.lir_capacity(capacity) {
+                    self.promote(capacity, next);
+                } else {
+                    self.status.insert(next, LirsStatus::ResidentHir);
+                    self.push_top(next);
+                    self.q.push_back(next);
+                }
+
+                evicted
+            }
+        }
+    }
+}
+
+impl<I: Item, H: BuildHasher> ReplacementPolicy<I, H> for Lirs<I> {
+    fn update_state(&mut self, _: &HashSet<I, H>, capacity: u32, next: I) {
+        self.access(capacity, next, false);
+    }
+
+    fn replace(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I> {
+        let evicted = self
+            .access(capacity, next, true)
+            .expect("The cache is full.");
+        // evicting `next`, the newest resident HIR block, would defeat the point
+        evict_until_fits(set, capacity, next, HashSet::from([evicted]), || {
+            Some(self.evict_other(next))
+        })
+    }
+
+    fn shrink(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        while self.lir_count > self.lir_capacity(capacity) {
+            self.demote();
+        }
+
+        let mut to_evict = HashSet::new();
+        while remaining_size(set, &to_evict) > capacity {
+            if self.q.is_empty() {
+                // there's no room for resident HIR blocks in a cache this small
+                self.demote();
+            }
+            to_evict.insert(self.evict());
+        }
+        to_evict
+    }
+
+    fn remove(&mut self, item: I) {
+        match self.status.get(&item) {
+            Some(LirsStatus::Lir) => {
+                self.status.insert(item, LirsStatus::NonResidentHir);
+                self.lir_count -= 1;
+                self.prune();
+            }
+            Some(LirsStatus::ResidentHir) => {
+                self.q.retain(|&i| i != item);
+                if self.s.contains(&item) {
+                    self.status.insert(item, LirsStatus::NonResidentHir);
+                } else {
+                    self.status.remove(&item);
+                }
+            }
+            Some(LirsStatus::NonResidentHir) | None => {}
+        }
+    }
+
+    fn clear(&mut self) {
+        self.status.clear();
+        self.s.clear();
+        self.q.clear();
+        self.lir_count = 0;
+    }
+}
+
+/// The LRU-K replacement policy, which evicts the item whose `K`th most recent reference is
+/// furthest in the past.
+///
+/// Detailed in this paper: <https://dl.acm.org/doi/10.1145/170036.170081>
+///
+/// Items with fewer than `K` references have an infinite backward `K`-distance, so they are
+/// evicted first, with ties broken by LRU. References within the correlated reference period of
+/// the last reference to an item are treated as a single reference, and items referenced within
+/// that period aren't eligible for eviction. History is retained for every item ever seen, even
+/// after it's evicted. `LruK<1>` is just LRU.
+///
+/// ```
+/// # use std::collections::HashSet;
+/// use cache_sim::{Cache, LruK};
+///
+/// let mut c = Cache::<LruK<2>>::new(2);
+///
+/// c.access(0);
+/// c.access(0);
+/// c.access(1);
+/// c.access(2); // 1 has only been referenced once, so it goes before 0
+///
+/// assert_eq!(c.set(), &HashSet::from([0, 2]));
+/// ```
+#[derive(Clone)]
+pub struct LruK<const K: usize, I: Item = u32> {
+    /// The times of the `K` most recent uncorrelated references to each item, most recent first,
+    /// and the time of the last reference.
+    history: HashMap<I, ([Option<u64>; K], u64)>,
+    correlated_reference_period: u64,
+    time: u64,
+}
+
+impl<const K: usize, I: Item> Default for LruK<K, I> {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl<const K: usize, I: Item> LruK<K, I> {
+    /// Instantiate a new LRU-K replacement policy.
+    ///
+    /// The `correlated_reference_period` is the number of accesses after a reference to an item
+    /// during which further references to it are considered correlated.
+    #[must_use]
+    pub fn new(correlated_reference_period: u64) -> Self {
+        Self {
+            history: HashMap::default(),
+            correlated_reference_period,
+            time: 0,
+        }
+    }
+}
+
+impl<const K: usize, I: Item, H: BuildHasher> ReplacementPolicy<I, H> for LruK<K, I> {
+    fn update_state(&mut self, _: &HashSet<I, H>, _: u32, next: I) {
+        let t = self.time;
+        self.time += 1;
+
+        if let Some((hist, last)) = self.history.get_mut(&next) {
+            if t - *last > self.correlated_reference_period {
+                // close the correlated period, shifting the older references by its length so the
+                // period counts as a single reference
+                let correlated_period = hist[0].map_or(0, |first| *last - first);
+                for i in (1..K).rev() {
+                    hist[i] = hist[i - 1].map(|h| h + correlated_period);
+                }
+                if K > 0 {
+                    hist[0] = Some(t);
+                }
+            }
+            *last = t;
+        } else {
+            let mut hist = [None; K];
+            if K > 0 {
+                hist[0] = Some(t);
+            }
+            self.history.insert(next, (hist, t));
+        }
+    }
+
+    fn replace(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I> {
+        let to_evict = self.shrink(set, capacity - next.size());
+        self.update_state(set, capacity, next);
+        to_evict
+    }
+
+    fn shrink(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        let t = self.time;
+        let key = |i: &&I| {
+            let (hist, last) = self
+                .history
+                .get(i)
+                .expect("Items in the set have a history.");
+            (
+                hist.last().copied().flatten(),
+                hist.first().copied().flatten(),
+                *last,
+            )
+        };
+
+        let mut to_evict = HashSet::new();
+        while remaining_size(set, &to_evict) > capacity {
+            let candidates = set.iter().filter(|i| !to_evict.contains(*i));
+            let eligible = candidates.clone().filter(|i| {
+                let (_, last) = self
+                    .history
+                    .get(i)
+                    .expect("Items in the set have a history.");
+                t - last > self.correlated_reference_period
+            });
+
+            let victim = *eligible
+                .min_by_key(key)
+                // if everything was referenced too recently, we have to evict something anyways
+                .or_else(|| candidates.min_by_key(key))
+                .expect("The set is non-empty.");
+            to_evict.insert(victim);
+        }
+
+        to_evict
+    }
+
+    fn remove(&mut self, _: I) {
+        // history is retained for items which aren't resident anyways
+    }
+
+    fn clear(&mut self) {
+        self.history.clear();
+        self.time = 0;
+    }
+}
+
+/// The MQ (multi-queue) replacement policy.
+///
+/// Detailed in this paper: <https://www.usenix.org/conference/2001-usenix-annual-technical-conference/multi-queue-replacement-algorithm-second-level>
+///
+/// Resident items are split between `m` LRU queues, with an item accessed `f` times in queue
+/// `min(log2(f), m - 1)`, and the least recently used item of the lowest non-empty queue is
+/// evicted. An item which hasn't been accessed for `life_time` accesses expires from the front of
+/// its queue down to the next queue, so items which were hot a long time ago eventually become
+/// evictable. Evicted items and their frequencies are remembered in a history buffer `Qout`, four
+/// times the size of the cache, so an item which comes back picks up its old frequency.
+///
+/// By default, there are 8 queues and the life time is 100 accesses.
+///
+/// ```
+/// # use std::collections::HashSet;
+/// use cache_sim::{Cache, Mq};
+///
+/// let mut c = Cache::<Mq>::new(3);
+///
+/// c.access(0);
+/// c.access(0); // moves 0 up to queue 1
+/// c.access(1);
+/// c.access(2);
+/// c.access(3); // evicts 1, the least recently used item in queue 0
+///
+/// assert_eq!(c.set(), &HashSet::from([0, 2, 3]));
+/// assert_eq!(c.replacement_policy().queue(&0), Some(1));
+/// ```
+#[derive(Clone)]
+pub struct Mq<I: Item = u32> {
+    /// The queues, each least recently used first.
+    queues: Vec<VecDeque<I>>,
+    /// The frequency, expiry time, and queue of each resident item.
+    entries: HashMap<I, (u32, u64, usize)>,
+    /// The history buffer of evicted items and their frequencies, oldest first.
+    history: VecDeque<(I, u32)>,
+    life_time: u64,
+    time: u64,
+}
+
+impl<I: Item> Default for Mq<I> {
+    fn default() -> Self {
+        Self::new(8, 100)
+    }
+}
+
+impl<I: Item> Mq<I> {
+    /// Instantiate a new MQ replacement policy with `queues` queues.
+    ///
+    /// The `life_time` is the number of accesses after which an item which hasn't been accessed
+    /// expires to a lower queue.
+    ///
+    /// # Panics
+    ///
+    /// If `queues` is zero.
+    #[must_use]
+    pub fn new(queues: usize, life_time: u64) -> Self {
+        assert!(queues > 0, "There must be at least one queue.");
+        Self {
+            queues: vec![VecDeque::new(); queues],
+            entries: HashMap::default(),
+            history: VecDeque::default(),
+            life_time,
+            time: 0,
+        }
+    }
+
+    /// Get the queue a resident item is in.
+    #[must_use]
+    pub fn queue(&self, item: &I) -> Option<usize> {
+        self.entries.get(item).map(|&(_, _, queue)| queue)
+    }
+
+    /// Move the expired item at the front of each queue, if any, down to the next queue.
+    fn adjust(&mut self) {
+        for queue in 1..self.queues.len() {
+            if let Some(&front) = self.queues[queue].front() {
+                let entry = self
+                    .entries
+                    .get_mut(&front)
+                    .expect("Queued items have an entry.");
+                if entry.1 < self.time {
+                    self.queues[queue].pop_front();
+                    self.queues[queue - 1].push_back(front);
+                    entry.1 = self.time + self.life_time;
+                    entry.2 = queue - 1;
+                }
+            }
+        }
+    }
+
+    /// Evict items until the rest fit in `capacity`, starting from the lowest queue.
+    fn evict<H: BuildHasher>(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        let mut to_evict = HashSet::new();
+        while remaining_size(set, &to_evict) > capacity {
+            let victim = self
+                .queues
+                .iter_mut()
+                .find_map(VecDeque::pop_front)
+                .expect("The cache is non-empty.");
+            let (frequency, _, _) = self
+                .entries
+                .remove(&victim)
+                .expect("Queued items have an entry.");
+
+            self.history.push_back((victim, frequency));
+            to_evict.insert(victim);
+        }
+        to_evict
+    }
+
+    /// Forget the oldest evicted items, keeping the history within four times `capacity`, as
+    /// suggested in the paper.
+    fn trim_history(&mut self, capacity: u32) {
+        while self.history.len() > 4 * capacity as usize {
+            self.history.pop_front();
+        }
+    }
+}
+
+impl<I: Item, H: BuildHasher> ReplacementPolicy<I, H> for Mq<I> {
+    fn update_state(&mut self, _: &HashSet<I, H>, capacity: u32, next: I) {
+        self.time += 1;
+
+        let frequency = if let Some((frequency, _, queue)) = self.entries.get(&next) {
+            self.queues[*queue].retain(|&i| i != next);
+            frequency + 1
+        } else if let Some(index) = self.history.iter().position(|&(i, _)| i == next) {
+            let (_, frequency) = self.history.remove(index).expect("The index is valid.");
+            frequency + 1
+        } else {
+            1
+        };
+
+        let queue = (frequency.ilog2() as usize).min(self.queues.len() - 1);
+        self.queues[queue].push_back(next);
+        self.entries
+            .insert(next, (frequency, self.time + self.life_time, queue));
+
+        self.adjust();
+        self.trim_history(capacity);
+    }
+
+    fn replace(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I> {
+        let to_evict = self.evict(set, capacity - next.size());
+        self.update_state(set, capacity, next);
+        to_evict
+    }
+
+    fn shrink(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        let to_evict = self.evict(set, capacity);
+        self.trim_history(capacity);
+        to_evict
+    }
+
+    fn remove(&mut self, item: I) {
+        if let Some((_, _, queue)) = self.entries.remove(&item) {
+            self.queues[queue].retain(|&i| i != item);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.queues.iter_mut().for_each(VecDeque::clear);
+        self.entries.clear();
+        self.history.clear();
+        self.time = 0;
+    }
+}
+
+/// The SIEVE replacement policy.
+///
+/// Detailed in this paper: <https://www.usenix.org/conference/nsdi24/presentation/zhang-yazhuo>
+///
+/// Items sit in a FIFO queue with a visited bit, which is set on a hit. New items are inserted at
+/// the head. To evict, a hand moves from where it last stopped (initially the tail) towards the
+/// head, clearing visited bits, and evicts the first unvisited item it finds. Unlike CLOCK, the
+/// hand keeps its position across evictions, and wraps back to the tail when it passes the head.
+///
+/// ```
+/// # use std::collections::HashSet;
+/// use cache_sim::{Cache, Sieve};
+///
+/// let mut c = Cache::<Sieve>::new(3);
+///
+/// c.access(0);
+/// c.access(1);
+/// c.access(2);
+/// c.access(0);
+/// c.access(3); // 0 was visited, so the hand skips past it and evicts 1
+///
+/// assert_eq!(c.set(), &HashSet::from([0, 2, 3]));
+/// ```
+#[derive(Default, Clone)]
+pub struct Sieve<I: Item = u32> {
+    /// The queue, from tail (oldest) to head (newest).
+    queue: VecDeque<(I, bool)>,
+    /// The index of the hand in the queue, or `None` if it should start from the tail.
+    hand: Option<usize>,
+}
+
+impl<I: Item, H: BuildHasher> ReplacementPolicy<I, H> for Sieve<I> {
+    fn update_state(&mut self, _: &HashSet<I, H>, _: u32, next: I) {
+        if let Some(entry) = self.queue.iter_mut().find(|(i, _)| *i == next) {
+            entry.1 = true;
+        } else {
+            self.queue.push_back((next, false));
+        }
+    }
+
+    fn replace(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I> {
+        let to_evict = self.shrink(set, capacity - next.size());
+        self.update_state(set, capacity, next);
+        to_evict
+    }
+
+    fn shrink(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        let mut to_evict = HashSet::new();
+
+        while remaining_size(set, &to_evict) > capacity {
+            assert!(!self.queue.is_empty(), "The cache is non-empty.");
+
+            let mut hand = self.hand.unwrap_or(0);
+            loop {
+                if hand >= self.queue.len() {
+                    hand = 0;
+                }
+
+                let (_, visited) = &mut self.queue[hand];
+                if *visited {
+                    *visited = false;
+                    hand += 1;
+                } else {
+                    break;
+                }
+            }
+
+            let (victim, _) = self.queue.remove(hand).expect("The hand is in the queue.");
+            to_evict.insert(victim);
+
+            // the item after the victim has moved into its index; if there isn't one, the next
+            // eviction starts from the tail
+            self.hand = (hand < self.queue.len()).then_some(hand);
+        }
+
+        to_evict
+    }
+
+    fn remove(&mut self, item: I) {
+        if let Some(index) = self.queue.iter().position(|(i, _)| *i == item) {
+            self.queue.remove(index);
+            self.hand = self.hand.and_then(|hand| {
+                let hand = if index < hand { hand - 1 } else { hand };
+                (hand < self.queue.len()).then_some(hand)
+            });
+        }
+    }
+
+    fn clear(&mut self) {
+        self.queue.clear();
+        self.hand = None;
+    }
+}
+
+/// The S3-FIFO replacement policy, built from three FIFO queues.
+///
+/// Detailed in this paper: <https://dl.acm.org/doi/10.1145/3600006.3613147>
+///
+/// New items enter the small queue, which is sized as `small_ratio` (default 0.1) of the
+/// capacity. Items leaving the small queue move to the main queue if they were hit while there,
+/// or are evicted and remembered in the ghost queue otherwise. Misses on items in the ghost queue
+/// go straight to the main queue. Items leaving the main queue are reinserted if they were hit
+/// since they were last inserted, decrementing their access counter.
+///
+/// The ghost queue stores only keys, and holds at most as many as the main queue's share of the
+/// capacity. It's an [`Exact`] ghost list by default, but any [`GhostStore`] will do.
+///
+/// ```
+/// # use std::collections::HashSet;
+/// use cache_sim::{Cache, S3Fifo};
+///
+/// let mut c = Cache::<S3Fifo>::new(3);
+///
+/// c.access(0);
+/// c.access(1);
+/// c.access(2);
+/// c.access(0);
+/// c.access(3); // 0 was hit, so it moves to the main queue and 1 is evicted instead
+///
+/// assert_eq!(c.set(), &HashSet::from([0, 2, 3]));
+/// ```
+#[derive(Clone)]
+pub struct S3Fifo<I: Item = u32, G = Exact<I>> {
+    /// The small queue, oldest first, with each item's access counter.
+    small: VecDeque<(I, u8)>,
+    /// The main queue, oldest first, with each item's access counter.
+    main: VecDeque<(I, u8)>,
+    /// The ghost queue, oldest first.
+    ghost: G,
+    small_ratio: f64,
+}
+
+impl<I: Item, G: GhostStore<I> + Default> Default for S3Fifo<I, G> {
+    fn default() -> Self {
+        Self::with_ghosts(0.1, G::default())
+    }
+}
+
+impl<I: Item> S3Fifo<I> {
+    /// Instantiate a new S3-FIFO replacement policy.
+    ///
+    /// The `small_ratio` parameter is the size of the small queue, as a ratio of the capacity of
+    /// the cache.
+    #[must_use]
+    pub fn new(small_ratio: f64) -> Self {
+        Self::with_ghosts(small_ratio, Exact::default())
+    }
+}
+
+impl<I: Item, G: GhostStore<I>> S3Fifo<I, G> {
+    /// The maximum value of an item's access counter.
+    const MAX_FREQ: u8 = 3;
+
+    /// Instantiate a new S3-FIFO replacement policy, like [`S3Fifo::new`], keeping the ghost
+    /// queue in the given ghost store.
+    #[must_use]
+    pub fn with_ghosts(small_ratio: f64, ghost: G) -> Self {
+        Self {
+            small: VecDeque::default(),
+            main: VecDeque::default(),
+            ghost,
+            small_ratio,
+        }
+    }
+
+    /// Get the number of items the small queue should hold for a given capacity.
+    fn small_capacity(&self, capacity: u32) -> usize {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let small = (self.small_ratio * f64::from(capacity)).round() as usize;
+        small.max(1)
+    }
+
+    /// Evict an item from the main queue.
+    fn evict_main(&mut self) -> I {
+        loop {
+            let (item, freq) = self.main.pop_front().expect("The main queue is non-empty.");
+            if freq > 0 {
+                self.main.push_back((item, freq - 1));
+            } else {
+                return item;
+            }
+        }
+    }
+
+    /// Evict an item from the small queue, moving any hit items to the main queue.
+    fn evict_small(&mut self, capacity: u32) -> I {
+        let main_capacity = (capacity as usize).saturating_sub(self.small_capacity(capacity));
+
+        while let Some((item, freq)) = self.small.pop_front() {
+            if freq > 0 {
+                self.main.push_back((item, freq));
+                if self.main.len() > main_capacity {
+                    return self.evict_main();
+                }
+            } else {
+                if main_capacity > 0 {
+                    if self.ghost.len() >= main_capacity {
+                        self.ghost.truncate(self.ghost.len() - 1);
+                    }
+                    self.ghost.insert(item);
+                }
+                return item;
+            }
+        }
+
+        // everything in the small queue was hit, so it all moved to the main queue
+        self.evict_main()
+    }
+
+    /// Evict an item from whichever queue is over its share of the capacity.
+    fn evict(&mut self, capacity: u32) -> I {
+        if self.small.len() >= self.small_capacity(capacity) || self.main.is_empty() {
+            self.evict_small(capacity)
+        } else {
+            self.evict_main()
+        }
+    }
+
+    /// Update the queues after an access to `next`, evicting an item if the cache is `full`.
+    fn access(&mut self, capacity: u32, next: I, full: bool) -> Option<I> {
+        if let Some((_, freq)) = self
+            .small
+            .iter_mut()
+            .chain(self.main.iter_mut())
+            .find(|(i, _)| *i == next)
+        {
+            *freq = (*freq + 1).min(Self::MAX_FREQ);
+            return None;
+        }
+
+        let evicted = full.then(|| self.evict(capacity));
+
+        if self.ghost.remove(&next) {
+            self.main.push_back((next, 0));
+        } else {
+            self.small.push_back((next, 0));
+        }
+
+        evicted
+    }
+}
+
+impl<I: Item, H: BuildHasher, G: GhostStore<I>> ReplacementPolicy<I, H> for S3Fifo<I, G> {
+    fn update_state(&mut self, _: &HashSet<I, H>, capacity: u32, next: I) {
+        self.access(capacity, next, false);
+    }
+
+    fn replace(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I> {
+        let evicted = self
+            .access(capacity, next, true)
+            .expect("The cache is full.");
+        evict_until_fits(set, capacity, next, HashSet::from([evicted]), || {
+            Some(self.evict(capacity))
+        })
+    }
+
+    fn shrink(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        let mut to_evict = HashSet::new();
+        while remaining_size(set, &to_evict) > capacity {
+            to_evict.insert(self.evict(capacity));
+        }
+        to_evict
+    }
+
+    fn remove(&mut self, item: I) {
+        self.small.retain(|&(i, _)| i != item);
+        self.main.retain(|&(i, _)| i != item);
+    }
+
+    fn clear(&mut self) {
+        self.small.clear();
+        self.main.clear();
+        self.ghost.clear();
+    }
+}
+
+/// The LFU replacement policy, which evicts the least frequently used item.
+///
+/// Ties between equally frequent items are broken by another policy, the tiebreaker, so
+/// evictions never depend on hash order. It defaults to [`Lru`], which evicts the least recently
+/// used of the tied items; [`Fifo`] evicts the first inserted, and [`SmallestId`] the smallest.
+///
+/// ```
+/// # use std::collections::HashSet;
+/// use cache_sim::{Cache, Lfu};
+///
+/// let mut c = Cache::<Lfu>::new(3);
+///
+/// c.access(0);
+/// c.access(0);
+/// c.access(1);
+/// c.access(2);
+/// c.access(2);
+/// c.access(3);
+///
+/// assert_eq!(c.set(), &HashSet::from([0, 2, 3]));
+/// ```
+#[derive(Default, Clone)]
+pub struct Lfu<I: Item = u32, T = Lru> {
+    counts: HashMap<I, u32>,
+    tiebreaker: T,
+}
+
+impl<I: Item, H: BuildHasher, T: Tiebreaker<I, H>> ReplacementPolicy<I, H> for Lfu<I, T> {
+    fn update_state(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) {
+        *self.counts.entry(next).or_insert(0) += 1;
+        self.tiebreaker.update_state(set, capacity, next);
+    }
+
+    fn replace(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I> {
+        self.update_state(set, capacity, next);
+        self.shrink(set, capacity - next.size())
+    }
+
+    fn shrink(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        let mut to_evict = HashSet::new();
+        while remaining_size(set, &to_evict) > capacity {
+            // we have to evict something that's in the cache
+            let candidates = set.iter().filter(|i| !to_evict.contains(*i));
+            let min = candidates
+                .clone()
+                .map(|i| self.counts[i])
+                .min()
+                .expect("The set is non-empty.");
+
+            let evicted = self.tiebreaker.tiebreak(
+                &candidates
+                    .filter(|&i| self.counts[i] == min)
+                    .copied()
+                    .collect(),
+                1,
+            );
+
+            for item in &evicted {
+                self.tiebreaker.remove(*item);
+            }
+            to_evict.extend(evicted);
+        }
+        to_evict
+    }
+
+    fn remove(&mut self, item: I) {
+        // like evicted items, removed items keep their counts
+        self.tiebreaker.remove(item);
+    }
+
+    fn clear(&mut self) {
+        self.counts.clear();
+        self.tiebreaker.clear();
+    }
+}
+
+/// The LRFU (least recently/frequently used) replacement policy.
+///
+/// Detailed in this paper: <https://doi.org/10.1109/TC.2001.970573>
+///
+/// Each item has a CRF (combined recency and frequency), the sum of `F(x) = (1/2)^(λx)` over the
+/// ages x of its past accesses, and the item with the lowest CRF is evicted. So with `λ = 0` every
+/// access counts equally, and LRFU is [`Lfu`]; with `λ >= 1` the most recent access outweighs all
+/// the earlier ones put together, and LRFU is [`Lru`]. Ties go to the least recently used item. The
+/// CRF can be updated on each access from its previous value, as `1 + F(Δt) * old`, where `Δt` is
+/// the time since the last access. Like [`Lfu`], CRFs are remembered across evictions.
+///
+/// ```
+/// # use std::collections::HashSet;
+/// use cache_sim::{Cache, Lrfu};
+///
+/// let mut lfu = Cache::<Lrfu>::with_replacement_policy(Lrfu::new(0.0), 2u32);
+/// let mut lru = Cache::<Lrfu>::with_replacement_policy(Lrfu::new(1.0), 2u32);
+///
+/// lfu.access_all([0, 0, 0, 1, 2]);
+/// lru.access_all([0, 0, 0, 1, 2]);
+///
+/// assert_eq!(lfu.set(), &HashSet::from([0, 2]));
+/// assert_eq!(lru.set(), &HashSet::from([1, 2]));
+/// ```
+#[derive(Clone)]
+pub struct Lrfu<I: Item = u32> {
+    lambda: f64,
+    /// The number of accesses so far.
+    time: u64,
+    /// The CRF of each item as of its last access, and the time of that access.
+    entries: HashMap<I, (f64, u64)>,
+}
+
+impl<I: Item> Default for Lrfu<I> {
+    fn default() -> Self {
+        Self::new(0.1)
+    }
+}
+
+impl<I: Item> Lrfu<I> {
+    /// Instantiate a new LRFU replacement policy with the given `λ` (default 0.1), which
+    /// interpolates between LFU at 0 and LRU at 1.
+    ///
+    /// # Panics
+    ///
+    /// If `lambda` is negative or NaN.
+    #[must_use]
+    pub fn new(lambda: f64) -> Self {
+        assert!(lambda >= 0.0, "λ must be non-negative.");
+        Self {
+            lambda,
+            time: 0,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Get `λ`.
+    #[must_use]
+    pub const fn lambda(&self) -> f64 {
+        self.lambda
+    }
+
+    /// Get the weight of an access `age` steps ago.
+    #[allow(clippy::cast_precision_loss)] // traces are much shorter than 2^52 accesses
+    fn weight(&self, age: u64) -> f64 {
+        0.5_f64.powf(self.lambda * age as f64)
+    }
+
+    /// Get the current CRF and last access time of an item which has been accessed.
+    fn crf(&self, item: &I) -> (f64, u64) {
+        let (crf, last) = self.entries[item];
+        (self.weight(self.time - last) * crf, last)
+    }
+}
+
+impl<I: Item, H: BuildHasher> ReplacementPolicy<I, H> for Lrfu<I> {
+    fn update_state(&mut self, _: &HashSet<I, H>, _: u32, next: I) {
+        self.time += 1;
+        let crf = self.entries.get(&next).map_or(1.0, |&(crf, last)| {
+            self.weight(self.time - last).mul_add(crf, 1.0)
+        });
+        self.entries.insert(next, (crf, self.time));
+    }
+
+    fn replace(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I> {
+        self.update_state(set, capacity, next);
+        self.shrink(set, capacity - next.size())
+    }
+
+    fn shrink(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        let mut to_evict = HashSet::new();
+        while remaining_size(set, &to_evict) > capacity {
+            // access times are unique, so there's always exactly one victim
+            let victim = set
+                .iter()
+                .filter(|i| !to_evict.contains(*i))
+                .min_by(|a, b| {
+                    let ((a_crf, a_last), (b_crf, b_last)) = (self.crf(a), self.crf(b));
+                    a_crf.total_cmp(&b_crf).then(a_last.cmp(&b_last))
+                })
+                .expect("The cache is non-empty.");
+            to_evict.insert(*victim);
+        }
+        to_evict
+    }
+
+    fn remove(&mut self, _: I) {
+        // like evicted items, removed items keep their CRFs
+    }
+
+    fn clear(&mut self) {
+        self.time = 0;
+        self.entries.clear();
+    }
+}
+
+/// The FBR (frequency-based replacement) policy.
+///
+/// Detailed in this paper: <https://dl.acm.org/doi/10.1145/98457.98523>
+///
+/// Items are kept in an LRU stack, split into a new section of the most recently used items, an
+/// old section of the least recently used items, and a middle section between them. Like LFU,
+/// each item has a reference count, but hits in the new section don't increment it, so a burst of
+/// correlated references only counts once. The victim is the item with the lowest count in the old
+/// section, with ties going to the least recently used.
+///
+/// The sections are the `new_ratio` and `old_ratio` (default 0.25 and 0.5) of the capacity,
+/// measured in item sizes, but the old section always has at least one item. The paper's periodic
+/// halving of the counts isn't implemented.
+///
+/// ```
+/// # use std::collections::HashSet;
+/// use cache_sim::{Cache, Fbr};
+///
+/// let mut c = Cache::<Fbr>::new(4);
+///
+/// for i in [0, 1, 2, 3, 0, 4, 5] {
+///     c.access(i);
+/// }
+///
+/// // 0 was reused outside the new section, so it outlasts 1 and 2 in the old section
+/// assert_eq!(c.set(), &HashSet::from([0, 3, 4, 5]));
+/// assert_eq!(c.replacement_policy().count(&0), Some(2));
+/// ```
+#[derive(Clone)]
+pub struct Fbr<I: Item = u32> {
+    /// The LRU stack, least recently used first.
+    stack: Vec<I>,
+    counts: HashMap<I, u32>,
+    new_ratio: f64,
+    old_ratio: f64,
+}
+
+impl<I: Item> Default for Fbr<I> {
+    fn default() -> Self {
+        Self::new(0.25, 0.5)
+    }
+}
+
+impl<I: Item> Fbr<I> {
+    /// Instantiate a new FBR replacement policy.
+    ///
+    /// The `new_ratio` and `old_ratio` parameters are the sizes of the new and old sections, as
+    /// ratios of the capacity of the cache.
+    ///
+    /// # Panics
+    ///
+    /// If either ratio is negative, or they add up to more than one.
+    #[must_use]
+    pub fn new(new_ratio: f64, old_ratio: f64) -> Self {
+        assert!(
+            new_ratio >= 0.0 && old_ratio >= 0.0 && new_ratio + old_ratio <= 1.0,
+            "The sections must fit in the stack."
+        );
+
+        Self {
+            stack: Vec::default(),
+            counts: HashMap::default(),
+            new_ratio,
+            old_ratio,
+        }
+    }
+
+    /// Get the reference count of a resident item.
+    #[must_use]
+    pub fn count(&self, item: &I) -> Option<u32> {
+        self.counts.get(item).copied()
+    }
+
+    /// Get the size of a section, given as a ratio of `capacity`.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn section_size(ratio: f64, capacity: u32) -> u32 {
+        (ratio * f64::from(capacity)).round() as u32
+    }
+
+    /// Evict items until the rest fit in `target`, choosing from the old section of a cache with
+    /// the given `capacity`.
+    fn evict<H: BuildHasher>(
+        &mut self,
+        set: &HashSet<I, H>,
+        capacity: u32,
+        target: u32,
+    ) -> HashSet<I> {
+        let old_size = Self::section_size(self.old_ratio, capacity);
+
+        let mut to_evict = HashSet::new();
+        while remaining_size(set, &to_evict) > target {
+            let old_len = self
+                .stack
+                .iter()
+                .scan(0, |size, i| {
+                    *size += i.size();
+                    Some(*size)
+                })
+                .take_while(|&size| size <= old_size)
+                .count()
+                .max(1);
+            let (index, _) = self.stack[..old_len]
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, i)| self.counts.get(i).copied().unwrap_or(0))
+                .expect("The cache is non-empty.");
+
+            let victim = self.stack.remove(index);
+            self.counts.remove(&victim);
+            to_evict.insert(victim);
+        }
+        to_evict
+    }
+}
+
+impl<I: Item, H: BuildHasher> ReplacementPolicy<I, H> for Fbr<I> {
+    fn update_state(&mut self, _: &HashSet<I, H>, capacity: u32, next: I) {
+        if let Some(index) = self.stack.iter().position(|&i| i == next) {
+            let more_recent = self.stack[index..].iter().map(Item::size).sum::<u32>();
+            if more_recent > Self::section_size(self.new_ratio, capacity) {
+                *self.counts.entry(next).or_insert(0) += 1;
+            }
+            self.stack.remove(index);
+        } else {
+            self.counts.insert(next, 1);
+        }
+        self.stack.push(next);
+    }
+
+    fn replace(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I> {
+        let to_evict = self.evict(set, capacity, capacity - next.size());
+        self.update_state(set, capacity, next);
+        to_evict
+    }
+
+    fn shrink(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        self.evict(set, capacity, capacity)
+    }
+
+    fn remove(&mut self, item: I) {
+        self.stack.retain(|&i| i != item);
+        self.counts.remove(&item);
+    }
+
+    fn clear(&mut self) {
+        self.stack.clear();
+        self.counts.clear();
+    }
+}
+
+/// The LFUDA (LFU with dynamic aging) replacement policy.
+///
+/// Detailed in this paper: <https://www.hpl.hp.com/techreports/98/HPL-98-173.pdf>
+///
+/// Each resident item has a key `K = L + frequency`, and the item with the lowest key is evicted.
+/// The global age `L` is set to the key of each evicted item, so that, like the inflation in
+/// [`Gdsf`], items which were once hot but haven't been accessed in a while eventually lose to
+/// newly active items. Unlike [`Lfu`], frequencies are forgotten on eviction.
+///
+/// Like [`Lfu`], ties between equal keys are broken by a tiebreaker policy, which defaults to
+/// [`Lru`].
+///
+/// ```
+/// # use std::collections::HashSet;
+/// use cache_sim::{Cache, Lfuda};
+///
+/// let mut c = Cache::<Lfuda>::new(2);
+///
+/// c.access_all([0, 0, 0, 1, 1, 2, 2]); // evicts 1, the age becomes 2
+/// c.access_all([3, 3]); // evicts 0, whose key of 3 is now lower than 2's
+///
+/// assert_eq!(c.set(), &HashSet::from([2, 3]));
+/// ```
+#[derive(Default, Clone)]
+pub struct Lfuda<I: Item = u32, T = Lru> {
+    /// The access frequency and key of each resident item.
+    entries: HashMap<I, (u32, u32)>,
+    age: u32,
+    tiebreaker: T,
+}
+
+impl<I: Item, T: Tiebreaker<I>> Lfuda<I, T> {
+    /// Get the current value of the global age `L`.
+    #[must_use]
+    pub const fn age(&self) -> u32 {
+        self.age
+    }
+}
+
+impl<I: Item, H: BuildHasher, T: Tiebreaker<I, H>> ReplacementPolicy<I, H> for Lfuda<I, T> {
+    fn update_state(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) {
+        let (frequency, key) = self.entries.entry(next).or_insert((0, 0));
+        *frequency += 1;
+        *key = self.age + *frequency;
+        self.tiebreaker.update_state(set, capacity, next);
+    }
+
+    fn replace(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I> {
+        let to_evict = self.shrink(set, capacity - next.size());
+        self.update_state(set, capacity, next);
+        to_evict
+    }
+
+    fn shrink(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        let mut to_evict = HashSet::new();
+        while remaining_size(set, &to_evict) > capacity {
+            let candidates = set.iter().filter(|i| !to_evict.contains(*i));
+            let min = candidates
+                .clone()
+                .map(|i| self.entries[i].1)
+                .min()
+                .expect("The set is non-empty.");
+
+            let evicted = self.tiebreaker.tiebreak(
+                &candidates
+                    .filter(|&i| self.entries[i].1 == min)
+                    .copied()
+                    .collect(),
+                1,
+            );
+
+            self.age = min;
+            for item in &evicted {
+                self.entries.remove(item);
+                self.tiebreaker.remove(*item);
+            }
+            to_evict.extend(evicted);
+        }
+        to_evict
+    }
+
+    fn remove(&mut self, item: I) {
+        self.entries.remove(&item);
+        self.tiebreaker.remove(item);
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.age = 0;
+        self.tiebreaker.clear();
+    }
+}
+
+/// The landlord replacement algotihm.
+///
+/// Detailed in this paper: <https://arxiv.org/abs/cs/0205033>
+///
+/// The tiebreaker (for evicting multiple zero-credit items) defaults to Lru.
+///
+/// ```
+/// # use std::collections::HashSet;
+/// use cache_sim::{Cache, Landlord, GeneralModelGenerator};
+///
+/// let mut cache = Cache::<Landlord, (), _>::new(3);
+/// let mut g = GeneralModelGenerator::new();
+///
+/// let a = g.item(1.0, 1);
+/// let b = g.item(0.5, 2);
+/// let c = g.item(100.0, 2);
+/// let d = g.item(1.0, 1);
+///
+/// cache.access(a);
+/// cache.access(b);
+/// cache.access(c);
+/// cache.access(d);
+///
+/// assert_eq!(cache.set(), &HashSet::from([c, d]));
+/// ```
+#[derive(Clone)]
+pub struct Landlord<I: Item = GeneralModelItem, T = Lru<GeneralModelItem>> {
+    credit: HashMap<I, f64>,
+    credit_increase: f64,
+    tiebreaker: T,
+}
+
+impl<I: Item, T: Tiebreaker<I> + Default> Default for Landlord<I, T> {
+    fn default() -> Self {
+        Self {
+            credit: HashMap::default(),
+            credit_increase: 1.0,
+            tiebreaker: T::default(),
+        }
+    }
+}
+
+impl<I: Item, T: Tiebreaker<I> + Default> Landlord<I, T> {
+    /// Instantiate a new landlord replacement policy.
+    ///
+    /// The `credit_increase` parameter represents the percentage of the gap between the current credit
+    /// and maximum credit (cost) to increase an item's credit when it is hit. It should not be above
+    /// one. Higher values are closer to LRU, lower values are closer to FIFO. This defaults to 1,
+    /// and should generally be between 0 and 1.
+    #[must_use]
+    pub fn new(credit_increase: f64) -> Self {
+        Self {
+            credit: HashMap::default(),
+            credit_increase,
+            tiebreaker: T::default(),
+        }
+    }
+}
+
+impl<I: Item, T: Tiebreaker<I>> Landlord<I, T> {
+    /// Instantiate a new landlord replacement policy, with a specifically configured tiebreaker.
+    ///
+    /// The `credit_increase` parameter represents the percentage of the gap between the current credit
+    /// and maximum credit (cost) to increase an item's credit when it is hit. It should not be above
+    /// one. Higher values are closer to LRU, lower values are closer to FIFO. This defaults to 1,
+    /// and should generally be between 0 and 1.
+    #[must_use]
+    pub fn with_tiebreaker(tiebreaker: T, credit_increase: f64) -> Self {
+        Self {
+            credit: HashMap::default(),
+            credit_increase,
+            tiebreaker,
+        }
+    }
+}
+
+impl<I: Item, H: BuildHasher, T: Tiebreaker<I, H>> ReplacementPolicy<I, H> for Landlord<I, T> {
+    fn update_state(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) {
+        // here we know that there is room in the cache, so we don't need to do the while loop in
+        // the algorithm
+        if set.contains(&next) {
+            if let Some(current_credit) = self.credit.get_mut(&next) {
+                *current_credit += (next.cost() - *current_credit) * self.credit_increase;
+            } else {
+                // should be impossible, because we know `next` is in the set.
+                self.credit.insert(next, next.cost());
+            }
+        } else {
+            self.credit.insert(next, next.cost());
+        }
+
+        self.tiebreaker.update_state(set, capacity, next);
+    }
+
+    fn replace(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I> {
+        let to_evict = self.shrink(set, capacity - next.size());
+        self.update_state(set, capacity, next);
+        to_evict
+    }
+
+    fn shrink(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        let mut to_evict = HashSet::default();
+
+        while remaining_size(set, &to_evict) > capacity {
+            // have to compute min cost by hand because of limitations with float
+            let mut current_delta = f64::MAX;
+            let mut current_min_item = None;
+            for item in set {
+                let item_delta = *self
+                    .credit
+                    .get(item)
+                    .expect("Items in the set have a credit.")
+                    / f64::from(item.size());
+                if item_delta < current_delta {
+                    current_delta = item_delta;
+                    current_min_item = Some(item);
+                }
+            }
+
+            let min = current_min_item.expect("The set is non-empty.");
+            let delta =
+                self.credit.get(min).expect("The item is in the set.") / f64::from(min.size());
+
+            // decrease the credit for everything in the set
+            for item in set {
+                *self.credit.get_mut(item).expect("The item is in the set.") -=
+                    delta * f64::from(item.size());
+            }
+
+            // evict items with no credit
+            let evicted = self.tiebreaker.tiebreak(
+                &set.iter()
+                    .filter(|&i| !to_evict.contains(i))
+                    .filter(|i| {
+                        abs_diff_eq!(self.credit.get(i).expect("The item is in the set."), &0.0)
+                    })
+                    .copied()
+                    .collect(),
+                remaining_size(set, &to_evict) - capacity,
+            );
+
+            for item in &evicted {
+                self.tiebreaker.remove(*item);
+            }
+            to_evict.extend(evicted);
+        }
+
+        to_evict
+    }
+
+    fn remove(&mut self, item: I) {
+        self.credit.remove(&item);
+        self.tiebreaker.remove(item);
+    }
+
+    fn clear(&mut self) {
+        self.credit.clear();
+        self.tiebreaker.clear();
+    }
+}
+
+/// The ARC replacement policy, which adapts between recency and frequency.
+///
+/// Detailed in this paper: <https://www.usenix.org/conference/fast-03/arc-self-tuning-low-overhead-replacement-cache>
+///
+/// Resident items are split between `t1`, which holds items seen once recently, and `t2`, which
+/// holds items seen at least twice recently. The ghost lists `b1` and `b2` remember the keys most
+/// recently evicted from `t1` and `t2` respectively. A hit in `b1` suggests `t1` is too small, so
+/// the target size `p` of `t1` grows; a hit in `b2` shrinks it. The ghost lists are [`Exact`] by
+/// default, but any [`GhostStore`] will do.
+///
+/// ```
+/// # use std::collections::HashSet;
+/// use cache_sim::{Arc, Cache};
+///
+/// let mut c = Cache::<Arc>::new(2);
+///
+/// c.access(0);
+/// c.access(0);
+/// c.access(1);
+/// c.access(2);
+///
+/// // 0 was seen twice, so it survives the eviction
+/// assert_eq!(c.set(), &HashSet::from([0, 2]));
+/// ```
+#[derive(Default, Clone)]
+pub struct Arc<I: Item = u32, G = Exact<I>> {
+    t1: VecDeque<I>,
+    t2: VecDeque<I>,
+    b1: G,
+    b2: G,
+    p: u32,
+}
+
+impl<I: Item, G: GhostStore<I>> Arc<I, G> {
+    /// Instantiate a new ARC replacement policy, keeping `b1` and `b2` in the given ghost stores.
+    #[must_use]
+    pub const fn with_ghosts(b1: G, b2: G) -> Self {
+        Self {
+            t1: VecDeque::new(),
+            t2: VecDeque::new(),
+            b1,
+            b2,
+            p: 0,
+        }
+    }
+
+    /// Get the current target size of `t1`.
+    #[must_use]
+    pub const fn p(&self) -> u32 {
+        self.p
+    }
+
+    /// Remove `item` from `list`, returning whether it was present.
+    fn remove(list: &mut VecDeque<I>, item: I) -> bool {
+        list.iter()
+            .position(|&i| i == item)
+            .and_then(|index| list.remove(index))
+            .is_some()
+    }
+
+    /// Evict the least recently used item of either `t1` or `t2`, depending on `p`, and remember
+    /// it in the corresponding ghost list.
+    fn evict(&mut self, in_b2: bool) -> I {
+        #[allow(clippy::cast_possible_truncation)] // the lists are bounded by the capacity
+        let t1_len = self.t1.len() as u32;
+
+        // spurious ghost hits from an inexact store can push p past t1's length with t2 empty
+        if !self.t1.is_empty()
+            && (self.t2.is_empty() || t1_len > self.p || (in_b2 && t1_len == self.p))
+        {
+            let item = self.t1.pop_front().expect("t1 is non-empty.");
+            self.b1.insert(item);
+            item
+        } else {
+            let item = self.t2.pop_front().expect("The cache is non-empty.");
+            self.b2.insert(item);
+            item
+        }
+    }
+
+    /// Update the lists after an access to `next`, evicting an item if the cache is `full`.
+    fn access(&mut self, capacity: u32, next: I, full: bool) -> Option<I> {
+        if Self::remove(&mut self.t1, next) || Self::remove(&mut self.t2, next) {
+            self.t2.push_back(next);
+            return None;
+        }
+
+        let capacity_len = capacity as usize;
+        let evicted;
+
+        // inexact ghost stores can find ghosts in a list they think is empty
+        if self.b1.contains(&next) {
+            #[allow(clippy::cast_possible_truncation)] // the lists are bounded by the capacity
+            let delta = (self.b2.len() / self.b1.len().max(1)).max(1) as u32;
+            self.p = (self.p + delta).min(capacity);
+            self.b1.remove(&next);
+            evicted = full.then(|| self.evict(false));
+            self.t2.push_back(next);
+        } else if self.b2.contains(&next) {
+            #[allow(clippy::cast_possible_truncation)] // the lists are bounded by the capacity
+            let delta = (self.b1.len() / self.b2.len().max(1)).max(1) as u32;
+            self.p = self.p.saturating_sub(delta);
+            self.b2.remove(&next);
+            evicted = full.then(|| self.evict(true));
+            self.t2.push_back(next);
+        } else {
+            if self.t1.len() + self.b1.len() >= capacity_len {
+                if self.t1.len() < capacity_len {
+                    self.b1.truncate(self.b1.len().saturating_sub(1));
+                    evicted = full.then(|| self.evict(false));
+                } else {
+                    // b1 is empty, so there's nothing to gain from remembering the eviction
+                    evicted = if full { self.t1.pop_front() } else { None };
+                }
+            } else {
+                let total = self.t1.len() + self.t2.len() + self.b1.len() + self.b2.len();
+                if total >= 2 * capacity_len {
+                    self.b2.truncate(self.b2.len().saturating_sub(1));
+                }
+                evicted = full.then(|| self.evict(false));
+            }
+            self.t1.push_back(next);
+        }
+
+        evicted
+    }
+}
+
+impl<I: Item, H: BuildHasher, G: GhostStore<I>> ReplacementPolicy<I, H> for Arc<I, G> {
+    fn update_state(&mut self, _: &HashSet<I, H>, capacity: u32, next: I) {
+        self.access(capacity, next, false);
+    }
+
+    fn replace(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I> {
+        let evicted = self
+            .access(capacity, next, true)
+            .expect("The cache is full.");
+        evict_until_fits(set, capacity, next, HashSet::from([evicted]), || {
+            Some(self.evict(false))
+        })
+    }
+
+    fn shrink(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        self.p = self.p.min(capacity);
+
+        let mut to_evict = HashSet::new();
+        while remaining_size(set, &to_evict) > capacity {
+            to_evict.insert(self.evict(false));
+        }
+
+        // the ghost lists are bounded by the capacity too
+        let capacity = capacity as usize;
+        self.b1.truncate(capacity.saturating_sub(self.t1.len()));
+        self.b2
+            .truncate((2 * capacity).saturating_sub(self.t1.len() + self.t2.len() + self.b1.len()));
+
+        to_evict
+    }
+
+    fn remove(&mut self, item: I) {
+        if !Self::remove(&mut self.t1, item) {
+            Self::remove(&mut self.t2, item);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.t1.clear();
+        self.t2.clear();
+        self.b1.clear();
+        self.b2.clear();
+        self.p = 0;
+    }
+}
+
+/// The CAR (clock with adaptive replacement) replacement policy, a clock-based variant of ARC.
+///
+/// Detailed in this paper: <https://www.usenix.org/conference/fast-04/car-clock-adaptive-replacement>
+///
+/// Like [`Arc`], resident items are split between `t1` and `t2`, with ghost lists `b1` and `b2`
+/// adapting the target size `p` of `t1`. But `t1` and `t2` are clocks: a hit only sets the item's
+/// reference bit, instead of moving it. On eviction, the hand sweeps `t1` if it's at least `p`
+/// long and `t2` otherwise; referenced items in `t1` move to `t2`, referenced items in `t2` go
+/// back to its tail, and the first unreferenced item is evicted to the corresponding ghost list.
+/// The ghost lists are [`Exact`] by default, but any [`GhostStore`] will do.
+///
+/// ```
+/// # use std::collections::HashSet;
+/// use cache_sim::{Cache, Car};
+///
+/// let mut c = Cache::<Car>::new(2);
+///
+/// c.access(0);
+/// c.access(0);
+/// c.access(1);
+/// c.access(2);
+///
+/// // 0 was referenced, so the hand moves it to t2 and evicts 1 instead
+/// assert_eq!(c.set(), &HashSet::from([0, 2]));
+/// ```
+#[derive(Default, Clone)]
+pub struct Car<I: Item = u32, G = Exact<I>> {
+    /// The recency clock, from the hand, with each item's reference bit.
+    t1: VecDeque<(I, bool)>,
+    /// The frequency clock, from the hand, with each item's reference bit.
+    t2: VecDeque<(I, bool)>,
+    b1: G,
+    b2: G,
+    p: u32,
+}
+
+impl<I: Item, G: GhostStore<I>> Car<I, G> {
+    /// Instantiate a new CAR replacement policy, keeping `b1` and `b2` in the given ghost stores.
+    #[must_use]
+    pub const fn with_ghosts(b1: G, b2: G) -> Self {
+        Self {
+            t1: VecDeque::new(),
+            t2: VecDeque::new(),
+            b1,
+            b2,
+            p: 0,
+        }
+    }
+
+    /// Get the current target size of `t1`.
+    #[must_use]
+    pub const fn p(&self) -> u32 {
+        self.p
+    }
+
+    /// Sweep the clocks until an unreferenced item is found, evicting it to its ghost list.
+    fn evict(&mut self) -> I {
+        loop {
+            #[allow(clippy::cast_possible_truncation)] // the clocks are bounded by the capacity
+            let t1_len = self.t1.len() as u32;
+
+            if !self.t1.is_empty() && (t1_len >= self.p.max(1) || self.t2.is_empty()) {
+                let (item, referenced) = self.t1.pop_front().expect("t1 is non-empty.");
+                if !referenced {
+                    self.b1.insert(item);
+                    return item;
+                }
+                self.t2.push_back((item, false));
+            } else {
+                let (item, referenced) = self.t2.pop_front().expect("The cache is non-empty.");
+                if !referenced {
+                    self.b2.insert(item);
+                    return item;
+                }
+                self.t2.push_back((item, false));
+            }
+        }
+    }
+
+    /// Update the clocks after an access to `next`, evicting an item if the cache is `full`.
+    fn access(&mut self, capacity: u32, next: I, full: bool) -> Option<I> {
+        if let Some((_, referenced)) = self
+            .t1
+            .iter_mut()
+            .chain(self.t2.iter_mut())
+            .find(|(i, _)| *i == next)
+        {
+            *referenced = true;
+            return None;
+        }
+
+        let capacity_len = capacity as usize;
+        let in_ghosts = self.b1.contains(&next) || self.b2.contains(&next);
+
+        let evicted = full.then(|| self.evict());
+        if full && !in_ghosts {
+            if self.t1.len() + self.b1.len() >= capacity_len {
+                self.b1.truncate(self.b1.len().saturating_sub(1));
+            } else if self.t1.len() + self.t2.len() + self.b1.len() + self.b2.len()
+                >= 2 * capacity_len
+            {
+                self.b2.truncate(self.b2.len().saturating_sub(1));
+            }
+        }
+
+        // inexact ghost stores can find ghosts in a list they think is empty
+        if self.b1.contains(&next) {
+            #[allow(clippy::cast_possible_truncation)] // the lists are bounded by the capacity
+            let delta = (self.b2.len() / self.b1.len().max(1)).max(1) as u32;
+            self.p = (self.p + delta).min(capacity);
+            self.b1.remove(&next);
+            self.t2.push_back((next, false));
+        } else if self.b2.contains(&next) {
+            #[allow(clippy::cast_possible_truncation)] // the lists are bounded by the capacity
+            let delta = (self.b1.len() / self.b2.len().max(1)).max(1) as u32;
+            self.p = self.p.saturating_sub(delta);
+            self.b2.remove(&next);
+            self.t2.push_back((next, false));
+        } else {
+            self.t1.push_back((next, false));
+        }
+
+        evicted
+    }
+}
+
+impl<I: Item, H: BuildHasher, G: GhostStore<I>> ReplacementPolicy<I, H> for Car<I, G> {
+    fn update_state(&mut self, _: &HashSet<I, H>, capacity: u32, next: I) {
+        self.access(capacity, next, false);
+    }
+
+    fn replace(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I> {
+        let evicted = self
+            .access(capacity, next, true)
+            .expect("The cache is full.");
+        evict_until_fits(set, capacity, next, HashSet::from([evicted]), || {
+            Some(self.evict())
+        })
+    }
+
+    fn shrink(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        self.p = self.p.min(capacity);
+
+        let mut to_evict = HashSet::new();
+        while remaining_size(set, &to_evict) > capacity {
+            to_evict.insert(self.evict());
+        }
+
+        // the ghost lists are bounded by the capacity too
+        let capacity = capacity as usize;
+        self.b1.truncate(capacity.saturating_sub(self.t1.len()));
+        self.b2
+            .truncate((2 * capacity).saturating_sub(self.t1.len() + self.t2.len() + self.b1.len()));
+
+        to_evict
+    }
+
+    fn remove(&mut self, item: I) {
+        self.t1.retain(|&(i, _)| i != item);
+        self.t2.retain(|&(i, _)| i != item);
+    }
+
+    fn clear(&mut self) {
+        self.t1.clear();
+        self.t2.clear();
+        self.b1.clear();
+        self.b2.clear();
+        self.p = 0;
+    }
+}
+
+/// The status of a page in [`ClockPro`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ClockProStatus {
+    Hot,
+    Cold,
+    /// A non-resident cold page, still in its test period.
+    Test,
+}
+
+/// A page in the [`ClockPro`] clock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ClockProPage<I> {
+    item: I,
+    status: ClockProStatus,
+    referenced: bool,
+}
+
+/// The CLOCK-Pro replacement policy, a clock-based approximation of [`Lirs`].
+///
+/// Detailed in this paper: <https://www.usenix.org/legacy/event/usenix05/tech/general/full_papers/jiang/jiang.pdf>
+///
+/// Pages are hot, cold, or test pages, which are non-resident cold pages still in their test
+/// period, and all three kinds share one clock in recency order. Three hands sweep the clock:
+/// - `HAND_cold` finds a victim on a miss: a referenced cold page is promoted to hot, and an
+///   unreferenced one is evicted, staying in the clock as a test page.
+/// - `HAND_hot` demotes the first unreferenced hot page to cold, whenever there are more hot pages
+///   than the capacity minus the cold target, clearing the reference bits of the hot pages it
+///   passes.
+/// - `HAND_test` ends the test periods of test pages, removing them from the clock, whenever
+///   there are more test pages than the capacity.
+///
+/// A miss on a test page means its reuse distance was short enough for it to be hot, so it's
+/// readmitted as a hot page and the cold target grows; a test page whose test period ends without
+/// a reuse shrinks the cold target instead. As in the authors' simplified implementation, every
+/// resident cold page is in its test period. New pages are inserted behind `HAND_hot`, at the
+/// head of the clock.
+///
+/// ```
+/// # use std::collections::HashSet;
+/// use cache_sim::{Cache, ClockPro};
+///
+/// let mut c = Cache::<ClockPro>::new(3);
+///
+/// c.access(0);
+/// c.access(1);
+/// c.access(2);
+/// c.access(0);
+/// c.access(3); // HAND_cold promotes 0 to hot, then evicts 1
+///
+/// assert_eq!(c.set(), &HashSet::from([0, 2, 3]));
+/// assert!(c.replacement_policy().is_hot(&0));
+/// ```
+#[derive(Clone)]
+pub struct ClockPro<I: Item = u32> {
+    /// The clock, with hands indexing into it; new pages go just before `hot_hand`.
+    clock: VecDeque<ClockProPage<I>>,
+    hot_hand: usize,
+    cold_hand: usize,
+    test_hand: usize,
+    cold_target: u32,
+}
+
+impl<I: Item> Default for ClockPro<I> {
+    fn default() -> Self {
+        Self {
+            clock: VecDeque::default(),
+            hot_hand: 0,
+            cold_hand: 0,
+            test_hand: 0,
+            cold_target: 1,
+        }
+    }
+}
+
+impl<I: Item> ClockPro<I> {
+    /// Check whether an item is a hot page.
+    #[must_use]
+    pub fn is_hot(&self, item: &I) -> bool {
+        self.status(item) == Some(ClockProStatus::Hot)
+    }
+
+    /// Get the current target number of resident cold pages.
+    #[must_use]
+    pub const fn cold_target(&self) -> u32 {
+        self.cold_target
+    }
+
+    fn status(&self, item: &I) -> Option<ClockProStatus> {
+        self.clock
+            .iter()
+            .find(|page| page.item == *item)
+            .map(|page| page.status)
+    }
+
+    #[allow(clippy::cast_possible_truncation)] // the clock is bounded by twice the capacity
+    fn count(&self, status: ClockProStatus) -> u32 {
+        self.clock
+            .iter()
+            .filter(|page| page.status == status)
+            .count() as u32
+    }
+
+    /// Insert a page at the head of the clock, keeping every hand on its page.
+    fn insert(&mut self, page: ClockProPage<I>) {
+        let index = self.hot_hand;
+        self.clock.insert(index, page);
+        if self.clock.len() > 1 {
+            for hand in [&mut self.hot_hand, &mut self.cold_hand, &mut self.test_hand] {
+                if *hand >= index {
+                    *hand += 1;
+                }
+            }
+        }
+    }
+
+    /// Take the page at `index` out of the clock; hands on it move on to the next page.
+    fn take(&mut self, index: usize) -> ClockProPage<I> {
+        let page = self
+            .clock
+            .remove(index)
+            .expect("The index is in the clock.");
+        let len = self.clock.len();
+        for hand in [&mut self.hot_hand, &mut self.cold_hand, &mut self.test_hand] {
+            if *hand > index {
+                *hand -= 1;
+            }
+            if *hand >= len {
+                *hand = 0;
+            }
+        }
+        page
+    }
+
+    /// Move `HAND_cold` one page, returning the item it evicts, if any.
+    fn run_cold_hand(&mut self, capacity: u32) -> Option<I> {
+        // the hot pages are bounded by numbers of items, so large enough items can leave no cold
+        // page to evict, in which case we need to demote one first
+        while self.count(ClockProStatus::Cold) == 0 {
+            self.run_hot_hand();
+        }
+
+        let page = &mut self.clock[self.cold_hand];
+        let mut evicted = None;
+        if page.status == ClockProStatus::Cold {
+            if page.referenced {
+                page.status = ClockProStatus::Hot;
+                page.referenced = false;
+            } else {
+                page.status = ClockProStatus::Test;
+                evicted = Some(page.item);
+            }
+        }
+        self.cold_hand = (self.cold_hand + 1) % self.clock.len();
+
+        while self.count(ClockProStatus::Test) > capacity {
+            self.run_test_hand();
+        }
+        while self.count(ClockProStatus::Hot) > capacity.saturating_sub(self.cold_target) {
+            self.run_hot_hand();
+        }
+
+        evicted
+    }
+
+    /// Move `HAND_hot` one page, demoting it if it's an unreferenced hot page.
+    fn run_hot_hand(&mut self) {
+        // the hot hand can't pass the test hand, since test pages behind it would never expire
+        if self.hot_hand == self.test_hand {
+            self.run_test_hand();
+        }
+
+        let page = &mut self.clock[self.hot_hand];
+        if page.status == ClockProStatus::Hot {
+            if page.referenced {
+                page.referenced = false;
+            } else {
+                page.status = ClockProStatus::Cold;
+            }
+        }
+        self.hot_hand = (self.hot_hand + 1) % self.clock.len();
+    }
+
+    /// Move `HAND_test` one page, ending its test period if it's a test page.
+    fn run_test_hand(&mut self) {
+        if self.clock[self.test_hand].status == ClockProStatus::Test {
+            self.take(self.test_hand);
+            self.cold_target = self.cold_target.saturating_sub(1).max(1);
+        } else {
+            self.test_hand = (self.test_hand + 1) % self.clock.len();
+        }
+    }
+
+    /// Update the clock after an access to `next`, evicting an item if the cache is `full`.
+    fn access(&mut self, capacity: u32, next: I, full: bool) -> Option<I> {
+        let index = self.clock.iter().position(|page| page.item == next);
+        if let Some(index) = index {
+            if self.clock[index].status != ClockProStatus::Test {
+                self.clock[index].referenced = true;
+                return None;
+            }
+        }
+
+        let mut evicted = None;
+        while full && evicted.is_none() {
+            evicted = self.run_cold_hand(capacity);
+        }
+
+        // the test page may have moved, or expired, while the cold hand found a victim
+        match self.clock.iter().position(|page| page.item == next) {
+            Some(index) => {
+                self.take(index);
+                self.cold_target = (self.cold_target + 1).min(capacity.max(1));
+                self.insert(ClockProPage {
+                    item: next,
+                    status: ClockProStatus::Hot,
+                    referenced: false,
+                });
+                while self.count(ClockProStatus::Hot) > capacity.saturating_sub(self.cold_target) {
+                    self.run_hot_hand();
+                }
+            }
+            None => self.insert(ClockProPage {
+                item: next,
+                status: ClockProStatus::Cold,
+                referenced: false,
+            }),
+        }
+
+        evicted
+    }
+}
+
+impl<I: Item, H: BuildHasher> ReplacementPolicy<I, H> for ClockPro<I> {
+    fn update_state(&mut self, _: &HashSet<I, H>, capacity: u32, next: I) {
+        self.access(capacity, next, false);
+    }
+
+    fn replace(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I> {
+        let evicted = self
+            .access(capacity, next, true)
+            .expect("The cache is full.");
+        evict_until_fits(set, capacity, next, HashSet::from([evicted]), || {
+            self.run_cold_hand(capacity)
+        })
+    }
+
+    fn shrink(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        self.cold_target = self.cold_target.min(capacity).max(1);
+
+        let mut to_evict = HashSet::new();
+        while remaining_size(set, &to_evict) > capacity {
+            if let Some(item) = self.run_cold_hand(capacity) {
+                to_evict.insert(item);
+            }
+        }
+
+        while self.count(ClockProStatus::Test) > capacity {
+            self.run_test_hand();
+        }
+
+        to_evict
+    }
+
+    fn remove(&mut self, item: I) {
+        if let Some(index) = self.clock.iter().position(|page| page.item == item) {
+            self.take(index);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.clock.clear();
+        self.hot_hand = 0;
+        self.cold_hand = 0;
+        self.test_hand = 0;
+        self.cold_target = 1;
+    }
+}
+
+/// The GDSF (greedy dual size frequency) replacement policy.
+///
+/// Detailed in this paper: <https://www.hpl.hp.com/techreports/98/HPL-98-173.pdf>
+///
+/// Each resident item has a priority `H = L + frequency * cost / size`, and the item with the
+/// lowest priority is evicted. The global inflation value `L` is set to the priority of each
+/// evicted item, so items which haven't been accessed in a while age out relative to newly
+/// accessed items. Costs and sizes come from the item model, so this is most useful with
+/// [`GeneralModelItem`]s.
+///
+/// ```
+/// # use std::collections::HashSet;
+/// use cache_sim::{Cache, Gdsf, GeneralModelGenerator};
+///
+/// let mut cache = Cache::<Gdsf, (), _>::new(3);
+/// let mut g = GeneralModelGenerator::new();
+///
+/// let a = g.item(1.0, 1);
+/// let b = g.item(1.0, 2);
+/// let c = g.item(1.0, 1);
+///
+/// cache.access(a);
+/// cache.access(b);
+/// cache.access(c); // b has the lowest cost per unit of size
+///
+/// assert_eq!(cache.set(), &HashSet::from([a, c]));
+/// ```
+#[derive(Clone)]
+pub struct Gdsf<I: Item = GeneralModelItem> {
+    /// The access frequency and priority of each resident item.
+    entries: HashMap<I, (u32, f64)>,
+    inflation: f64,
+}
+
+impl<I: Item> Default for Gdsf<I> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::default(),
+            inflation: 0.0,
+        }
+    }
+}
+
+impl<I: Item> Gdsf<I> {
+    /// Get the current value of the global inflation value `L`.
+    #[must_use]
+    pub const fn inflation(&self) -> f64 {
+        self.inflation
+    }
+}
+
+impl<I: Item, H: BuildHasher> ReplacementPolicy<I, H> for Gdsf<I> {
+    fn update_state(&mut self, _: &HashSet<I, H>, _: u32, next: I) {
+        let (frequency, priority) = self.entries.entry(next).or_insert((0, 0.0));
+        *frequency += 1;
+        *priority = self.inflation + f64::from(*frequency) * next.cost() / f64::from(next.size());
+    }
+
+    fn replace(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I> {
+        let to_evict = self.shrink(set, capacity - next.size());
+        self.update_state(set, capacity, next);
+        to_evict
+    }
+
+    fn shrink(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        let mut to_evict = HashSet::default();
+
+        while remaining_size(set, &to_evict) > capacity {
+            // have to compute min priority by hand because of limitations with float
+            let mut current_min = f64::MAX;
+            let mut current_min_item = None;
+            for item in set.iter().filter(|i| !to_evict.contains(*i)) {
+                let (_, priority) = self.entries[item];
+                if priority < current_min {
+                    current_min = priority;
+                    current_min_item = Some(*item);
+                }
+            }
+
+            let min = current_min_item.expect("The set is non-empty.");
+            self.inflation = current_min;
+            self.entries.remove(&min);
+            to_evict.insert(min);
+        }
+
+        to_evict
+    }
+
+    fn remove(&mut self, item: I) {
+        self.entries.remove(&item);
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.inflation = 0.0;
+    }
+}
+
+/// A count-min sketch, for estimating access frequencies in bounded space.
+///
+/// After `window` increments, every counter is halved, so that the estimates favor recent
+/// history.
+#[derive(Clone)]
+struct CountMinSketch {
+    counters: Vec<u32>,
+    width: usize,
+    depth: usize,
+    window: u32,
+    samples: u32,
+}
+
+impl CountMinSketch {
+    fn new(width: usize, depth: usize, window: u32) -> Self {
+        Self {
+            counters: vec![0; width * depth],
+            width,
+            depth,
+            window,
+            samples: 0,
+        }
+    }
+
+    /// Get the index of the counter for `item` in `row`.
+    fn index<I: Item>(&self, row: usize, item: &I) -> usize {
+        #[allow(clippy::cast_possible_truncation)] // the result is less than the width
+        let column = (stable_hash(&(row, item)) % self.width as u64) as usize;
+        row * self.width + column
+    }
+
+    /// Record an access to `item`.
+    fn increment<I: Item>(&mut self, item: &I) {
+        for row in 0..self.depth {
+            let index = self.index(row, item);
+            self.counters[index] = self.counters[index].saturating_add(1);
+        }
+
+        self.samples += 1;
+        if self.samples >= self.window {
+            for counter in &mut self.counters {
+                *counter /= 2;
+            }
+            self.samples = 0;
+        }
+    }
+
+    /// Forget every recorded access.
+    fn clear(&mut self) {
+        self.counters.fill(0);
+        self.samples = 0;
+    }
+
+    /// Estimate the number of recorded accesses to `item`.
+    fn estimate<I: Item>(&self, item: &I) -> u32 {
+        (0..self.depth)
+            .map(|row| self.counters[self.index(row, item)])
+            .min()
+            .unwrap_or(0)
+    }
+}
+
+/// The `TinyLFU` admission policy, which wraps another replacement policy.
+///
+/// Detailed in this paper: <https://arxiv.org/abs/1512.00727>
+///
+/// Access frequencies are estimated with a count-min sketch, whose counters are halved every
+/// `window` accesses. On a miss in a full cache, the wrapped policy proposes a victim, and the new
+/// item is only admitted if its estimated frequency is higher than the victim's; otherwise it
+/// bypasses the cache, and the wrapped policy never sees it.
+///
+/// The victim is proposed by [`ReplacementPolicy::victims`], without touching the wrapped policy,
+/// since we might not go through with the eviction. That falls back on replacing on a copy of the
+/// wrapped policy, which is why it must be [`Clone`], but [`Lru`] and [`Fifo`] find their victims
+/// directly.
+///
+/// ```
+/// # use std::collections::HashSet;
+/// use cache_sim::{Cache, Lru, TinyLfu};
+///
+/// let mut c = Cache::<TinyLfu<Lru>>::new(2);
+///
+/// c.access(0);
+/// c.access(0);
+/// c.access(1);
+/// c.access(2); // 2 has been seen less often than 0, so it isn't admitted
+///
+/// assert_eq!(c.set(), &HashSet::from([0, 1]));
+/// ```
+#[derive(Clone)]
+pub struct TinyLfu<P> {
+    policy: P,
+    sketch: CountMinSketch,
+}
+
+impl<P: Default> Default for TinyLfu<P> {
+    fn default() -> Self {
+        Self::new(P::default(), 1024, 4, 10_000)
+    }
+}
+
+impl<P> TinyLfu<P> {
+    /// Wrap a replacement policy with `TinyLFU` admission.
+    ///
+    /// The sketch has `depth` rows of `width` counters, which are halved every `window` accesses.
+    ///
+    /// # Panics
+    ///
+    /// If `width` or `depth` is zero.
+    #[must_use]
+    pub fn new(policy: P, width: usize, depth: usize, window: u32) -> Self {
+        assert!(width > 0 && depth > 0, "The sketch must be non-empty.");
+
+        Self {
+            policy,
+            sketch: CountMinSketch::new(width, depth, window),
+        }
+    }
+
+    /// Get a reference to the wrapped replacement policy.
+    pub const fn policy(&self) -> &P {
+        &self.policy
+    }
+}
+
+impl<I: Item, H: BuildHasher, P: ReplacementPolicy<I, H> + Clone> ReplacementPolicy<I, H>
+    for TinyLfu<P>
+{
+    fn update_state(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) {
+        self.sketch.increment(&next);
+        self.policy.update_state(set, capacity, next);
+    }
+
+    fn replace(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I> {
+        self.sketch.increment(&next);
+
+        let victim_frequency = self
+            .policy
+            .victims(set, capacity, next)
+            .iter()
+            .map(|victim| self.sketch.estimate(victim))
+            .max()
+            .unwrap_or(0);
+
+        if self.sketch.estimate(&next) > victim_frequency {
+            self.policy.replace(set, capacity, next)
+        } else {
+            HashSet::from([next])
+        }
+    }
+
+    fn shrink(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        // admission only matters for new items, so there's nothing to decide here
+        self.policy.shrink(set, capacity)
+    }
+
+    fn remove(&mut self, item: I) {
+        self.policy.remove(item);
+    }
+
+    fn clear(&mut self) {
+        self.policy.clear();
+        self.sketch.clear();
+    }
+
+    fn prefetch(&mut self, next: I) -> Vec<I> {
+        self.policy.prefetch(next)
+    }
+}
+
+/// The W-TinyLFU replacement policy, which puts an LRU window in front of an [`Slru`] main region
+/// guarded by `TinyLFU` admission.
+///
+/// Detailed in this paper: <https://arxiv.org/abs/1512.00727>
+///
+/// New items always enter the window, which takes up the `window_ratio` (default 0.01) of the
+/// capacity, but at least one item's worth. Items pushed out of the window are candidates for the
+/// main region, and when it's full a candidate is only admitted if its estimated frequency is
+/// higher than each of the main region's victims it would replace; otherwise the candidate is
+/// evicted, and so are candidates which wouldn't fit even in place of the whole main region. So an
+/// item has to be accessed again while it's in the window, or often enough to beat the victims, to
+/// get into the main region, which keeps bursts of one-hit wonders from flushing it. Frequencies
+/// are estimated with the same aging count-min sketch as [`TinyLfu`].
+///
+/// The newest item always stays in the window, even if it's larger than the window's share, so
+/// new items are never bypassed.
+///
+/// ```
+/// # use std::collections::HashSet;
+/// use cache_sim::{Cache, WTinyLfu};
+///
+/// let mut c = Cache::<WTinyLfu>::new(3);
+///
+/// c.access_all([0, 0, 1, 1, 2]);
+/// c.access(3); // 2 leaves the window, but it's been seen less often than the main region's 0
+///
+/// assert_eq!(c.set(), &HashSet::from([0, 1, 3]));
+/// assert!(c.replacement_policy().in_window(&3));
+/// ```
+#[derive(Clone)]
+pub struct WTinyLfu<I: Item = u32> {
+    /// The window, least recently used first.
+    window: VecDeque<I>,
+    main: Slru<I>,
+    sketch: CountMinSketch,
+    window_ratio: f64,
+}
+
+impl<I: Item> Default for WTinyLfu<I> {
+    fn default() -> Self {
+        Self::new(0.01, 1024, 4, 10_000)
+    }
+}
+
+impl<I: Item> WTinyLfu<I> {
+    /// Instantiate a new W-TinyLFU replacement policy, whose window takes up the `window_ratio` of
+    /// the capacity.
+    ///
+    /// Like [`TinyLfu::new`], the sketch has `depth` rows of `width` counters, which are halved
+    /// every `sample` accesses.
+    ///
+    /// # Panics
+    ///
+    /// If `window_ratio` isn't between 0 and 1, or `width` or `depth` is zero.
+    #[must_use]
+    pub fn new(window_ratio: f64, width: usize, depth: usize, sample: u32) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&window_ratio),
+            "The window ratio must be in [0, 1]."
+        );
+        assert!(width > 0 && depth > 0, "The sketch must be non-empty.");
+
+        Self {
+            window: VecDeque::new(),
+            main: Slru::default(),
+            sketch: CountMinSketch::new(width, depth, sample),
+            window_ratio,
+        }
+    }
+
+    /// Check whether an item is in the window.
+    #[must_use]
+    pub fn in_window(&self, item: &I) -> bool {
+        self.window.contains(item)
+    }
+
+    /// Get the number of items in the window.
+    #[must_use]
+    pub fn window_len(&self) -> usize {
+        self.window.len()
+    }
+
+    /// Get the number of items in the main region.
+    #[must_use]
+    pub fn main_len(&self) -> usize {
+        self.main.probationary_len() + self.main.protected_len()
+    }
+
+    /// Get the main region.
+    #[must_use]
+    pub const fn main(&self) -> &Slru<I> {
+        &self.main
+    }
+
+    /// Get the window's share of `capacity`.
+    fn window_capacity(&self, capacity: u32) -> u32 {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let window_capacity = (self.window_ratio * f64::from(capacity)).round() as u32;
+        window_capacity.max(1).min(capacity)
+    }
+
+    /// Get the total size of the items in both regions.
+    fn size(&self) -> u32 {
+        self.window
+            .iter()
+            .chain(&self.main.probationary)
+            .chain(&self.main.protected)
+            .map(Item::size)
+            .sum()
+    }
+
+    /// Move items out of the window until it fits in its share of `capacity`, making each a
+    /// candidate for the main region, and return the items evicted to make room.
+    fn spill(&mut self, capacity: u32) -> HashSet<I> {
+        let window_capacity = self.window_capacity(capacity);
+        let mut to_evict = HashSet::new();
+
+        // the newest item stays, however large it is
+        while self.window.len() > 1
+            && self.window.iter().map(Item::size).sum::<u32>() > window_capacity
+        {
+            let candidate = self
+                .window
+                .pop_front()
+                .expect("The window is over capacity.");
+
+            // the candidate has to beat every victim it would replace, so find them all before
+            // evicting any
+            let size = self.size();
+            let mut victims = HashSet::new();
+            let mut freed = 0;
+            for &victim in self.main.probationary.iter().chain(&self.main.protected) {
+                if size - freed + candidate.size() <= capacity
+                    || self.sketch.estimate(&candidate) <= self.sketch.estimate(&victim)
+                {
+                    break;
+                }
+                victims.insert(victim);
+                freed += victim.size();
+            }
+
+            if size - freed + candidate.size() <= capacity {
+                self.main.probationary.retain(|i| !victims.contains(i));
+                self.main.protected.retain(|i| !victims.contains(i));
+                self.main.probationary.push_back(candidate);
+                to_evict.extend(victims);
+            } else {
+                to_evict.insert(candidate);
+            }
+        }
+
+        to_evict
+    }
+
+    /// Evict items until the rest fit in `capacity`, starting from the main region's victims.
+    fn evict(&mut self, capacity: u32) -> HashSet<I> {
+        let mut to_evict = HashSet::new();
+        while self.size() > capacity {
+            to_evict.insert(
+                self.main
+                    .probationary
+                    .pop_front()
+                    .or_else(|| self.main.protected.pop_front())
+                    .or_else(|| self.window.pop_front())
+                    .expect("The cache is non-empty."),
+            );
+        }
+        to_evict
+    }
+}
+
+impl<I: Item, H: BuildHasher> ReplacementPolicy<I, H> for WTinyLfu<I> {
+    fn update_state(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) {
+        self.sketch.increment(&next);
+
+        if let Some(index) = self.window.iter().position(|&i| i == next) {
+            self.window.remove(index);
+            self.window.push_back(next);
+        } else if set.contains(&next) {
+            let main_capacity = capacity - self.window_capacity(capacity);
+            self.main.update_state(set, main_capacity, next);
+        } else {
+            // there's room for the new item, so spilling it over doesn't evict anything
+            self.window.push_back(next);
+            self.spill(capacity);
+        }
+    }
+
+    fn replace(&mut self, _: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I> {
+        self.sketch.increment(&next);
+        self.window.push_back(next);
+
+        let mut to_evict = self.spill(capacity);
+        to_evict.extend(self.evict(capacity));
+        to_evict
+    }
+
+    fn shrink(&mut self, _: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        self.main.demote(capacity - self.window_capacity(capacity));
+        self.evict(capacity)
+    }
+
+    fn remove(&mut self, item: I) {
+        self.window.retain(|&i| i != item);
+        ReplacementPolicy::<I, H>::remove(&mut self.main, item);
+    }
+
+    fn clear(&mut self) {
+        self.window.clear();
+        ReplacementPolicy::<I, H>::clear(&mut self.main);
+        self.sketch.clear();
+    }
+}
+
+/// A wrapper which sequentially prefetches the next item after every miss.
+///
+/// On a miss for `x`, the cache also brings in [`x.next()`](Sequential::next), as if it had been
+/// accessed right after `x`; the wrapped policy decides what both of them evict. The prefetch
+/// isn't an access, so it doesn't count as a hit or a miss, but a later access to the prefetched
+/// item will hit if it's still cached.
+///
+/// ```
+/// use cache_sim::{Cache, Lru, Prefetch, Trace};
+///
+/// let trace = Trace::from((0..10).collect::<Vec<_>>());
+///
+/// let mut bare = Cache::<Lru>::new(4);
+/// bare.run_trace(&trace);
+///
+/// let mut prefetching = Cache::<Prefetch<Lru>>::new(4);
+/// prefetching.run_trace(&trace);
+///
+/// assert_eq!(bare.hits(), 0);
+/// assert_eq!(prefetching.hits(), 5); // every other access was prefetched by the one before it
+/// ```
+#[derive(Default, Clone)]
+pub struct Prefetch<P> {
+    policy: P,
+}
+
+impl<P> Prefetch<P> {
+    /// Wrap a replacement policy with sequential prefetching.
+    #[must_use]
+    pub const fn new(policy: P) -> Self {
+        Self { policy }
+    }
+
+    /// Get a reference to the wrapped replacement policy.
+    pub const fn policy(&self) -> &P {
+        &self.policy
+    }
+}
+
+impl<I: Sequential, H: BuildHasher, P: ReplacementPolicy<I, H>> ReplacementPolicy<I, H>
+    for Prefetch<P>
+{
+    fn update_state(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) {
+        self.policy.update_state(set, capacity, next);
+    }
+
+    fn replace(&mut self, set: &HashSet<I, H>, capacity: u32, next: I) -> HashSet<I> {
+        self.policy.replace(set, capacity, next)
+    }
+
+    fn shrink(&mut self, set: &HashSet<I, H>, capacity: u32) -> HashSet<I> {
+        self.policy.shrink(set, capacity)
+    }
+
+    fn remove(&mut self, item: I) {
+        self.policy.remove(item);
+    }
+
+    fn clear(&mut self) {
+        self.policy.clear();
+    }
+
+    fn prefetch(&mut self, next: I) -> Vec<I> {
+        next.next().into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ghost::Bloom;
+    use crate::stats::Stat;
+    use crate::Cache;
+
+    /// Records every evicted item, in order of eviction.
+    #[derive(Default)]
+    struct Evictions(Vec<u32>);
+
+    impl<H: BuildHasher> Stat<u32, H> for Evictions {
+        fn update(&mut self, _: &HashSet<u32, H>, _: u32, to_be_evicted: &HashSet<u32>) {
+            self.0.extend(to_be_evicted);
+        }
+    }
+
+    macro_rules! eviction_order_test {
+        ($name:ident ($policy:ty, $capacity:expr): $($in:expr),* => $($out:expr),*) => {
+            #[test]
+            fn $name() {
+                let mut c = Cache::<$policy, Evictions>::new($capacity);
+
+                $(
+                    c.access($in);
+                )*
+
+                assert_eq!(c.stat().0, vec![$($out),*]);
+            }
+        };
+    }
+
+    macro_rules! integration_test {
+        ($name:ident ($policy:ty): $($in:expr),* => $($out:expr),*) => {
+            #[test]
+            fn $name() {
+                let mut c = Cache::<$policy>::new(3);
+
+                $(
+                    c.access($in);
+                )*
+
+                assert_eq!(c.set(), &HashSet::from([$($out),*]));
+            }
+        };
+    }
+
+    macro_rules! replacement_policy_test {
+        ($name:ident ($policy:ty):
+            counting_up => $($counting_up_out:expr),*;
+            repeated => $($repeated_out:expr),*;
+            one_repetition => $($one_repetition_out:expr),*;
+            cycle => $($cycle_out:expr),*;
+        ) => {
+            mod $name {
+                use super::*;
+
+                integration_test!(counting_up ($policy): 0, 1, 2, 3 => $($counting_up_out),*);
+                integration_test!(repeated ($policy): 0, 0, 0, 0 =>  $($repeated_out),*);
+                integration_test!(one_repetition ($policy): 0, 1, 2, 0, 3 => $($one_repetition_out),*);
+                integration_test!(cycle ($policy): 0, 1, 2, 0, 1, 2, 0, 1, 2, 3, 3 => $($cycle_out),*);
+            }
+        }
+    }
+
+    replacement_policy_test! {
+        lru (Lru):
+            counting_up => 1, 2, 3;
+            repeated => 0;
+            one_repetition => 0, 2, 3;
+            cycle => 1, 2, 3;
+    }
+
+    replacement_policy_test! {
+        mru (Mru):
+            counting_up => 0, 1, 3;
+            repeated => 0;
+            one_repetition => 1, 2, 3;
+            cycle => 0, 1, 3;
+    }
+
+    replacement_policy_test! {
+        hyperbolic (Hyperbolic):
+            counting_up => 1, 2, 3;
+            repeated => 0;
+            one_repetition => 0, 2, 3;
+            cycle => 1, 2, 3;
+    }
+
+    mod hyperbolic_sampling {
+        use super::*;
+        use crate::generate::zipf_trace;
+
+        fn evictions(policy: Hyperbolic) -> Vec<u32> {
+            let mut c = Cache::<Hyperbolic, Evictions>::with_replacement_policy(policy, 16u32);
+            c.run_trace(&zipf_trace(100, 0.8, 2000, 0));
+            c.stat().0.clone()
+        }
+
+        #[test]
+        fn same_seed_same_evictions() {
+            let evicted = evictions(Hyperbolic::with_seed(4, 42));
+
+            assert!(!evicted.is_empty());
+            assert_eq!(evicted, evictions(Hyperbolic::with_seed(4, 42)));
+            assert_ne!(evicted, evictions(Hyperbolic::with_seed(4, 43)));
+        }
+
+        #[test]
+        fn full_sample_is_exact() {
+            // with every item sampled, the seed can't matter
+            assert_eq!(
+                evictions(Hyperbolic::with_seed(16, 1)),
+                evictions(Hyperbolic::with_seed(16, 2))
+            );
+        }
+
+        #[test]
+        fn new_items_outrank_stale_ones() {
+            let mut c = Cache::<Hyperbolic, Evictions>::with_replacement_policy(
+                Hyperbolic::with_seed(64, 0),
+                2u32,
+            );
+
+            // 0 was hot early on, but its priority decays while 1 keeps getting hit
+            c.access_all([0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 2]);
+            assert_eq!(c.stat().0, vec![0]);
+        }
+
+        #[test]
+        #[should_panic]
+        fn zero_sample_size() {
+            let _ = Hyperbolic::<u32>::new(0);
+        }
+    }
+
+    mod mru_order {
+        use super::*;
+
+        // a hit makes its item the most recently used, so it's the next to go
+        eviction_order_test!(hit_then_miss (Mru, 2): 1, 2, 3, 1, 4 => 2, 1);
+        eviction_order_test!(lru_crossover (Lru, 2): 1, 2, 3, 1, 4 => 1, 2, 3);
+        eviction_order_test!(repeated_hits (Mru, 2): 1, 2, 2, 1, 1, 3, 1 => 1, 3);
+        eviction_order_test!(miss_is_most_recent (Mru, 2): 1, 2, 3, 4, 5 => 2, 3, 4);
+
+        #[test]
+        fn evicts_previous_access() {
+            let trace = Trace::from(vec![0, 1, 2, 0, 3, 1, 1, 4, 2, 5, 0]);
+            let mut c = Cache::<Mru>::new(3);
+
+            for (i, &item) in trace.iter().enumerate() {
+                let before = c.set().clone();
+                c.access(item);
+
+                // on a miss in a full cache, the eviction is whatever was touched just before
+                if let Some(evicted) = before.difference(c.set()).next() {
+                    assert_eq!(*evicted, trace[i - 1]);
+                }
+            }
+        }
+    }
+
+    replacement_policy_test! {
+        fifo (Fifo):
+            counting_up => 1, 2, 3;
+            repeated => 0;
+            one_repetition => 1, 2, 3;
+            cycle => 1, 2, 3;
+    }
+
+    replacement_policy_test! {
+        clock (Clock):
+            counting_up => 1, 2, 3;
+            repeated => 0;
+            one_repetition => 1, 2, 3;
+            cycle => 1, 2, 3;
+    }
+
+    mod clock_hand {
+        use super::*;
+
+        eviction_order_test!(second_chance (Clock, 3): 0, 1, 2, 0, 3, 4, 3, 2, 5, 6 => 0, 1, 2, 3);
+
+        #[test]
+        fn full_revolution() {
+            let mut c = Cache::<Clock, Evictions>::new(3);
+
+            for i in [0, 1, 2, 0, 1, 2] {
+                c.access(i);
+            }
+
+            // every bit is set, so the hand should come back around to 0
+            c.access(3);
+            assert_eq!(c.stat().0, vec![0]);
+            assert_eq!(c.replacement_policy().hand, 1);
+
+            // and the revolution cleared the other bits, so 1 goes next
+            c.access(4);
+            assert_eq!(c.stat().0, vec![0, 1]);
+        }
+    }
+
+    mod belady {
+        use super::*;
+        use crate::stats::MissCount;
+
+        macro_rules! belady_test {
+            ($name:ident ($capacity:expr): $($in:expr),* => $misses:expr) => {
+                #[test]
+                fn $name() {
+                    let trace = Trace::from(vec![$($in),*]);
+                    let mut c = Cache::<_, MissCount>::with_replacement_policy(
+                        Belady::new(&trace),
+                        $capacity as u32,
+                    );
+
+                    c.run_trace(&trace);
+
+                    assert_eq!(c.stat().0, $misses);
+                }
+            };
+        }
+
+        belady_test!(classic (3): 1, 2, 3, 4, 1, 2, 5, 1, 2, 3, 4, 5 => 7);
+        belady_test!(classic_bigger (4): 1, 2, 3, 4, 1, 2, 5, 1, 2, 3, 4, 5 => 6);
+        belady_test!(never_used_again (2): 0, 1, 2, 0, 0, 0 => 3);
+        belady_test!(cycle (3): 0, 1, 2, 3, 0, 1, 2, 3 => 5);
+
+        #[test]
+        fn oversized_items_are_skipped() {
+            let mut gen = crate::GeneralModelGenerator::new();
+            let items: Vec<_> = (0..20).map(|_| gen.item(1.0, 1)).collect();
+            let oversized: Vec<_> = (0..3).map(|_| gen.item(1.0, 10)).collect();
+
+            for seed in 0..20 {
+                let fitting: Vec<_> = crate::generate::zipf_trace(20, 0.8, 200, seed)
+                    .iter()
+                    .map(|&i| items[i as usize])
+                    .collect();
+                let mut with_oversized = fitting.clone();
+                for (i, &item) in oversized.iter().enumerate() {
+                    with_oversized.insert(50 * (i + 1), item);
+                }
+
+                let misses = |trace: Vec<GeneralModelItem>| {
+                    let trace = Trace::from(trace);
+                    let mut c = Cache::<_, MissCount, _>::with_replacement_policy(
+                        Belady::new(&trace),
+                        5u32,
+                    );
+                    c.run_trace(&trace);
+                    c.stat().0
+                };
+
+                // the oversized items bypass the cache, so they're extra misses, but the rest of
+                // the trace is cached just as optimally
+                assert_eq!(misses(with_oversized), misses(fitting) + 3);
+            }
+        }
+
+        #[test]
+        fn prepopulated() {
+            let warm_up = [0, 1];
+            let trace = Trace::from(vec![2, 0, 1, 2, 0]);
+            let whole = Trace::from(
+                warm_up
+                    .iter()
+                    .chain(trace.iter())
+                    .copied()
+                    .collect::<Vec<_>>(),
+            );
+
+            let mut c = Cache::<_, MissCount>::with_replacement_policy(Belady::new(&whole), 2u32);
+            c.prepopulate(warm_up);
+            c.run_trace(&trace);
+
+            // 2 replaces 1, which is next used after 0; then 1 replaces 0, which is used last
+            assert_eq!(c.stat().0, 3);
+        }
+
+        #[test]
+        fn accesses_past_the_trace() {
+            let trace = Trace::from(vec![1, 2, 3, 4, 1, 2, 5, 1, 2, 3, 4, 5]);
+            let mut c = Cache::<_, MissCount>::with_replacement_policy(Belady::new(&trace), 3u32);
+
+            // the second time through, nothing is used again as far as the policy knows
+            c.prepopulate(trace.iter().copied());
+            c.run_trace(&trace);
+            assert!(c.stat().0 <= trace.len() as u32);
+        }
+    }
+
+    replacement_policy_test! {
+        two_q (TwoQ):
+            counting_up => 1, 2, 3;
+            repeated => 0;
+            one_repetition => 1, 2, 3;
+            cycle => 1, 2, 3;
+    }
+
+    mod two_q_queues {
+        use super::*;
+
+        #[test]
+        fn promotion() {
+            let mut c = Cache::<TwoQ, Evictions>::new(4);
+
+            for i in [0, 1, 2, 3, 4, 5] {
+                c.access(i);
+            }
+
+            // 0 and 1 were pushed out of a1in and are remembered only as keys
+            assert_eq!(c.stat().0, vec![0, 1]);
+            assert_eq!(c.replacement_policy().a1out.keys(), &VecDeque::from([0, 1]));
+            assert!(!c.set().contains(&0));
+
+            // seeing 0 again after the gap promotes it to am
+            c.access(0);
+            assert_eq!(c.replacement_policy().am, VecDeque::from([0]));
+
+            // while the one-shot items keep getting evicted from a1in
+            c.access(6);
+            assert_eq!(c.stat().0, vec![0, 1, 2, 3]);
+            assert!(c.set().contains(&0));
+
+            let policy = c.replacement_policy();
+            assert_eq!(policy.a1in_len(), 3);
+            assert_eq!(policy.a1out_len(), 2);
+            assert_eq!(policy.am_len(), 1);
+        }
+
+        #[test]
+        fn a1in_hits_do_not_promote() {
+            let mut c = Cache::<TwoQ>::new(4);
+
+            for i in [0, 1, 0, 0] {
+                c.access(i);
+            }
+
+            assert_eq!(c.replacement_policy().a1in_len(), 2);
+            assert_eq!(c.replacement_policy().am_len(), 0);
+        }
+    }
+
+    replacement_policy_test! {
+        slru (Slru):
+            counting_up => 1, 2, 3;
+            repeated => 0;
+            one_repetition => 0, 2, 3;
+            cycle => 1, 2, 3;
+    }
+
+    mod ghost_stores {
+        use super::*;
+        use crate::generate::zipf_trace;
+
+        #[test]
+        fn exact_promotions_match_reference() {
+            let mut c = Cache::<TwoQ<u32, Exact<u32>>, Evictions>::with_replacement_policy(
+                TwoQ::with_ghosts(0.25, 0.5, Exact::default()),
+                4u32,
+            );
+
+            // 0, 1, and then 2 are pushed out of a1in; a1out holds two keys, so it forgets 0
+            for i in [0, 1, 2, 3, 4, 5, 6, 2, 0] {
+                c.access(i);
+            }
+
+            let policy = c.replacement_policy();
+            assert_eq!(policy.am, VecDeque::from([2]));
+            assert!(policy.a1in.contains(&0));
+            assert_eq!(policy.a1out.keys(), &VecDeque::from([3, 4]));
+        }
+
+        #[test]
+        fn bloom_is_close_to_exact() {
+            let trace = zipf_trace(500, 0.8, 20_000, 11);
+
+            fn hits<R: ReplacementPolicy<u32> + Default>(trace: &crate::Trace) -> f64 {
+                let mut c = Cache::<R>::new(50);
+                c.run_trace(trace);
+                c.hits() as f64
+            }
+
+            for (exact, bloom) in [
+                (hits::<TwoQ>(&trace), hits::<TwoQ<u32, Bloom<u32>>>(&trace)),
+                (hits::<Arc>(&trace), hits::<Arc<u32, Bloom<u32>>>(&trace)),
+                (hits::<Car>(&trace), hits::<Car<u32, Bloom<u32>>>(&trace)),
+                (
+                    hits::<S3Fifo>(&trace),
+                    hits::<S3Fifo<u32, Bloom<u32>>>(&trace),
+                ),
+            ] {
+                assert!((exact - bloom).abs() / exact < 0.05, "{exact} vs {bloom}");
+            }
+        }
+
+        #[test]
+        fn bloom_ghost_hits_once_bounded() {
+            // a1out holds ten keys, but far fewer than a generation of the filters; re-accessing
+            // the most recent ghosts right after it first fills should promote them all the same
+            let mut exact = Cache::<TwoQ>::with_replacement_policy(TwoQ::new(0.25, 0.5), 20u32);
+            let mut bloom = Cache::<TwoQ<u32, Bloom<u32>>>::with_replacement_policy(
+                TwoQ::with_ghosts(0.25, 0.5, Bloom::new(1000, 0.01)),
+                20u32,
+            );
+
+            let trace: Vec<u32> = (0..42).chain(14..22).collect();
+            exact.access_all(trace.iter().copied());
+            bloom.access_all(trace.iter().copied());
+
+            assert_eq!(exact.replacement_policy().am_len(), 8);
+            assert_eq!(
+                bloom.replacement_policy().am_len(),
+                exact.replacement_policy().am_len()
+            );
+        }
+
+        #[test]
+        fn bloom_spurious_promotions_bounded() {
+            let mut c = Cache::<TwoQ<u32, Bloom<u32>>>::with_replacement_policy(
+                TwoQ::with_ghosts(0.25, 0.5, Bloom::new(100, 0.01)),
+                20u32,
+            );
+
+            // nothing is ever seen twice, so every promotion is a false positive
+            let mut promotions = 0;
+            for i in 0..10_000 {
+                c.access(i);
+                if c.replacement_policy().am.back() == Some(&i) {
+                    promotions += 1;
+                }
+            }
+
+            assert!(promotions <= 100, "{promotions} spurious promotions");
+        }
+    }
+
+    mod slru_segments {
+        use super::*;
+
+        fn cache() -> Cache<Slru, Evictions> {
+            // two slots for each segment
+            Cache::with_replacement_policy(Slru::new(0.5), 4u32)
+        }
+
+        #[test]
+        fn one_shot_items_evicted_first() {
+            let mut c = cache();
+
+            for i in [0, 0, 1, 2, 3, 4] {
+                c.access(i);
+            }
+
+            // 0 is the oldest item, but it was accessed twice, so it's protected
+            assert_eq!(c.stat().0, vec![1]);
+            assert!(c.replacement_policy().is_protected(&0));
+            assert!(!c.replacement_policy().is_protected(&4));
+        }
+
+        #[test]
+        fn protected_overflow_demotes() {
+            let mut c = cache();
+
+            for i in [0, 0, 1, 1, 2, 2] {
+                c.access(i);
+            }
+
+            // promoting 2 overflowed the protected segment, so 0 was demoted, not evicted
+            let policy = c.replacement_policy();
+            assert!(!policy.is_protected(&0));
+            assert!(policy.is_protected(&1) && policy.is_protected(&2));
+            assert_eq!((policy.probationary_len(), policy.protected_len()), (1, 2));
+            assert!(c.stat().0.is_empty());
+
+            // 0 went to the most recently used end of the probationary segment, but it's still the
+            // first to go once that segment is full
+            c.access(3);
+            c.access(4);
+            assert_eq!(c.stat().0, vec![0]);
+        }
+
+        #[test]
+        fn demoted_items_can_be_promoted_again() {
+            let mut c = cache();
+
+            for i in [0, 0, 1, 1, 2, 2, 0] {
+                c.access(i);
+            }
+
+            let policy = c.replacement_policy();
+            assert!(policy.is_protected(&0) && policy.is_protected(&2));
+            assert!(!policy.is_protected(&1));
+        }
+    }
+
+    mod scan_resistant_lru {
+        use super::*;
+
+        #[test]
+        fn hot_set_survives_scan() {
+            let hot = (0..5).cycle().take(25);
+            let trace: Trace = hot.chain(100..300).collect();
+
+            let mut lru = Cache::<Lru>::new(6);
+            lru.run_trace(&trace);
+
+            let mut c = Cache::<ScanResistantLru>::with_replacement_policy(
+                ScanResistantLru::new(2, 0.1),
+                6u32,
+            );
+            c.run_trace(&trace);
+
+            // the first three accesses of the scan displace 0 and 1, then the rest share a slot
+            let survivors = |set: &HashSet<u32>| (0..5).filter(|i| set.contains(i)).count();
+            assert_eq!(survivors(lru.set()), 0);
+            assert_eq!(survivors(c.set()), 3);
+            assert_eq!(c.replacement_policy().probationary_len(), 1);
+        }
+
+        #[test]
+        fn re_referenced_items_promoted() {
+            let mut c = Cache::<ScanResistantLru, Evictions>::with_replacement_policy(
+                ScanResistantLru::new(0, 0.1),
+                2u32,
+            );
+
+            c.access_all([0, 0, 1, 2]);
+
+            // 0 was promoted, so it outlasted 1 though it was older
+            assert_eq!(c.stat().0, vec![1]);
+            assert!(!c.replacement_policy().is_probationary(&0));
+            assert!(c.replacement_policy().is_probationary(&2));
+        }
+
+        #[test]
+        fn misses_on_seen_items_end_scan() {
+            let mut c = Cache::<ScanResistantLru>::with_replacement_policy(
+                ScanResistantLru::new(1, 0.1),
+                2u32,
+            );
+
+            c.access_all([0, 1, 2]);
+            assert!(c.replacement_policy().is_probationary(&2));
+
+            // 1 was evicted from the probationary queue, but it's not new, so it goes to main
+            c.access(1);
+            assert_eq!(c.set(), &HashSet::from([0, 1]));
+            assert_eq!(c.replacement_policy().probationary_len(), 0);
+        }
+
+        #[test]
+        fn probationary_queue_bounded() {
+            let mut c = Cache::<ScanResistantLru>::with_replacement_policy(
+                ScanResistantLru::new(0, 0.2),
+                10u32,
+            );
+
+            // the whole scan is probationary, but only the newest two stay in the queue
+            c.access_all(0..10);
+            assert_eq!(c.replacement_policy().probationary_len(), 2);
+            assert!(c.replacement_policy().is_probationary(&8));
+
+            // so 8 is evicted first, and the rest of the scan outlasts it in the main queue
+            c.access(10);
+            assert!(!c.contains(&8));
+            assert_eq!(c.replacement_policy().probationary_len(), 2);
+            assert!(c.replacement_policy().is_probationary(&10));
+        }
+
+        #[test]
+        fn evicted_items_forgotten() {
+            let mut c = Cache::<ScanResistantLru>::with_replacement_policy(
+                ScanResistantLru::new(1, 0.1),
+                2u32,
+            );
+
+            // only the last two evicted items are remembered, so 1 is new again
+            c.access_all(0..6);
+            assert_eq!(c.replacement_policy().evicted.len(), 2);
+
+            c.access(1);
+            assert!(c.replacement_policy().is_probationary(&1));
+        }
+    }
+
+    mod lirs {
+        use super::*;
+
+        #[test]
+        fn resident_blocks() {
+            // hand-traced from the rules in the paper, with two LIR blocks and one HIR block
+            let accesses = [
+                (0, vec![0]),
+                (1, vec![0, 1]),
+                (2, vec![0, 1, 2]),
+                (3, vec![0, 1, 3]),
+                // 2 is a non-resident HIR block in the stack, so it's promoted to LIR and 0 is
+                // demoted
+                (2, vec![0, 1, 2]),
+                (0, vec![0, 1, 2]),
+                (4, vec![1, 2, 4]),
+                // 1 is the bottom LIR block, so this prunes 3 from the stack
+                (1, vec![1, 2, 4]),
+                (3, vec![1, 2, 3]),
+                (0, vec![0, 1, 2]),
+            ];
+
+            let mut c = Cache::<Lirs, Evictions>::new(3);
+
+            for (item, resident) in accesses {
+                c.access(item);
+                assert_eq!(
+                    c.set(),
+                    &resident.into_iter().collect(),
+                    "after accessing {item}"
+                );
+            }
+
+            assert_eq!(c.stat().0, vec![2, 3, 0, 4, 3]);
+
+            let lirs = c.replacement_policy();
+            assert_eq!(lirs.s, VecDeque::from([1, 3, 0]));
+            assert_eq!(lirs.q, VecDeque::from([2]));
+            assert_eq!(lirs.status[&3], LirsStatus::NonResidentHir);
+            assert!(!lirs.status.contains_key(&4));
+        }
+
+        #[test]
+        fn resident_hir_outside_stack() {
+            let mut c = Cache::<Lirs>::new(3);
+
+            for i in [0, 1, 2, 3, 2, 0] {
+                c.access(i);
+            }
+
+            // 0 was demoted and pruned from the stack, so the hit keeps it HIR
+            let lirs = c.replacement_policy();
+            assert_eq!(lirs.status[&0], LirsStatus::ResidentHir);
+            assert_eq!(lirs.q, VecDeque::from([0]));
+            assert_eq!(lirs.lir_count, 2);
+        }
+
+        #[test]
+        fn capacity_one() {
+            let mut c = Cache::<Lirs, Evictions>::new(1);
+
+            for i in [0, 1, 0, 0, 2] {
+                c.access(i);
+            }
+
+            assert_eq!(c.stat().0, vec![0, 1, 0]);
+            assert_eq!(c.set(), &HashSet::from([2]));
+        }
+    }
+
+    replacement_policy_test! {
+        lru_1 (LruK<1>):
+            counting_up => 1, 2, 3;
+            repeated => 0;
+            one_repetition => 0, 2, 3;
+            cycle => 1, 2, 3;
+    }
+
+    mod lru_k {
+        use super::*;
+
+        #[test]
+        fn diverges_from_lru() {
+            let trace = Trace::from(vec![0, 1, 0, 2, 3, 0, 4, 1]);
+
+            let mut lru = Cache::<Lru, Evictions>::new(2);
+            lru.run_trace(&trace);
+
+            let mut lru_2 = Cache::<LruK<2>, Evictions>::new(2);
+            lru_2.run_trace(&trace);
+
+            assert_eq!(lru.stat().0, vec![1, 0, 2, 3, 0]);
+            // 0 is the only item with two references, so it's never evicted
+            assert_eq!(lru_2.stat().0, vec![1, 2, 3, 4]);
+        }
+
+        #[test]
+        fn correlated_references() {
+            let mut c = Cache::<LruK<2>>::with_replacement_policy(LruK::new(1), 2u32);
+
+            c.access(0);
+            c.access(0);
+            c.access(1);
+            c.access(2);
+
+            // the second reference to 0 was correlated, so it only has one reference; and 1 was
+            // referenced too recently to be evicted
+            assert_eq!(c.set(), &HashSet::from([1, 2]));
+        }
+    }
+
+    replacement_policy_test! {
+        sieve (Sieve):
+            counting_up => 1, 2, 3;
+            repeated => 0;
+            one_repetition => 0, 2, 3;
+            cycle => 1, 2, 3;
+    }
+
+    mod sieve_hand {
+        use super::*;
+
+        #[test]
+        fn hand_is_retained() {
+            let mut c = Cache::<Sieve, Evictions>::new(3);
+
+            for i in [0, 1, 2, 0, 3] {
+                c.access(i);
+            }
+
+            // the hand cleared 0's bit then evicted 1, so it's left pointing at 2
+            assert_eq!(c.stat().0, vec![1]);
+            assert_eq!(c.replacement_policy().hand, Some(1));
+
+            // 0 is now the unvisited tail, but the hand carries on from 2 instead of restarting
+            c.access(4);
+            assert_eq!(c.stat().0, vec![1, 2]);
+            assert_eq!(c.set(), &HashSet::from([0, 3, 4]));
+        }
+
+        eviction_order_test!(wraps_to_tail (Sieve, 2): 0, 1, 2, 1, 2, 3 => 0, 1);
+    }
+
+    replacement_policy_test! {
+        s3_fifo (S3Fifo):
+            counting_up => 1, 2, 3;
+            repeated => 0;
+            one_repetition => 0, 2, 3;
+            cycle => 1, 2, 3;
+    }
+
+    mod s3_fifo_queues {
+        use super::*;
+
+        fn cache() -> Cache<S3Fifo, Evictions> {
+            // one slot for the small queue and three for the main queue
+            Cache::with_replacement_policy(S3Fifo::new(0.25), 4u32)
+        }
+
+        #[test]
+        fn promotion() {
+            let mut c = cache();
+
+            for i in [0, 1, 2, 3, 1, 4, 5] {
+                c.access(i);
+            }
+
+            // 1 was hit in the small queue, so it moved to main rather than being evicted
+            assert_eq!(c.stat().0, vec![0, 2]);
+            assert_eq!(c.replacement_policy().main, VecDeque::from([(1, 1)]));
+            assert_eq!(c.replacement_policy().ghost.keys(), &VecDeque::from([0, 2]));
+        }
+
+        #[test]
+        fn ghost_hit() {
+            let mut c = cache();
+
+            for i in [0, 1, 2, 3, 1, 4, 5, 0] {
+                c.access(i);
+            }
+
+            // 0 was in the ghost queue, so it goes straight to main
+            assert_eq!(c.stat().0, vec![0, 2, 3]);
+            assert_eq!(c.set(), &HashSet::from([0, 1, 4, 5]));
+
+            let policy = c.replacement_policy();
+            assert_eq!(policy.main, VecDeque::from([(1, 1), (0, 0)]));
+            assert_eq!(policy.ghost.keys(), &VecDeque::from([2, 3]));
+        }
+
+        #[test]
+        fn ghost_is_bounded() {
+            let mut c = cache();
+
+            for i in 0..20 {
+                c.access(i);
+            }
+
+            let ghost = c.replacement_policy().ghost.keys();
+            assert_eq!(ghost, &VecDeque::from([13, 14, 15]));
+            assert!(ghost.iter().all(|i| !c.set().contains(i)));
+        }
+    }
+
+    replacement_policy_test! {
+        lfuda (Lfuda):
+            counting_up => 1, 2, 3;
+            repeated => 0;
+            one_repetition => 0, 2, 3;
+            cycle => 1, 2, 3;
+    }
+
+    replacement_policy_test! {
+        fbr (Fbr):
+            counting_up => 1, 2, 3;
+            repeated => 0;
+            one_repetition => 0, 2, 3;
+            cycle => 1, 2, 3;
+    }
+
+    mod lrfu {
+        use super::*;
+        use crate::generate::zipf_trace;
+
+        fn assert_same_sets<R: ReplacementPolicy<u32> + Default>(lrfu: Lrfu, capacity: u32) {
+            let mut lrfu = Cache::<Lrfu>::with_replacement_policy(lrfu, capacity);
+            let mut other = Cache::<R>::new(capacity);
+
+            for i in zipf_trace(30, 0.9, 1000, 7) {
+                lrfu.access(i);
+                other.access(i);
+                assert_eq!(lrfu.set(), other.set());
+            }
+        }
+
+        #[test]
+        fn lambda_zero_is_lfu() {
+            assert_same_sets::<Lfu>(Lrfu::new(0.0), 5);
+        }
+
+        #[test]
+        fn large_lambda_is_lru() {
+            assert_same_sets::<Lru>(Lrfu::new(1.0), 5);
+            assert_same_sets::<Lru>(Lrfu::new(20.0), 5);
+        }
+
+        #[test]
+        fn discriminating_trace() {
+            // 0 is frequent but stale, so which end λ is at decides whether it survives
+            let trace = [0, 0, 0, 1, 2, 1, 3];
+            for (lambda, evictions) in [(0.0, vec![1, 2, 1]), (1.0, vec![0, 2])] {
+                let mut c =
+                    Cache::<Lrfu, Evictions>::with_replacement_policy(Lrfu::new(lambda), 2u32);
+                c.access_all(trace);
+                assert_eq!(c.stat().0, evictions);
+            }
+        }
+
+        #[test]
+        fn in_between() {
+            // with λ = 0.5, three accesses to 0 outweigh one newer access to 1, until 0 ages
+            let mut c = Cache::<Lrfu, Evictions>::with_replacement_policy(Lrfu::new(0.5), 2u32);
+            c.access_all([0, 0, 0, 1, 2]);
+            assert_eq!(c.stat().0, vec![1]);
+
+            c.access_all([3, 4, 5]);
+            assert!(!c.contains(&0));
+        }
+
+        #[test]
+        #[should_panic(expected = "λ must be non-negative.")]
+        fn negative_lambda() {
+            let _ = Lrfu::<u32>::new(-1.0);
+        }
+    }
+
+    mod fbr_sections {
+        use super::*;
+
+        #[test]
+        fn new_section_hits_dont_count() {
+            let mut c = Cache::<Fbr>::new(8);
+
+            for _ in 0..5 {
+                c.access(0);
+            }
+            assert_eq!(c.replacement_policy().count(&0), Some(1));
+
+            // once 0 has aged out of the new section, a reuse counts
+            for i in [1, 2, 3, 0] {
+                c.access(i);
+            }
+            assert_eq!(c.replacement_policy().count(&0), Some(2));
+        }
+
+        // 0's second reference is outside the new section, so it survives until it's the only
+        // item with a count of 2 in the old section
+        eviction_order_test!(evicts_lowest_old_count (Fbr, 4): 0, 1, 2, 3, 0, 4, 5, 6, 7 => 1, 2, 3, 4);
+
+        #[test]
+        fn bursts_dont_protect() {
+            let mut c = Cache::<Fbr, Evictions>::new(3);
+
+            // 0's burst is all in the new section, so it's evicted like any other old item
+            for i in [0, 0, 0, 0, 1, 2, 3] {
+                c.access(i);
+            }
+            assert_eq!(c.stat().0, vec![0]);
+        }
+
+        #[test]
+        #[should_panic(expected = "The sections must fit in the stack.")]
+        fn overlapping_sections() {
+            let _ = Fbr::<u32>::new(0.6, 0.6);
+        }
+    }
+
+    mod tiebreakers {
+        use super::*;
+
+        // 4, 2, and 6 are all accessed twice, at which point 6 is the least recently used, but 4
+        // was inserted first and 2 is the smallest
+        macro_rules! tiebreak_test {
+            ($name:ident ($policy:ty) => $victim:expr) => {
+                #[test]
+                fn $name() {
+                    for _ in 0..10 {
+                        let mut c = Cache::<$policy, Evictions>::new(3);
+                        c.access_all([4, 2, 6, 6, 4, 2, 9]);
+                        assert_eq!(c.stat().0, vec![$victim]);
+                    }
+                }
+            };
+        }
+
+        tiebreak_test!(lfu_lru(Lfu<u32, Lru>) => 6);
+        tiebreak_test!(lfu_fifo(Lfu<u32, Fifo>) => 4);
+        tiebreak_test!(lfu_smallest_id(Lfu<u32, SmallestId>) => 2);
+        tiebreak_test!(lfuda_lru(Lfuda<u32, Lru>) => 6);
+        tiebreak_test!(lfuda_fifo(Lfuda<u32, Fifo>) => 4);
+        tiebreak_test!(lfuda_smallest_id(Lfuda<u32, SmallestId>) => 2);
+
+        #[test]
+        fn frequency_first() {
+            // 2 is the smallest, but it's accessed more, so the tiebreaker never sees it
+            let mut c = Cache::<Lfu<u32, SmallestId>, Evictions>::new(2);
+            c.access_all([2, 2, 7, 5]);
+            assert_eq!(c.stat().0, vec![7]);
+        }
+
+        #[test]
+        fn reinsertion_is_newer() {
+            // 1 is evicted and then reinserted after 4, so when they tie, 4 is the older one
+            let mut c = Cache::<Lfu<u32, Fifo>, Evictions>::new(2);
+            c.access_all([1, 2, 3, 2, 3, 4, 4, 1, 5]);
+            assert_eq!(c.stat().0, vec![1, 2, 3, 4]);
+
+            // likewise for 1 and 3, when every item runs out of credit together
+            let mut c = Cache::<Landlord<u32, Fifo>, Evictions>::new(2);
+            c.access_all([1, 2, 3, 1, 2]);
+            assert_eq!(c.stat().0, vec![1, 2, 3]);
+        }
+
+        eviction_order_test!(smallest_id_policy (SmallestId, 2): 5, 3, 8, 1, 9 => 3, 5, 1);
+    }
+
+    mod lfuda_aging {
+        use super::*;
+
+        #[test]
+        fn stale_hot_item_ages_out() {
+            let mut trace = vec![0; 5];
+            trace.extend([1, 1, 2, 2, 3, 3, 4, 4]);
+            let trace = Trace::from(trace);
+
+            let mut lfu = Cache::<Lfu, Evictions>::new(2);
+            lfu.run_trace(&trace);
+
+            let mut lfuda = Cache::<Lfuda, Evictions>::new(2);
+            lfuda.run_trace(&trace);
+
+            // pure LFU holds on to 0 forever, since nothing else reaches its count
+            assert!(lfu.set().contains(&0));
+            assert_eq!(lfu.stat().0, vec![1, 2, 3]);
+
+            // but with aging, each eviction raises the bar until 0 falls below it
+            assert_eq!(lfuda.stat().0, vec![1, 2, 0]);
+            assert_eq!(lfuda.set(), &HashSet::from([3, 4]));
+            assert_eq!(lfuda.replacement_policy().age(), 5);
+        }
+
+        #[test]
+        fn frequencies_reset_on_eviction() {
+            let mut c = Cache::<Lfuda, Evictions>::new(1);
+
+            c.access_all([0, 0, 0, 1, 0]);
+
+            // 0 comes back with frequency one on top of the age left by 1's eviction
+            assert_eq!(c.stat().0, vec![0, 1]);
+            assert_eq!(c.replacement_policy().entries[&0], (1, 5));
+        }
+    }
+
+    mod gdsf {
+        use super::*;
+        use crate::GeneralModelGenerator;
+
+        #[test]
+        fn frequent_small_item_survives() {
+            let mut cache = Cache::<Gdsf, (), _>::new(10);
+            let mut gen = GeneralModelGenerator::new();
+
+            let small = gen.item(1.0, 1);
+            let large = gen.item(1.0, 8);
+            let new = gen.item(1.0, 2);
+
+            cache.access(large);
+            for _ in 0..5 {
+                cache.access(small);
+            }
+            cache.access(new);
+
+            assert_eq!(cache.set(), &HashSet::from([small, new]));
+            assert!((cache.replacement_policy().inflation() - 0.125).abs() < f64::EPSILON);
+        }
+
+        #[test]
+        fn evicts_until_it_fits() {
+            let mut cache = Cache::<Gdsf, (), _>::new(6);
+            let mut gen = GeneralModelGenerator::new();
+
+            let a = gen.item(1.0, 1);
+            let b = gen.item(2.0, 3);
+            let c = gen.item(1.0, 2);
+            let d = gen.item(1.0, 5);
+
+            cache.access(a);
+            cache.access(b);
+            cache.access(c);
+            cache.access(d);
+
+            // c and then b have the lowest priorities
+            assert_eq!(cache.set(), &HashSet::from([a, d]));
+        }
+
+        #[test]
+        fn inflation_is_monotonic() {
+            let mut cache = Cache::<Gdsf, (), _>::new(8);
+            let mut gen = GeneralModelGenerator::new();
+
+            let items: Vec<_> = (1..=6)
+                .map(|i| gen.item(f64::from(i % 3 + 1), i % 4 + 1))
+                .collect();
+
+            let mut last = 0.0;
+            for i in [0, 1, 2, 0, 3, 4, 0, 5, 1, 2, 3, 0, 4, 5, 5, 1, 0, 2] {
+                cache.access(items[i]);
+
+                let inflation = cache.replacement_policy().inflation();
+                assert!(inflation >= last);
+                last = inflation;
+            }
+
+            assert!(last > 0.0);
+        }
+    }
+
+    mod sized_items {
+        use super::*;
+        use crate::generate::zipf_trace;
+        use crate::GeneralModelGenerator;
+
+        macro_rules! sized_test {
+            ($name:ident ($policy:ty)) => {
+                #[test]
+                fn $name() {
+                    let mut gen = GeneralModelGenerator::new();
+                    let small: Vec<_> = (0..4).map(|_| gen.item(1.0, 1)).collect();
+                    let large = gen.item(1.0, 3);
+
+                    // filling the cache with small items means one eviction can't fit the large one
+                    let mut c = Cache::<$policy, (), GeneralModelItem>::new(4);
+                    c.access_all(small.iter().copied());
+                    c.access(large);
+                    assert!(c.contains(&large));
+                    assert_eq!(c.occupied_size(), 4);
+
+                    let items: Vec<_> = (1..=30).map(|i| gen.item(1.0, i % 4 + 1)).collect();
+                    let mut c = Cache::<$policy, (), GeneralModelItem>::new(9);
+                    for i in zipf_trace(30, 0.8, 3000, 5) {
+                        c.access(items[i as usize]);
+                        assert!(c.occupied_size() <= 9);
+                    }
+                }
+            };
+        }
+
+        sized_test!(arc(Arc<GeneralModelItem>));
+        sized_test!(car(Car<GeneralModelItem>));
+        sized_test!(clock_pro(ClockPro<GeneralModelItem>));
+        sized_test!(two_q(TwoQ<GeneralModelItem>));
+        sized_test!(lirs(Lirs<GeneralModelItem>));
+        sized_test!(s3_fifo(S3Fifo<GeneralModelItem>));
+        sized_test!(w_tiny_lfu(WTinyLfu<GeneralModelItem>));
+    }
+
+    mod tiny_lfu {
+        use super::*;
+
+        #[test]
+        fn one_hit_wonder_rejected() {
+            let mut c = Cache::<TinyLfu<Lru>, Evictions>::new(2);
+
+            for i in [0, 0, 0, 1, 1, 1, 2] {
+                c.access(i);
+            }
+
+            // 0 would be the LRU victim, but it's hotter than 2
+            assert_eq!(c.set(), &HashSet::from([0, 1]));
+            assert!(c.stat().0.is_empty());
+
+            // once 2 is hotter than the victim, it's admitted
+            c.access(2);
+            c.access(2);
+            assert_eq!(c.set(), &HashSet::from([0, 1]));
+
+            c.access(2);
+            assert_eq!(c.set(), &HashSet::from([1, 2]));
+            assert_eq!(c.stat().0, vec![0]);
+        }
+
+        macro_rules! victims_test {
+            ($name:ident ($policy:ty)) => {
+                #[test]
+                fn $name() {
+                    let mut gen = crate::GeneralModelGenerator::new();
+                    let items: Vec<_> = (0..8).map(|i| gen.item(1.0, i % 3 + 1)).collect();
+                    let trace = (0..200).map(|i| items[(i * 7 + i / 5) % items.len()]);
+
+                    let mut c = Cache::<$policy, (), GeneralModelItem>::new(6);
+                    for item in trace {
+                        if !c.contains(&item) {
+                            let policy = c.replacement_policy();
+                            let mut copy = policy.clone();
+                            assert_eq!(
+                                policy.victims(c.set(), 6, item),
+                                copy.replace(c.set(), 6, item)
+                            );
+                        }
+                        c.access(item);
+                    }
+                }
+            };
+        }
+
+        victims_test!(lru_victims(Lru<GeneralModelItem>));
+        victims_test!(fifo_victims(Fifo<GeneralModelItem>));
+
+        #[test]
+        fn wrapped_policy_not_cloned() {
+            #[derive(Default)]
+            struct Unclonable(Lru);
+
+            impl Clone for Unclonable {
+                fn clone(&self) -> Self {
+                    panic!("TinyLFU cloned the wrapped policy.");
+                }
+            }
+
+            impl<H: BuildHasher> ReplacementPolicy<u32, H> for Unclonable {
+                fn update_state(&mut self, set: &HashSet<u32, H>, capacity: u32, next: u32) {
+                    self.0.update_state(set, capacity, next);
+                }
+
+                fn replace(
+                    &mut self,
+                    set: &HashSet<u32, H>,
+                    capacity: u32,
+                    next: u32,
+                ) -> HashSet<u32> {
+                    self.0.replace(set, capacity, next)
+                }
+
+                fn victims(&self, set: &HashSet<u32, H>, capacity: u32, next: u32) -> HashSet<u32> {
+                    self.0.victims(set, capacity, next)
+                }
+
+                fn shrink(&mut self, set: &HashSet<u32, H>, capacity: u32) -> HashSet<u32> {
+                    self.0.shrink(set, capacity)
+                }
+
+                fn remove(&mut self, item: u32) {
+                    ReplacementPolicy::<u32, H>::remove(&mut self.0, item);
+                }
+
+                fn clear(&mut self) {
+                    ReplacementPolicy::<u32, H>::clear(&mut self.0);
+                }
+            }
+
+            let mut c = Cache::<TinyLfu<Unclonable>>::new(2);
+
+            // 2 is admitted once it's hotter than 0, but 3 never is
+            c.access_all([0, 0, 1, 2, 2, 2, 3]);
+            assert_eq!(c.set(), &HashSet::from([1, 2]));
+        }
+
+        #[test]
+        fn rejection_leaves_policy_untouched() {
+            let mut c = Cache::<TinyLfu<Fifo>>::new(2);
+
+            for i in [0, 0, 1, 1, 2] {
+                c.access(i);
+            }
+
+            assert_eq!(
+                c.replacement_policy().policy().stack,
+                VecDeque::from([0, 1])
+            );
+        }
+
+        #[test]
+        fn sketch_aging() {
+            let mut sketch = CountMinSketch::new(16, 2, 4);
+
+            for _ in 0..3 {
+                sketch.increment(&0);
+            }
+            assert_eq!(sketch.estimate(&0), 3);
+
+            // the fourth sample fills the window, so everything is halved
+            sketch.increment(&1);
+            assert_eq!(sketch.estimate(&0), 1);
+            assert_eq!(sketch.estimate(&1), 0);
+        }
+    }
+
+    mod w_tiny_lfu {
+        use super::*;
+
+        #[test]
+        fn burst_does_not_pollute_main() {
+            let mut c = Cache::<WTinyLfu, Evictions>::new(100);
+
+            let hot = 0..100;
+            for _ in 0..5 {
+                c.access_all(hot.clone());
+            }
+            assert_eq!(c.replacement_policy().main_len(), 99);
+
+            // every one-hit wonder passes through the window and loses to the main region's victim
+            c.access_all(1000..2000);
+            let policy = c.replacement_policy();
+            assert_eq!(policy.window_len(), 1);
+            assert!(policy.in_window(&1999));
+            assert!(c.set().iter().all(|&i| i < 100 || i == 1999));
+            assert!(c.stat().0.iter().all(|&i| i >= 1000 || i == 99));
+
+            // so the hot items still hit, except the one which was in the window
+            c.reset_stats();
+            c.access_all(hot);
+            assert_eq!(c.hits(), 99);
+        }
+
+        #[test]
+        fn reaccess_in_window_admits() {
+            let mut c = Cache::<WTinyLfu, Evictions>::new(4);
+
+            c.access_all([0, 0, 1, 1, 2, 2]);
+            // 5 loses to the victim 0 when it leaves the window, but 3 is seen often enough there
+            // to beat it
+            c.access_all([5, 3, 3, 3, 6]);
+
+            assert!(c.contains(&3));
+            assert_eq!(c.stat().0, vec![5, 0]);
+        }
+
+        #[test]
+        fn main_is_slru() {
+            let mut c = Cache::<WTinyLfu>::new(10);
+            c.access_all([0, 1, 2, 1]);
+
+            // 0 and 1 spilled over into probation, and the hit promoted 1
+            let main = c.replacement_policy().main();
+            assert!(main.is_protected(&1));
+            assert!(!main.is_protected(&0));
+            assert!(c.replacement_policy().in_window(&2));
+        }
+
+        #[test]
+        fn candidate_beats_every_victim() {
+            let mut gen = crate::GeneralModelGenerator::new();
+            let (a, b, candidate, newest) = (
+                gen.item(1.0, 1),
+                gen.item(1.0, 1),
+                gen.item(1.0, 2),
+                gen.item(1.0, 1),
+            );
+
+            let mut policy = WTinyLfu::new(0.25, 64, 4, 1000);
+            policy.main.probationary.extend([a, b]);
+            policy.window.extend([candidate, newest]);
+            for (item, count) in [(a, 1), (b, 5), (candidate, 3)] {
+                for _ in 0..count {
+                    policy.sketch.increment(&item);
+                }
+            }
+
+            // the candidate needs both victims' room, and it's seen more often than a, but not b
+            assert_eq!(policy.spill(3), HashSet::from([candidate]));
+            assert_eq!(policy.main.probationary, VecDeque::from([a, b]));
+            assert_eq!(policy.window, VecDeque::from([newest]));
+        }
+
+        #[test]
+        #[should_panic]
+        fn nan_window_ratio() {
+            let _ = WTinyLfu::<u32>::new(f64::NAN, 64, 4, 1000);
+        }
+
+        #[test]
+        #[should_panic]
+        fn negative_window_ratio() {
+            let _ = WTinyLfu::<u32>::new(-0.1, 64, 4, 1000);
+        }
+    }
+
+    mod prefetch {
+        use super::*;
+        use crate::{AccessResult, Trace};
+
+        #[test]
+        fn sequential_trace_hits() {
+            let trace = Trace::from((0..100).collect::<Vec<_>>());
+
+            let mut lru = Cache::<Lru>::new(4);
+            lru.run_trace(&trace);
+            let mut prefetching_lru = Cache::<Prefetch<Lru>>::new(4);
+            prefetching_lru.run_trace(&trace);
+
+            let mut fifo = Cache::<Fifo>::new(4);
+            fifo.run_trace(&trace);
+            let mut prefetching_fifo = Cache::<Prefetch<Fifo>>::new(4);
+            prefetching_fifo.run_trace(&trace);
+
+            assert_eq!((lru.hits(), fifo.hits()), (0, 0));
+            assert_eq!(prefetching_lru.hits(), 50);
+            assert_eq!(prefetching_fifo.hits(), 50);
+        }
+
+        #[test]
+        fn prefetches_arent_accesses() {
+            let mut c = Cache::<Prefetch<Lru>, Evictions>::new(2);
+
+            assert_eq!(c.access(0), AccessResult::MissNoEvict);
+            assert_eq!((c.hits(), c.misses()), (0, 1));
+            assert_eq!(c.set(), &HashSet::from([0, 1]));
+
+            // 2 evicts 0, and prefetching 3 evicts 1, but only the first is recorded
+            assert_eq!(c.access(2), AccessResult::MissEvicted(HashSet::from([0])));
+            assert_eq!(c.set(), &HashSet::from([2, 3]));
+            assert_eq!(c.stat().0, vec![0]);
+
+            assert!(c.access(3).is_hit());
+        }
+
+        #[test]
+        fn only_on_misses() {
+            let mut c = Cache::<Prefetch<Lru>>::new(3);
+            c.prepopulate([5]);
+
+            c.access(5);
+            assert_eq!(c.set(), &HashSet::from([5]));
+        }
+
+        #[test]
+        fn no_successor() {
+            let mut c = Cache::<Prefetch<Lru>>::new(2);
+            c.access(u32::MAX);
+            assert_eq!(c.set(), &HashSet::from([u32::MAX]));
+        }
+
+        #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+        struct Block {
+            address: u32,
+            write: bool,
+        }
+
+        impl std::fmt::Display for Block {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", self.address)
+            }
+        }
+
+        impl Item for Block {
+            fn cost(&self) -> f64 {
+                1.0
+            }
+
+            fn size(&self) -> u32 {
+                1
+            }
+
+            fn is_write(&self) -> bool {
+                self.write
+            }
+        }
+
+        impl crate::item::Sequential for Block {
+            fn next(&self) -> Option<Self> {
+                Some(Self {
+                    address: self.address + 1,
+                    write: false,
+                })
+            }
+        }
+
+        #[derive(Default)]
+        struct Evicted(Vec<u32>);
+
+        impl<H: BuildHasher> Stat<Block, H> for Evicted {
+            fn update(&mut self, _: &HashSet<Block, H>, _: Block, _: &HashSet<Block>) {}
+
+            fn evict(&mut self, _: &HashSet<Block, H>, evicted: &HashSet<Block>) {
+                self.0.extend(evicted.iter().map(|block| block.address));
+            }
+        }
+
+        #[test]
+        fn prefetch_evictions_are_reported() {
+            use std::cell::RefCell;
+            use std::rc::Rc;
+
+            let mut c = Cache::<Prefetch<Lru<Block>>, Evicted, Block>::new(3);
+            c.set_write_policy(crate::WritePolicy::WriteBack);
+
+            let causes = Rc::new(RefCell::new(Vec::new()));
+            let listener_causes = Rc::clone(&causes);
+            c.on_evict(move |evicted, cause| {
+                listener_causes
+                    .borrow_mut()
+                    .push((evicted.address, cause.map(|block| block.address)));
+            });
+
+            // 0 is written and prefetches 1, then 5 fits, but prefetching 6 evicts 0
+            c.access(Block {
+                address: 0,
+                write: true,
+            });
+            assert_eq!(
+                c.access(Block {
+                    address: 5,
+                    write: false,
+                }),
+                AccessResult::MissNoEvict
+            );
+
+            assert_eq!(c.writebacks(), 1);
+            assert_eq!(c.stat().0, vec![0]);
+            assert_eq!(&*causes.borrow(), &[(0, Some(6))]);
+        }
+    }
+
+    mod multi_eviction {
+        use super::*;
+        use crate::GeneralModelGenerator;
+
+        macro_rules! multi_eviction_test {
+            ($name:ident ($policy:ty) => $($out:expr),*) => {
+                #[test]
+                fn $name() {
+                    let mut cache = Cache::<$policy, (), _>::new(6);
+                    let mut gen = GeneralModelGenerator::new();
+
+                    let items = [gen.item(1.0, 1), gen.item(1.0, 3), gen.item(1.0, 5)];
+                    for item in items {
+                        cache.access(item);
+                    }
+
+                    assert_eq!(cache.set(), &HashSet::from([$(items[$out]),*]));
+                }
+            };
+        }
+
+        multi_eviction_test!(lru (Lru<_>) => 2);
+        multi_eviction_test!(fifo (Fifo<_>) => 2);
+        multi_eviction_test!(mru (Mru<_>) => 0, 2);
+        multi_eviction_test!(lfu (Lfu<_, Lru<_>>) => 2);
+        multi_eviction_test!(fbr (Fbr<_>) => 2);
+        multi_eviction_test!(lfuda (Lfuda<_, Lru<_>>) => 2);
+        multi_eviction_test!(clock (Clock<_>) => 2);
+        multi_eviction_test!(sieve (Sieve<_>) => 2);
+        multi_eviction_test!(lru_2 (LruK<2, _>) => 2);
+        multi_eviction_test!(mq (Mq<_>) => 2);
+        multi_eviction_test!(gdsf (Gdsf) => 0, 2);
+
+        #[test]
+        fn oversized_item_bypasses() {
+            let mut cache = Cache::<Lru<_>, (), _>::new(4);
+            let mut gen = GeneralModelGenerator::new();
+
+            let small = gen.item(1.0, 2);
+            let huge = gen.item(1.0, 5);
+
+            cache.access(small);
+            cache.access(huge);
+
+            assert_eq!(cache.set(), &HashSet::from([small]));
+            assert_eq!(cache.misses(), 2);
+            assert_eq!(cache.replacement_policy().stack, vec![small]);
+        }
+    }
+
+    mod shrink {
+        use super::*;
+
+        macro_rules! shrink_order_test {
+            ($name:ident ($policy:ty) => $($out:expr),*) => {
+                #[test]
+                fn $name() {
+                    let mut cache = Cache::<$policy>::new(4);
+                    for item in [0, 1, 2, 3, 0, 1] {
+                        cache.access(item);
+                    }
+
+                    cache.resize(2);
+                    assert_eq!(cache.set(), &HashSet::from([$($out),*]));
+                }
+            };
+        }
+
+        shrink_order_test!(lru (Lru) => 0, 1);
+        shrink_order_test!(fifo (Fifo) => 2, 3);
+        shrink_order_test!(mru (Mru) => 2, 3);
+
+        /// Records the items evicted by shrinking the cache.
+        #[derive(Default)]
+        struct ShrinkEvictions(Vec<u32>);
+
+        impl Stat<u32> for ShrinkEvictions {
+            fn update(&mut self, _: &HashSet<u32>, _: u32, _: &HashSet<u32>) {}
+
+            fn evict(&mut self, _: &HashSet<u32>, evicted: &HashSet<u32>) {
+                self.0.extend(evicted);
+            }
+        }
+
+        #[test]
+        fn evictions_are_reported() {
+            use std::cell::RefCell;
+            use std::rc::Rc;
+
+            let mut cache = Cache::<Lru, (crate::stats::HitCount, ShrinkEvictions)>::new(4);
+            let evictions = Rc::new(RefCell::new(Vec::new()));
+            let listener_evictions = Rc::clone(&evictions);
+            cache.on_evict(move |evicted, cause| {
+                listener_evictions.borrow_mut().push((evicted, cause));
+            });
+
+            cache.access_all([0, 1, 2, 3, 0]);
+            cache.resize(3);
+
+            // the statistic sees the eviction even inside a tuple, but the access counts don't
+            assert_eq!(cache.stat().0 .0, 1);
+            assert_eq!(cache.stat().1 .0, vec![1]);
+            assert_eq!(&*evictions.borrow(), &[(1, None)]);
+        }
+
+        macro_rules! shrink_test {
+            ($name:ident ($policy:ty)) => {
+                #[test]
+                fn $name() {
+                    let mut cache = Cache::<$policy>::new(4);
+                    let trace = [0, 1, 2, 0, 3, 4, 0, 5, 1, 2];
+
+                    for item in trace {
+                        cache.access(item);
+                    }
+                    cache.resize(1);
+                    assert_eq!(cache.set().len(), 1);
+
+                    for item in trace {
+                        cache.access(item);
+                    }
+                    cache.resize(0);
+                    assert!(cache.set().is_empty());
+
+                    cache.resize(3);
+                    for item in trace {
+                        cache.access(item);
+                        assert!(cache.set().contains(&item));
+                    }
+                    assert_eq!(cache.set().len(), 3);
+                }
+            };
         }
 
-        self.tiebreaker.update_state(set, capacity, next);
+        shrink_test!(lru_to_zero(Lru));
+        shrink_test!(fifo_to_zero(Fifo));
+        shrink_test!(rand_to_zero(Rand));
+        shrink_test!(hyperbolic_to_zero(Hyperbolic));
+        shrink_test!(mru_to_zero(Mru));
+        shrink_test!(clock_to_zero(Clock));
+        shrink_test!(two_q_to_zero(TwoQ));
+        shrink_test!(lirs_to_zero(Lirs));
+        shrink_test!(lru_2_to_zero(LruK<2>));
+        shrink_test!(mq_to_zero(Mq));
+        shrink_test!(sieve_to_zero(Sieve));
+        shrink_test!(s3_fifo_to_zero(S3Fifo));
+        shrink_test!(slru_to_zero(Slru));
+        shrink_test!(lfu_to_zero(Lfu));
+        shrink_test!(fbr_to_zero(Fbr));
+        shrink_test!(lfuda_to_zero(Lfuda));
+        shrink_test!(landlord_to_zero(Landlord<u32, Lru>));
+        shrink_test!(arc_to_zero(Arc));
+        shrink_test!(car_to_zero(Car));
+        shrink_test!(clock_pro_to_zero(ClockPro));
+        shrink_test!(gdsf_to_zero(Gdsf<u32>));
+        shrink_test!(prefetch_to_zero(Prefetch<Lru>));
+        shrink_test!(lrfu_to_zero(Lrfu));
+        shrink_test!(w_tiny_lfu_to_zero(WTinyLfu));
+        shrink_test!(smallest_id_to_zero(SmallestId));
+        shrink_test!(scan_resistant_lru_to_zero(ScanResistantLru));
     }
 
-    fn replace(&mut self, set: &HashSet<I>, capacity: u32, next: I) -> HashSet<I> {
-        let mut to_evict = HashSet::default();
+    mod clear {
+        use super::*;
+        use crate::generate::zipf_trace;
 
-        while set
-            .iter()
-            .filter(|i| !to_evict.contains(*i))
-            .map(Item::size)
-            .sum::<u32>()
-            + next.size()
-            > capacity
-        {
-            // have to compute min cost by hand because of limitations with float
-            let mut current_delta = f64::MAX;
-            let mut current_min_item = None;
-            for item in set {
-                let item_delta = *self
-                    .credit
-                    .get(item)
-                    .expect("Items in the set have a credit.")
-                    / f64::from(item.size());
-                if item_delta < current_delta {
-                    current_delta = item_delta;
-                    current_min_item = Some(item);
+        macro_rules! clear_test {
+            ($name:ident ($policy:ty, $init:expr)) => {
+                #[test]
+                fn $name() {
+                    let trace = zipf_trace(40, 0.9, 600, 6);
+
+                    let mut reused =
+                        Cache::<$policy, Evictions>::with_replacement_policy($init, 5u32);
+                    reused.run_trace(&trace);
+                    reused.clear();
+                    reused.reset_stats();
+                    assert!(reused.set().is_empty());
+                    reused.run_trace(&trace);
+
+                    let mut fresh =
+                        Cache::<$policy, Evictions>::with_replacement_policy($init, 5u32);
+                    fresh.run_trace(&trace);
+
+                    assert_eq!(reused.stat().0, fresh.stat().0);
+                    assert_eq!(reused.set(), fresh.set());
                 }
-            }
+            };
+            ($name:ident ($policy:ty)) => {
+                clear_test!($name($policy, <$policy>::default()));
+            };
+        }
 
-            let min = current_min_item.expect("The set is non-empty.");
-            let delta =
-                self.credit.get(min).expect("The item is in the set.") / f64::from(min.size());
+        // GDSF isn't here, since it breaks ties between equal priorities by hash order, so even two
+        // fresh caches can differ
+        clear_test!(lru(Lru));
+        clear_test!(fifo(Fifo));
+        clear_test!(mru(Mru));
+        clear_test!(clock(Clock));
+        clear_test!(two_q(TwoQ));
+        clear_test!(slru(Slru));
+        clear_test!(scan_resistant_lru(ScanResistantLru));
+        clear_test!(lirs(Lirs));
+        clear_test!(lru_2(LruK<2>));
+        clear_test!(mq(Mq));
+        clear_test!(sieve(Sieve));
+        clear_test!(s3_fifo(S3Fifo));
+        clear_test!(lfu(Lfu));
+        clear_test!(fbr(Fbr));
+        clear_test!(lfuda(Lfuda));
+        clear_test!(landlord(Landlord<u32, Lru>));
+        clear_test!(arc(Arc));
+        clear_test!(car(Car));
+        clear_test!(clock_pro(ClockPro));
+        clear_test!(tiny_lfu(TinyLfu<Lru>));
+        clear_test!(prefetch(Prefetch<Lru>));
+        clear_test!(lrfu(Lrfu));
+        clear_test!(w_tiny_lfu(WTinyLfu));
+        clear_test!(lfu_fifo(Lfu<u32, Fifo>));
+        clear_test!(lfu_smallest_id(Lfu<u32, SmallestId>));
+        clear_test!(two_q_bloom(TwoQ<u32, Bloom<u32>>));
+        clear_test!(arc_bloom(Arc<u32, Bloom<u32>>));
+        clear_test!(s3_fifo_bloom(S3Fifo<u32, Bloom<u32>>));
+        clear_test!(rand(Rand, Rand::with_seed(3)));
+        clear_test!(hyperbolic(Hyperbolic, Hyperbolic::with_seed(4, 5)));
+    }
 
-            // decrease the credit for everything in the set
-            for item in set {
-                *self.credit.get_mut(item).expect("The item is in the set.") -=
-                    delta * f64::from(item.size());
-            }
+    mod snapshot {
+        use super::*;
+        use crate::generate::zipf_trace;
 
-            // evict items with no credit
-            to_evict.extend(
-                self.tiebreaker.tiebreak(
-                    &set.iter()
-                        .filter(|&i| !to_evict.contains(i))
-                        .filter(|i| {
-                            abs_diff_eq!(self.credit.get(i).expect("The item is in the set."), &0.0)
-                        })
-                        .copied()
-                        .collect(),
-                    set.iter()
-                        .filter(|i| !to_evict.contains(*i))
-                        .map(Item::size)
-                        .sum::<u32>()
-                        + next.size()
-                        - capacity,
-                ),
-            );
+        macro_rules! snapshot_test {
+            ($name:ident ($policy:ty, $init:expr)) => {
+                #[test]
+                fn $name() {
+                    let trace = zipf_trace(40, 0.9, 1000, 9);
+                    let (before, after) = trace.inner().split_at(400);
+
+                    let mut c = Cache::<$policy>::with_replacement_policy($init, 6u32);
+                    c.access_all(before.iter().copied());
+                    let state = c.snapshot();
+
+                    let first: Vec<_> = after.iter().map(|&i| c.access(i)).collect();
+                    let first_hits = c.hits();
+
+                    c.restore(state);
+                    let second: Vec<_> = after.iter().map(|&i| c.access(i)).collect();
+
+                    assert_eq!(first, second);
+                    assert_eq!(first_hits, c.hits());
+                }
+            };
         }
 
-        self.update_state(set, capacity, next);
+        snapshot_test!(lru(Lru, Lru::default()));
+        snapshot_test!(arc(Arc, Arc::default()));
+        snapshot_test!(lirs(Lirs, Lirs::default()));
+        snapshot_test!(tiny_lfu(TinyLfu<Lru>, TinyLfu::default()));
+        snapshot_test!(w_tiny_lfu(WTinyLfu, WTinyLfu::default()));
+        // the random number generator is restored too, so even random choices are repeated
+        snapshot_test!(rand(Rand, Rand::with_seed(3)));
+        snapshot_test!(hyperbolic(Hyperbolic, Hyperbolic::with_seed(8, 3)));
 
-        to_evict
+        #[test]
+        fn restores_dirty_items() {
+            let mut c = Cache::<Lru<_>, (), GeneralModelItem>::new(1);
+            c.set_write_policy(crate::WritePolicy::WriteBack);
+
+            let mut g = crate::GeneralModelGenerator::new();
+            let (a, b) = (g.item(1.0, 1).with_write(true), g.item(1.0, 1));
+
+            c.access(a);
+            let state = c.snapshot();
+            c.access(b);
+            assert_eq!(c.writebacks(), 1);
+
+            c.restore(state);
+            assert_eq!(c.writebacks(), 0);
+            c.access(b);
+            assert_eq!(c.writebacks(), 1);
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::Cache;
+    mod access_observer {
+        use super::*;
+        use std::cell::Cell;
+        use std::rc::Rc;
 
-    macro_rules! integration_test {
-        ($name:ident ($policy:ty): $($in:expr),* => $($out:expr),*) => {
-            #[test]
-            fn $name() {
-                let mut c = Cache::<$policy>::new(3);
+        #[test]
+        fn latency_model() {
+            let latency = Rc::new(Cell::new(0));
 
-                $(
-                    c.access($in);
-                )*
+            let mut c = Cache::<Lru>::new(2);
+            let observer_latency = Rc::clone(&latency);
+            c.set_access_observer(move |_, hit| {
+                observer_latency.set(observer_latency.get() + if hit { 1 } else { 100 });
+            });
 
-                assert_eq!(c.set(), &HashSet::from([$($out),*]));
-            }
-        };
-    }
+            // misses on 0, 1, 2, and the second 1; hits on the second 0 and the last 2
+            c.run_trace(&Trace::from(vec![0, 1, 0, 2, 1, 2]));
+            assert_eq!((c.hits(), c.misses()), (2, 4));
+            assert_eq!(latency.get(), 2 + 400);
+        }
 
-    macro_rules! replacement_policy_test {
-        ($name:ident ($policy:ty):
-            counting_up => $($counting_up_out:expr),*;
-            repeated => $($repeated_out:expr),*;
-            one_repetition => $($one_repetition_out:expr),*;
-            cycle => $($cycle_out:expr),*;
-        ) => {
-            mod $name {
-                use super::*;
+        #[test]
+        fn fires_before_eviction() {
+            let mut c = Cache::<Lru>::new(1);
+            c.prepopulate([0]);
 
-                integration_test!(counting_up ($policy): 0, 1, 2, 3 => $($counting_up_out),*);
-                integration_test!(repeated ($policy): 0, 0, 0, 0 =>  $($repeated_out),*);
-                integration_test!(one_repetition ($policy): 0, 1, 2, 0, 3 => $($one_repetition_out),*);
-                integration_test!(cycle ($policy): 0, 1, 2, 0, 1, 2, 0, 1, 2, 3, 3 => $($cycle_out),*);
-            }
+            let seen = Rc::new(Cell::new(None));
+            let observer_seen = Rc::clone(&seen);
+            c.set_access_observer(move |&item, hit| observer_seen.set(Some((item, hit))));
+
+            // the observer runs before 0 is evicted, but not for prepopulating accesses
+            assert_eq!(seen.get(), None);
+            c.access(1);
+            assert_eq!(seen.get(), Some((1, false)));
+            c.access(1);
+            assert_eq!(seen.get(), Some((1, true)));
         }
     }
 
-    replacement_policy_test! {
-        lru (Lru):
-            counting_up => 1, 2, 3;
-            repeated => 0;
-            one_repetition => 0, 2, 3;
-            cycle => 1, 2, 3;
-    }
+    mod hasher {
+        use super::*;
+        use crate::generate::zipf_trace;
+        use crate::hash::StableHasher;
+        use std::hash::BuildHasherDefault;
 
-    replacement_policy_test! {
-        mru (Mru):
-            counting_up => 0, 1, 3;
-            repeated => 0;
-            one_repetition => 1, 2, 3;
-            cycle => 0, 1, 3;
-    }
+        /// A deterministic hasher with a different algorithm from the standard library's, standing
+        /// in for a faster one like `FxHash`.
+        type Deterministic = BuildHasherDefault<StableHasher>;
 
-    replacement_policy_test! {
-        fifo (Fifo):
-            counting_up => 1, 2, 3;
-            repeated => 0;
-            one_repetition => 1, 2, 3;
-            cycle => 1, 2, 3;
+        macro_rules! hasher_test {
+            ($name:ident ($policy:ty)) => {
+                #[test]
+                fn $name() {
+                    let trace = zipf_trace(200, 0.8, 20_000, 8);
+
+                    let mut std = Cache::<$policy, Evictions>::new(25);
+                    std.run_trace(&trace);
+
+                    let mut custom = Cache::<$policy, Evictions, u32, Deterministic>::new(25);
+                    custom.run_trace(&trace);
+
+                    assert_eq!(std.stat().0, custom.stat().0);
+                    assert_eq!(std.hits(), custom.hits());
+                    assert!(std.iter().all(|i| custom.contains(i)));
+                }
+            };
+        }
+
+        // policies which break ties by hash order, like GDSF, or randomly, like RAND, can't be
+        // expected to agree
+        hasher_test!(lru(Lru));
+        hasher_test!(fifo(Fifo));
+        hasher_test!(mru(Mru));
+        hasher_test!(clock(Clock));
+        hasher_test!(two_q(TwoQ));
+        hasher_test!(slru(Slru));
+        hasher_test!(lirs(Lirs));
+        hasher_test!(lru_2(LruK<2>));
+        hasher_test!(mq(Mq));
+        hasher_test!(sieve(Sieve));
+        hasher_test!(s3_fifo(S3Fifo));
+        hasher_test!(lfu(Lfu));
+        hasher_test!(fbr(Fbr));
+        hasher_test!(lfuda(Lfuda));
+        hasher_test!(landlord(Landlord<u32, Lru>));
+        hasher_test!(arc(Arc));
+        hasher_test!(car(Car));
+        hasher_test!(clock_pro(ClockPro));
+        hasher_test!(tiny_lfu(TinyLfu<Lru>));
     }
 
     mod landlord {
@@ -478,4 +5797,309 @@ mod tests {
             assert_eq!(cache.set(), &HashSet::from([itm_a, itm_d, itm_z]));
         }
     }
+
+    mod arc {
+        use super::*;
+
+        // expected evictions are from a reference implementation of the algorithm in the paper
+        eviction_order_test!(reference (Arc, 3): 0, 1, 2, 0, 3, 1, 4, 0, 5, 2, 1, 3 => 1, 2, 0, 3, 4, 5, 2);
+        eviction_order_test!(capacity_one (Arc, 1): 0, 1, 1, 2, 1, 0, 3, 0, 2 => 0, 1, 2, 1, 0, 3, 0);
+
+        #[test]
+        fn ghost_hits_adapt_p() {
+            let mut c = Cache::<Arc, Evictions>::new(3);
+
+            for i in [0, 1, 2, 0, 1, 3, 4, 2] {
+                c.access(i);
+            }
+
+            // 2 was a hit in b1, so t1 should be allowed to grow
+            assert_eq!(c.replacement_policy().p(), 1);
+
+            for i in [3, 5, 0, 4] {
+                c.access(i);
+            }
+
+            assert_eq!(c.replacement_policy().p(), 3);
+            assert_eq!(c.stat().0, vec![2, 3, 0, 1, 2, 4, 3]);
+            assert_eq!(c.set(), &HashSet::from([0, 4, 5]));
+        }
+
+        #[test]
+        fn ghosts_are_not_resident() {
+            let mut c = Cache::<Arc>::new(2);
+
+            for i in [0, 0, 1, 2] {
+                c.access(i);
+            }
+
+            let arc = c.replacement_policy();
+            assert_eq!(arc.b1.keys(), &VecDeque::from([1]));
+            assert!(!c.set().contains(&1));
+            assert_eq!(arc.t1.len() + arc.t2.len(), c.set().len());
+        }
+    }
+
+    replacement_policy_test! {
+        car (Car):
+            counting_up => 1, 2, 3;
+            repeated => 0;
+            one_repetition => 0, 2, 3;
+            cycle => 1, 2, 3;
+    }
+
+    mod car_clocks {
+        use super::*;
+
+        // expected evictions are from a reference implementation of the algorithm in the paper
+        eviction_order_test!(reference (Car, 3): 0, 1, 2, 0, 3, 1, 4, 0, 5, 2, 1, 3 => 1, 2, 3, 4, 5, 2);
+        eviction_order_test!(capacity_one (Car, 1): 0, 1, 1, 2, 1, 0, 3, 0, 2 => 0, 1, 2, 1, 0, 3, 0);
+        eviction_order_test!(capacity_four (Car, 4): 0, 1, 2, 3, 0, 1, 4, 0, 1, 2, 3, 4, 5, 0, 1 => 2, 3, 4, 2, 3);
+
+        #[test]
+        fn ghost_hits_adapt_p() {
+            let mut c = Cache::<Car, Evictions>::new(3);
+
+            for i in [0, 1, 2, 0, 1, 3, 4, 2] {
+                c.access(i);
+            }
+
+            // 2 was a hit in b1, so t1 should be allowed to grow
+            assert_eq!(c.replacement_policy().p(), 1);
+            assert_eq!(c.set(), &HashSet::from([0, 1, 2]));
+
+            for i in [3, 5, 0, 4] {
+                c.access(i);
+            }
+
+            assert_eq!(c.replacement_policy().p(), 2);
+            assert_eq!(c.stat().0, vec![2, 3, 4, 0, 1, 2, 5]);
+            assert_eq!(c.set(), &HashSet::from([0, 3, 4]));
+        }
+
+        #[test]
+        fn referenced_items_move_to_t2() {
+            let mut c = Cache::<Car>::new(3);
+
+            for i in [0, 1, 2, 0, 3] {
+                c.access(i);
+            }
+
+            // the hand found 0 referenced, so it moved to t2 and 1 was evicted to b1
+            let car = c.replacement_policy();
+            assert_eq!(car.t1, VecDeque::from([(2, false), (3, false)]));
+            assert_eq!(car.t2, VecDeque::from([(0, false)]));
+            assert_eq!(car.b1.keys(), &VecDeque::from([1]));
+        }
+    }
+
+    replacement_policy_test! {
+        clock_pro (ClockPro):
+            counting_up => 1, 2, 3;
+            repeated => 0;
+            one_repetition => 0, 2, 3;
+            cycle => 1, 2, 3;
+    }
+
+    mod clock_pro_hands {
+        use super::*;
+
+        fn statuses(clock_pro: &ClockPro, items: &[u32]) -> Vec<Option<ClockProStatus>> {
+            items.iter().map(|item| clock_pro.status(item)).collect()
+        }
+
+        #[test]
+        fn classification_after_each_access() {
+            use ClockProStatus::{Cold, Hot, Test};
+
+            let mut c = Cache::<ClockPro, Evictions>::new(3);
+            let items = [0, 1, 2, 3];
+
+            for item in [0, 1, 2] {
+                c.access(item);
+            }
+            assert_eq!(
+                statuses(c.replacement_policy(), &items),
+                vec![Some(Cold), Some(Cold), Some(Cold), None]
+            );
+
+            // a hit only sets the reference bit
+            c.access(0);
+            assert_eq!(
+                statuses(c.replacement_policy(), &items),
+                vec![Some(Cold), Some(Cold), Some(Cold), None]
+            );
+
+            // HAND_cold promotes the referenced 0, then evicts 1, which becomes a test page
+            c.access(3);
+            assert_eq!(
+                statuses(c.replacement_policy(), &items),
+                vec![Some(Hot), Some(Test), Some(Cold), Some(Cold)]
+            );
+            assert_eq!(c.replacement_policy().cold_target(), 1);
+
+            // 1 is reused in its test period, so it comes back hot and the cold target grows;
+            // then there's only room for one hot page, so HAND_hot demotes 0
+            c.access(1);
+            assert_eq!(
+                statuses(c.replacement_policy(), &items),
+                vec![Some(Cold), Some(Hot), Some(Test), Some(Cold)]
+            );
+            assert_eq!(c.replacement_policy().cold_target(), 2);
+
+            assert_eq!(c.stat().0, vec![1, 2]);
+            assert_eq!(c.set(), &HashSet::from([0, 1, 3]));
+        }
+
+        #[test]
+        fn expired_test_pages_shrink_cold_target() {
+            let mut c = Cache::<ClockPro>::new(3);
+
+            for item in [0, 1, 2, 0, 3, 1] {
+                c.access(item);
+            }
+            assert_eq!(c.replacement_policy().cold_target(), 2);
+
+            // a scan of new items fills the clock with test pages, the oldest of which expire
+            for item in 10..20 {
+                c.access(item);
+            }
+
+            let clock_pro = c.replacement_policy();
+            assert_eq!(clock_pro.cold_target(), 1);
+            assert!(clock_pro.count(ClockProStatus::Test) <= 3);
+            assert!(clock_pro.status(&2).is_none());
+        }
+
+        #[test]
+        fn hands_stay_in_bounds() {
+            let mut c = Cache::<ClockPro>::new(4);
+            let trace = crate::generate::zipf_trace(20, 0.9, 2000, 1);
+
+            for &item in trace.iter() {
+                c.access(item);
+                let clock_pro = c.replacement_policy();
+                let len = clock_pro.clock.len();
+
+                assert!(clock_pro.hot_hand < len && clock_pro.cold_hand < len);
+                assert!(clock_pro.test_hand < len);
+                assert!(clock_pro.count(ClockProStatus::Test) <= 4);
+                assert_eq!(
+                    clock_pro.count(ClockProStatus::Hot) + clock_pro.count(ClockProStatus::Cold),
+                    4.min(c.set().len() as u32)
+                );
+            }
+        }
+    }
+
+    replacement_policy_test! {
+        mq (Mq):
+            counting_up => 1, 2, 3;
+            repeated => 0;
+            one_repetition => 0, 2, 3;
+            cycle => 1, 2, 3;
+    }
+
+    mod mq_queues {
+        use super::*;
+
+        #[test]
+        fn queue_rises_with_frequency() {
+            let mut c = Cache::<Mq>::new(2);
+            let mut queues = Vec::new();
+
+            for _ in 0..9 {
+                c.access(0);
+                queues.push(c.replacement_policy().queue(&0).expect("0 is resident."));
+            }
+
+            assert_eq!(queues, vec![0, 1, 1, 2, 2, 2, 2, 3, 3]);
+        }
+
+        #[test]
+        fn evicts_from_lowest_queue() {
+            let mut c = Cache::<Mq, Evictions>::new(3);
+
+            // 0 is the least recently used, but it's in a higher queue than 1 and 2
+            for i in [0, 0, 0, 1, 2, 3, 4] {
+                c.access(i);
+            }
+
+            assert_eq!(c.stat().0, vec![1, 2]);
+            assert_eq!(c.set(), &HashSet::from([0, 3, 4]));
+        }
+
+        #[test]
+        fn queues_are_capped() {
+            let mut c = Cache::<Mq>::with_replacement_policy(Mq::new(2, 100), 2u32);
+            for _ in 0..10 {
+                c.access(0);
+            }
+
+            assert_eq!(c.replacement_policy().queue(&0), Some(1));
+        }
+
+        #[test]
+        fn idle_items_expire() {
+            let mut c = Cache::<Mq>::with_replacement_policy(Mq::new(8, 2), 3u32);
+
+            for i in [0, 0, 1, 2] {
+                c.access(i);
+            }
+            assert_eq!(c.replacement_policy().queue(&0), Some(1));
+
+            // 0 hasn't been accessed for more than the life time, so it expires to queue 0, behind
+            // 1 and 2
+            c.access(2);
+            assert_eq!(c.replacement_policy().queue(&0), Some(0));
+
+            c.access(3);
+            assert_eq!(c.set(), &HashSet::from([0, 2, 3]));
+        }
+
+        #[test]
+        fn history_remembers_frequency() {
+            let mut c = Cache::<Mq>::new(1);
+
+            for i in [0, 0, 0, 1, 0] {
+                c.access(i);
+            }
+
+            // 0 was evicted with frequency 3, so it comes back with frequency 4
+            assert_eq!(c.replacement_policy().queue(&0), Some(2));
+        }
+    }
+
+    mod rand_seed {
+        use super::*;
+        use crate::generate::zipf_trace;
+
+        fn evictions(policy: Rand) -> Vec<u32> {
+            let mut c = Cache::<Rand, Evictions>::with_replacement_policy(policy, 8u32);
+            c.run_trace(&zipf_trace(50, 0.8, 1000, 0));
+            c.stat().0.clone()
+        }
+
+        #[test]
+        fn same_seed_same_evictions() {
+            let evicted = evictions(Rand::with_seed(42));
+
+            assert!(!evicted.is_empty());
+            assert_eq!(evicted, evictions(Rand::with_seed(42)));
+        }
+
+        #[test]
+        fn different_seeds_differ() {
+            assert_ne!(evictions(Rand::with_seed(1)), evictions(Rand::with_seed(2)));
+        }
+
+        #[test]
+        fn never_refuses_admission() {
+            let mut c = Cache::<Rand>::with_replacement_policy(Rand::with_seed(0), 3u32);
+            for i in 0..20 {
+                c.access(i);
+                assert!(c.contains(&i));
+            }
+        }
+    }
 }
@@ -0,0 +1,181 @@
+//! A set-associative cache, like most hardware caches.
+
+use crate::item::SetIndex;
+use crate::replacement_policy::ReplacementPolicy;
+use crate::trace::Trace;
+use crate::{AccessResult, Cache};
+
+/// A cache split into several sets of a fixed number of ways, each of which is managed
+/// independently.
+///
+/// Each item can only be cached in the set given by its [`SetIndex`]; for `u32`s that's the item
+/// modulo the number of sets, like a hardware cache indexing by the low bits of the address. So
+/// items which map to the same set compete for its ways even when other sets are empty, giving
+/// conflict misses which a fully-associative [`Cache`] of the same total capacity wouldn't have.
+/// Each set is a cache of its own with capacity `ways`, running its own copy of the replacement
+/// policy.
+///
+/// With one way per set, this is a direct-mapped cache; with one set, it's fully associative.
+///
+/// ```
+/// use cache_sim::{Lru, SetAssociativeCache, Trace};
+///
+/// // 0, 2, and 4 all map to set 0, and only two of them fit
+/// let trace = Trace::from(vec![0, 2, 4, 0, 1, 1]);
+///
+/// let mut c = SetAssociativeCache::<Lru>::new(2, 2);
+/// c.run_trace(&trace);
+///
+/// assert_eq!(c.misses(), 5);
+/// assert_eq!(c.set_miss_rates(), vec![1.0, 0.5]);
+/// ```
+pub struct SetAssociativeCache<P: ReplacementPolicy<I>, I: SetIndex = u32> {
+    sets: Vec<Cache<P, (), I>>,
+}
+
+impl<P: ReplacementPolicy<I> + Default, I: SetIndex> SetAssociativeCache<P, I> {
+    /// Create an empty cache with `sets` sets of `ways` ways each, using the default parameters
+    /// for the replacement policy.
+    ///
+    /// The ways bound the total size of the items in a set, like a [`Cache`]'s capacity.
+    ///
+    /// # Panics
+    ///
+    /// If there are no sets.
+    #[must_use]
+    pub fn new(sets: usize, ways: u32) -> Self {
+        assert!(sets > 0, "There must be at least one set.");
+
+        Self {
+            sets: (0..sets).map(|_| Cache::new(ways)).collect(),
+        }
+    }
+}
+
+impl<P: ReplacementPolicy<I>, I: SetIndex> SetAssociativeCache<P, I> {
+    /// Get the index of the set `item` belongs in.
+    pub fn set_index(&self, item: &I) -> usize {
+        item.set_index(self.sets.len())
+    }
+
+    /// Access `item` in its set.
+    ///
+    /// # Panics
+    ///
+    /// If the item's set index is out of range.
+    pub fn access(&mut self, item: I) -> AccessResult<I> {
+        let index = self.set_index(&item);
+        self.sets[index].access(item)
+    }
+
+    /// Access every item of the trace, in order.
+    pub fn run_trace(&mut self, trace: &Trace<I>) {
+        for &item in trace {
+            self.access(item);
+        }
+    }
+
+    /// Check whether `item` is in the cache.
+    pub fn contains(&self, item: &I) -> bool {
+        self.sets[self.set_index(item)].contains(item)
+    }
+
+    /// Get the sets, each of which is a fully-associative cache.
+    #[must_use]
+    pub fn sets(&self) -> &[Cache<P, (), I>] {
+        &self.sets
+    }
+
+    /// Get the total number of hits in every set.
+    #[must_use]
+    pub fn hits(&self) -> u64 {
+        self.sets.iter().map(Cache::hits).sum()
+    }
+
+    /// Get the total number of misses in every set.
+    #[must_use]
+    pub fn misses(&self) -> u64 {
+        self.sets.iter().map(Cache::misses).sum()
+    }
+
+    /// Get the fraction of all accesses which missed, or zero if there haven't been any accesses.
+    #[must_use]
+    pub fn miss_rate(&self) -> f64 {
+        miss_rate(self.hits(), self.misses())
+    }
+
+    /// Get the miss rate of each set, which is zero for sets which were never accessed.
+    #[must_use]
+    pub fn set_miss_rates(&self) -> Vec<f64> {
+        self.sets
+            .iter()
+            .map(|set| miss_rate(set.hits(), set.misses()))
+            .collect()
+    }
+}
+
+#[allow(clippy::cast_precision_loss)] // caches see much fewer than 2^52 accesses
+fn miss_rate(hits: u64, misses: u64) -> f64 {
+    let accesses = hits + misses;
+    if accesses == 0 {
+        0.0
+    } else {
+        misses as f64 / accesses as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Fifo, Lru};
+
+    #[test]
+    fn direct_mapped_conflicts() {
+        let mut c = SetAssociativeCache::<Lru>::new(4, 1);
+
+        // 1 and 5 share set 1, so they keep evicting each other, while 2 stays put in set 2
+        let trace = [1, 5, 1, 2, 5, 2, 1, 9, 5];
+        let expected = [false, false, false, false, false, true, false, false, false];
+        for (item, hit) in trace.into_iter().zip(expected) {
+            assert_eq!(c.access(item).is_hit(), hit, "accessing {item}");
+        }
+
+        assert_eq!(c.set_index(&9), 1);
+        assert!(c.contains(&5) && !c.contains(&1) && !c.contains(&9));
+        assert_eq!((c.hits(), c.misses()), (1, 8));
+        assert_eq!(c.set_miss_rates(), vec![0.0, 1.0, 0.5, 0.0]);
+    }
+
+    #[test]
+    fn one_set_is_fully_associative() {
+        let trace = crate::generate::zipf_trace(40, 0.9, 1000, 1);
+
+        let mut associative = SetAssociativeCache::<Fifo>::new(1, 8);
+        associative.run_trace(&trace);
+        let mut cache = Cache::<Fifo>::new(8);
+        cache.run_trace(&trace);
+
+        assert_eq!(associative.hits(), cache.hits());
+        assert_eq!(associative.sets()[0].set(), cache.set());
+    }
+
+    #[test]
+    fn conflicts_cost_hits() {
+        let trace = crate::generate::zipf_trace(64, 0.8, 2000, 5);
+
+        let mut direct = SetAssociativeCache::<Lru>::new(16, 1);
+        direct.run_trace(&trace);
+        let mut full = Cache::<Lru>::new(16);
+        full.run_trace(&trace);
+
+        assert!(direct.hits() < full.hits());
+        assert_eq!(direct.hits() + direct.misses(), 2000);
+    }
+
+    #[test]
+    fn empty() {
+        let c = SetAssociativeCache::<Lru>::new(2, 2);
+        assert_eq!(c.miss_rate(), 0.0);
+        assert_eq!(c.set_miss_rates(), vec![0.0, 0.0]);
+    }
+}
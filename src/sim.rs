@@ -0,0 +1,556 @@
+//! Helpers for running whole simulations.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::item::Item;
+use crate::replacement_policy::ReplacementPolicy;
+use crate::stats::Stat;
+use crate::{Cache, Trace};
+
+/// Run a trace through a cache with each of several replacement policies, returning the name and
+/// hit rate of each.
+///
+/// The policies are type-erased, so they can be of different types. Each gets its own empty cache
+/// with the given capacity, and results are in the same order as the policies.
+///
+/// ```
+/// use cache_sim::replacement_policy::ReplacementPolicy;
+/// use cache_sim::sim::compare_policies;
+/// use cache_sim::{Fifo, Lru, Trace};
+///
+/// let trace = Trace::from(vec![0, 1, 0, 2, 0, 3, 0, 1]);
+/// let policies: Vec<(_, Box<dyn ReplacementPolicy<_>>)> = vec![
+///     ("LRU", Box::new(Lru::default())),
+///     ("FIFO", Box::new(Fifo::default())),
+/// ];
+///
+/// let results = compare_policies(&trace, 2, policies);
+/// assert_eq!(results[0], (String::from("LRU"), 3.0 / 8.0));
+/// assert_eq!(results[1], (String::from("FIFO"), 2.0 / 8.0));
+/// ```
+pub fn compare_policies<I: Item, N: Into<String>>(
+    trace: &Trace<I>,
+    capacity: u32,
+    policies: impl IntoIterator<Item = (N, Box<dyn ReplacementPolicy<I>>)>,
+) -> Vec<(String, f64)> {
+    policies
+        .into_iter()
+        .map(|(name, policy)| {
+            let mut cache = Cache::<_, (), I>::with_replacement_policy(policy, capacity);
+            cache.run_trace(trace);
+            (name.into(), cache.hit_rate())
+        })
+        .collect()
+}
+
+/// Simulate the miss ratio curve of a replacement policy, by running the trace through a cache of
+/// each capacity from 0 to `max_capacity`.
+///
+/// The cth element is the miss ratio with capacity c, so the curve has `max_capacity + 1` points,
+/// and the first is always 1.0, since a cache with no room misses every access. That's the same
+/// indexing as [`StackDistance::miss_ratio_curve`](crate::trace::StackDistance::miss_ratio_curve),
+/// which is much faster but only models LRU, so the two curves can be compared index by index.
+/// Each capacity needs a fresh policy, so they're made by calling `policy`. The curve is empty if
+/// the trace is.
+///
+/// ```
+/// use cache_sim::sim::miss_ratio_curve;
+/// use cache_sim::{Fifo, Trace};
+///
+/// let trace = Trace::from(vec![0, 1, 0, 2, 0, 1]);
+/// let curve = miss_ratio_curve(&trace, Fifo::default, 3);
+///
+/// assert_eq!(curve, vec![1.0, 1.0, 5.0 / 6.0, 3.0 / 6.0]);
+/// ```
+#[allow(clippy::cast_precision_loss)] // traces are much shorter than 2^52 accesses
+pub fn miss_ratio_curve<I: Item, R: ReplacementPolicy<I>>(
+    trace: &Trace<I>,
+    mut policy: impl FnMut() -> R,
+    max_capacity: u32,
+) -> Vec<f64> {
+    if trace.is_empty() {
+        return Vec::new();
+    }
+
+    (0..=max_capacity)
+        .map(|capacity| {
+            let mut cache = Cache::<_, (), I>::with_replacement_policy(policy(), capacity);
+            cache.run_trace(trace);
+            cache.misses() as f64 / trace.len() as f64
+        })
+        .collect()
+}
+
+/// Find where a replacement policy exhibits Belady's anomaly on a trace, returning each capacity
+/// from 1 to `max_capacity` with more misses than the capacity one smaller.
+///
+/// This runs [`miss_ratio_curve`], so `policy` makes a fresh policy for each capacity. Stack
+/// policies like LRU never have anomalies, but FIFO can:
+///
+/// ```
+/// use cache_sim::sim::detect_belady_anomaly;
+/// use cache_sim::{Fifo, Lru, Trace};
+///
+/// let trace = Trace::from(vec![1, 2, 3, 4, 1, 2, 5, 1, 2, 3, 4, 5]);
+///
+/// assert_eq!(detect_belady_anomaly(&trace, Fifo::default, 5), vec![4]);
+/// assert!(detect_belady_anomaly(&trace, Lru::default, 5).is_empty());
+/// ```
+pub fn detect_belady_anomaly<I: Item, R: ReplacementPolicy<I>>(
+    trace: &Trace<I>,
+    policy: impl FnMut() -> R,
+    max_capacity: u32,
+) -> Vec<usize> {
+    // every ratio has the same denominator, so comparing them compares the misses exactly
+    miss_ratio_curve(trace, policy, max_capacity)
+        .windows(2)
+        .enumerate()
+        .filter(|(_, pair)| pair[1] > pair[0])
+        .map(|(capacity, _)| capacity + 1)
+        .collect()
+}
+
+/// The reason for a cache miss.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MissKind {
+    /// The first ever access to the item, which no cache could have hit.
+    Compulsory,
+    /// An access to an item which has been seen before, but was evicted (or never admitted).
+    Capacity,
+}
+
+/// Records the kind of each access, for [`miss_classification`].
+#[derive(Default)]
+struct MissClassifier<I: Item> {
+    seen: HashSet<I>,
+    kinds: Vec<Option<MissKind>>,
+}
+
+impl<I: Item> Stat<I> for MissClassifier<I> {
+    fn update(&mut self, set: &HashSet<I>, next: I, _: &HashSet<I>) {
+        self.kinds.push(if set.contains(&next) {
+            None
+        } else if self.seen.insert(next) {
+            Some(MissKind::Compulsory)
+        } else {
+            Some(MissKind::Capacity)
+        });
+    }
+}
+
+/// Classify each access of a trace through a cache with the given policy and capacity.
+///
+/// The cth element is `None` if access c was a hit, or else the kind of miss. Every item's first
+/// access is a compulsory miss, so the compulsory misses are the same for every policy; the rest
+/// of the misses are the ones a better policy or bigger cache could avoid.
+///
+/// ```
+/// use cache_sim::sim::{miss_classification, MissKind};
+/// use cache_sim::{Lru, Trace};
+///
+/// let trace = Trace::from(vec![0, 1, 0, 2, 1]);
+/// let kinds = miss_classification(&trace, 2, Lru::default());
+///
+/// assert_eq!(
+///     kinds,
+///     vec![
+///         Some(MissKind::Compulsory),
+///         Some(MissKind::Compulsory),
+///         None,
+///         Some(MissKind::Compulsory),
+///         Some(MissKind::Capacity), // 2 evicted 1
+///     ]
+/// );
+/// ```
+pub fn miss_classification<I: Item, R: ReplacementPolicy<I>>(
+    trace: &Trace<I>,
+    capacity: u32,
+    policy: R,
+) -> Vec<Option<MissKind>> {
+    let mut cache = Cache::<_, MissClassifier<I>, I>::with_replacement_policy(policy, capacity);
+    cache.run_trace(trace);
+    cache.stat().kinds.clone()
+}
+
+/// Count the hits and misses of each item of a trace through a cache with the given policy and
+/// capacity.
+///
+/// Each item maps to its `(hits, misses)`, which together add up to its frequency in the trace.
+/// Sorting by misses shows which items are most responsible for the cache's miss ratio.
+///
+/// ```
+/// use cache_sim::sim::per_item_stats;
+/// use cache_sim::{Lru, Trace};
+///
+/// let trace = Trace::from(vec![0, 1, 0, 2, 1, 0]);
+/// let stats = per_item_stats(&trace, 2, Lru::default());
+///
+/// assert_eq!(stats[&0], (1, 2));
+/// assert_eq!(stats[&1], (0, 2));
+/// assert_eq!(stats[&2], (0, 1));
+/// ```
+pub fn per_item_stats<I: Item, R: ReplacementPolicy<I>>(
+    trace: &Trace<I>,
+    capacity: u32,
+    policy: R,
+) -> HashMap<I, (usize, usize)> {
+    let mut stats = HashMap::new();
+    for (item, kind) in trace
+        .iter()
+        .zip(miss_classification(trace, capacity, policy))
+    {
+        let (hits, misses) = stats.entry(*item).or_insert((0, 0));
+        if kind.is_some() {
+            *misses += 1;
+        } else {
+            *hits += 1;
+        }
+    }
+    stats
+}
+
+/// Run a trace through a cache with the given policy and capacity, returning whether each access
+/// hit.
+///
+/// The result is as long as the trace, so it can be lined up position by position with other
+/// per-access measures, like [`Trace::stack_distances`].
+///
+/// ```
+/// use cache_sim::sim::simulate;
+/// use cache_sim::{Lru, Trace};
+///
+/// let trace = Trace::from(vec![0, 1, 0, 2, 1]);
+/// let hits = simulate(&trace, 2, Lru::default());
+///
+/// assert_eq!(hits, vec![false, false, true, false, false]);
+/// ```
+pub fn simulate<I: Item, R: ReplacementPolicy<I>>(
+    trace: &Trace<I>,
+    capacity: u32,
+    policy: R,
+) -> Vec<bool> {
+    let mut cache = Cache::<_, (), I>::with_replacement_policy(policy, capacity);
+    trace
+        .iter()
+        .map(|item| cache.access(*item).is_hit())
+        .collect()
+}
+
+/// Run a trace through a cache with the given policy and capacity, returning the number of items
+/// resident after each access.
+///
+/// For unit-sized items and a policy which admits every item, the occupancy grows by one with each
+/// new item until the cache is full, and then stays at the capacity. Larger items take up more of
+/// the capacity, so they fill the cache with fewer items.
+///
+/// ```
+/// use cache_sim::sim::occupancy_timeline;
+/// use cache_sim::{Lru, Trace};
+///
+/// let trace = Trace::from(vec![0, 1, 0, 2, 3]);
+/// let occupancy = occupancy_timeline(&trace, 2, Lru::default());
+///
+/// assert_eq!(occupancy, vec![1, 2, 2, 2, 2]);
+/// ```
+pub fn occupancy_timeline<I: Item, R: ReplacementPolicy<I>>(
+    trace: &Trace<I>,
+    capacity: u32,
+    policy: R,
+) -> Vec<usize> {
+    let mut cache = Cache::<_, (), I>::with_replacement_policy(policy, capacity);
+    trace
+        .iter()
+        .map(|item| {
+            cache.access(*item);
+            cache.set().len()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Fifo, Lfu, Lru, Mru};
+
+    fn policies() -> Vec<(&'static str, Box<dyn ReplacementPolicy<u32>>)> {
+        vec![
+            ("LRU", Box::new(Lru::default())),
+            ("FIFO", Box::new(Fifo::default())),
+            ("LFU", Box::new(Lfu::<u32>::default())),
+            ("MRU", Box::new(Mru::default())),
+        ]
+    }
+
+    #[test]
+    fn lru_friendly() {
+        // a hot item which FIFO keeps evicting, since it was inserted first
+        let trace = Trace::from(vec![0, 1, 0, 2, 0, 3, 0, 4, 0, 5, 0, 6]);
+
+        let results = compare_policies(&trace, 2, policies());
+        let hit_rate = |name| {
+            results
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, hit_rate)| *hit_rate)
+                .expect("every policy has a result")
+        };
+
+        assert!(hit_rate("LRU") >= hit_rate("FIFO"));
+        assert!(hit_rate("LRU") > 0.0);
+    }
+
+    #[test]
+    fn preserves_order() {
+        let trace = Trace::from(vec![0, 1, 2]);
+        let names = compare_policies(&trace, 2, policies())
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, vec!["LRU", "FIFO", "LFU", "MRU"]);
+    }
+
+    #[test]
+    fn empty_trace() {
+        let results = compare_policies(&Trace::default(), 2, policies());
+        assert!(results.iter().all(|(_, hit_rate)| *hit_rate == 0.0));
+    }
+
+    mod miss_ratio_curve {
+        use super::*;
+        use crate::generate::zipf_trace;
+
+        #[test]
+        fn lru_matches_stack_distances() {
+            for trace in [
+                Trace::from(vec![0, 0, 1, 0, 3, 0, 1]),
+                zipf_trace(50, 0.8, 2000, 0),
+            ] {
+                let simulated = miss_ratio_curve(&trace, Lru::default, 60);
+                let analytic = trace.stack_distances().miss_ratio_curve();
+
+                // the analytic curve stops once it's flat
+                for (capacity, miss_ratio) in simulated.into_iter().enumerate() {
+                    assert_eq!(miss_ratio, analytic[capacity.min(analytic.len() - 1)]);
+                }
+            }
+        }
+
+        #[test]
+        fn fresh_policy_per_capacity() {
+            let mut made = 0;
+            let _ = miss_ratio_curve(
+                &Trace::from(vec![0, 1]),
+                || {
+                    made += 1;
+                    Fifo::default()
+                },
+                4,
+            );
+            assert_eq!(made, 5);
+        }
+
+        #[test]
+        fn empty_trace() {
+            assert!(miss_ratio_curve(&Trace::<u32>::default(), Lru::default, 4).is_empty());
+        }
+    }
+
+    mod belady_anomaly {
+        use super::*;
+        use crate::generate::zipf_trace;
+
+        #[test]
+        fn classic_fifo_anomaly() {
+            let trace = Trace::from(vec![1, 2, 3, 4, 1, 2, 5, 1, 2, 3, 4, 5]);
+
+            // 9 misses with capacity 3, but 10 with capacity 4
+            assert_eq!(detect_belady_anomaly(&trace, Fifo::default, 6), vec![4]);
+        }
+
+        #[test]
+        fn never_for_lru() {
+            for seed in 0..5 {
+                let trace = zipf_trace(30, 0.7, 500, seed);
+                assert!(detect_belady_anomaly(&trace, Lru::default, 40).is_empty());
+            }
+        }
+
+        #[test]
+        fn empty_trace() {
+            assert!(detect_belady_anomaly(&Trace::<u32>::default(), Fifo::default, 4).is_empty());
+        }
+    }
+
+    mod miss_classification {
+        use super::*;
+
+        #[test]
+        fn first_accesses_are_compulsory() {
+            let trace = Trace::from(vec![0, 1, 2, 0, 3, 1, 0, 2]);
+            let kinds = miss_classification(&trace, 2, Lru::default());
+
+            let mut seen = HashSet::new();
+            for (item, kind) in trace.iter().zip(&kinds) {
+                if seen.insert(item) {
+                    assert_eq!(*kind, Some(MissKind::Compulsory));
+                } else {
+                    assert_ne!(*kind, Some(MissKind::Compulsory));
+                }
+            }
+
+            // every reuse has at least two distinct items in between, so none of them hit
+            assert_eq!(kinds.iter().filter(|kind| kind.is_none()).count(), 0);
+            assert_eq!(
+                kinds
+                    .iter()
+                    .filter(|&&kind| kind == Some(MissKind::Capacity))
+                    .count(),
+                4
+            );
+        }
+
+        #[test]
+        fn big_enough_cache_only_misses_compulsorily() {
+            let trace = Trace::from(vec![0, 1, 2, 0, 3, 1, 0, 2]);
+            let kinds = miss_classification(&trace, 4, Fifo::default());
+
+            assert_eq!(
+                kinds,
+                vec![
+                    Some(MissKind::Compulsory),
+                    Some(MissKind::Compulsory),
+                    Some(MissKind::Compulsory),
+                    None,
+                    Some(MissKind::Compulsory),
+                    None,
+                    None,
+                    None,
+                ]
+            );
+        }
+
+        #[test]
+        fn counts_match_cache() {
+            let trace = crate::generate::zipf_trace(30, 1.0, 500, 2);
+            let kinds = miss_classification(&trace, 5, Lru::default());
+
+            let mut cache = Cache::<Lru>::new(5);
+            cache.run_trace(&trace);
+
+            assert_eq!(kinds.len(), trace.len());
+            assert_eq!(
+                kinds.iter().filter(|kind| kind.is_some()).count(),
+                cache.misses() as usize
+            );
+        }
+    }
+
+    mod per_item_stats {
+        use super::*;
+        use crate::condition::NoCondition;
+
+        #[test]
+        fn sums_to_frequencies() {
+            let trace = crate::generate::zipf_trace(20, 0.9, 300, 4);
+            let stats = per_item_stats(&trace, 4, Fifo::default());
+            let frequencies = trace.frequency_histogram(&NoCondition);
+
+            assert_eq!(stats.len(), frequencies.len());
+            for (item, (hits, misses)) in &stats {
+                assert_eq!(hits + misses, frequencies[item] as usize);
+            }
+        }
+
+        #[test]
+        fn counts_match_cache() {
+            let trace = Trace::from(vec![0, 1, 2, 0, 1, 3, 0, 0, 2]);
+            let stats = per_item_stats(&trace, 2, Lru::default());
+
+            let mut cache = Cache::<Lru>::new(2);
+            cache.run_trace(&trace);
+
+            assert_eq!(stats[&0], (1, 3));
+            assert_eq!(
+                stats.values().map(|(hits, _)| hits).sum::<usize>(),
+                cache.hits() as usize
+            );
+        }
+    }
+
+    mod simulate {
+        use super::*;
+        use crate::generate::zipf_trace;
+
+        #[test]
+        fn lru_hits_match_stack_distances() {
+            let trace = zipf_trace(40, 0.9, 1000, 5);
+
+            for capacity in [1, 4, 10] {
+                let hits = simulate(&trace, capacity, Lru::default());
+                let distances = trace.stack_distances();
+
+                assert_eq!(hits.len(), trace.len());
+                for (hit, distance) in hits.into_iter().zip(distances.inner()) {
+                    assert_eq!(hit, distance.is_some_and(|d| d < capacity));
+                }
+            }
+        }
+
+        #[test]
+        fn counts_match_cache() {
+            let trace = zipf_trace(20, 1.0, 300, 6);
+            let hits = simulate(&trace, 3, Fifo::default());
+
+            let mut cache = Cache::<Fifo>::new(3);
+            cache.run_trace(&trace);
+
+            assert_eq!(
+                hits.iter().filter(|&&hit| hit).count(),
+                cache.hits() as usize
+            );
+        }
+    }
+
+    mod occupancy_timeline {
+        use super::*;
+        use crate::generate::zipf_trace;
+
+        #[test]
+        fn fills_then_stays_full() {
+            let trace = zipf_trace(50, 0.7, 500, 8);
+
+            for capacity in [1, 5, 20] {
+                let occupancy = occupancy_timeline(&trace, capacity, Fifo::default());
+                let capacity = capacity as usize;
+
+                // the position of each item's first access
+                let mut seen = HashSet::new();
+                let first_accesses: Vec<usize> = (0..trace.len())
+                    .filter(|&i| seen.insert(trace[i]))
+                    .collect();
+
+                // full from the capacity-th new item, and still full just before the next
+                assert_eq!(occupancy[first_accesses[capacity - 1]], capacity);
+                assert_eq!(occupancy[first_accesses[capacity] - 1], capacity);
+                assert!(occupancy[..first_accesses[capacity - 1]]
+                    .iter()
+                    .all(|&o| o < capacity));
+                assert!(occupancy.windows(2).all(|w| w[0] <= w[1]));
+                assert!(occupancy.iter().all(|&o| o <= capacity));
+            }
+        }
+
+        #[test]
+        fn sized_items() {
+            let mut g = crate::GeneralModelGenerator::new();
+            let (a, b, c) = (g.item(1.0, 2), g.item(1.0, 3), g.item(1.0, 1));
+            let trace = Trace::from(vec![a, b, c, a]);
+
+            assert_eq!(
+                occupancy_timeline(&trace, 5, Lru::default()),
+                vec![1, 2, 2, 2]
+            );
+        }
+    }
+}
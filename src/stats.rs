@@ -1,6 +1,8 @@
 //! Implementations of statistics computed by the cache simulator.
 
+use std::collections::hash_map::RandomState;
 use std::collections::HashSet;
+use std::hash::BuildHasher;
 
 use crate::item::Item;
 
@@ -11,11 +13,20 @@ use crate::item::Item;
 /// tuple of statistics.
 #[impl_trait_for_tuples::impl_for_tuples(12)] // can't go higher bc the stdlib doesn't impl default
                                               // for bigger tuples
-pub trait Stat<I: Item>: Default {
+pub trait Stat<I: Item, H: BuildHasher = RandomState>: Default {
     /// Update the stat.
     ///
     /// We get passed the set _before_ evictions occur.
-    fn update(&mut self, set: &HashSet<I>, next: I, to_be_evicted: &HashSet<I>);
+    fn update(&mut self, set: &HashSet<I, H>, next: I, to_be_evicted: &HashSet<I>);
+
+    /// Update the stat after items are evicted without an access it sees, because the cache
+    /// [shrank](crate::Cache::resize) or made room for a [prefetched](crate::Cache::access) or
+    /// [prepopulated](crate::Cache::prepopulate) item.
+    ///
+    /// Again we get passed the set before the evictions. By default this does nothing, since most
+    /// stats are about accesses.
+    #[allow(unused_variables)] // the tuple impls forward the arguments by name
+    fn evict(&mut self, set: &HashSet<I, H>, evicted: &HashSet<I>) {}
 }
 
 /// The raw count of cache hits.
@@ -37,8 +48,8 @@ pub trait Stat<I: Item>: Default {
 #[derive(Default, Debug)]
 pub struct HitCount(pub u32);
 
-impl<I: Item> Stat<I> for HitCount {
-    fn update(&mut self, set: &HashSet<I>, next: I, _: &HashSet<I>) {
+impl<I: Item, H: BuildHasher> Stat<I, H> for HitCount {
+    fn update(&mut self, set: &HashSet<I, H>, next: I, _: &HashSet<I>) {
         if set.contains(&next) {
             self.0 += 1;
         }
@@ -64,8 +75,8 @@ impl<I: Item> Stat<I> for HitCount {
 #[derive(Default, Debug)]
 pub struct MissCount(pub u32);
 
-impl<I: Item> Stat<I> for MissCount {
-    fn update(&mut self, set: &HashSet<I>, next: I, _: &HashSet<I>) {
+impl<I: Item, H: BuildHasher> Stat<I, H> for MissCount {
+    fn update(&mut self, set: &HashSet<I, H>, next: I, _: &HashSet<I>) {
         if !set.contains(&next) {
             self.0 += 1;
         }
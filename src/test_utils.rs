@@ -0,0 +1,77 @@
+//! Helpers for testing replacement policies and trace analyses against each other.
+
+use crate::item::Item;
+use crate::sim::simulate;
+use crate::{Lru, Trace};
+
+/// Check the fundamental LRU invariant on a trace: running it through an LRU cache with the given
+/// capacity, each access hits exactly when its stack distance is less than the capacity.
+///
+/// Stack distances are size-aware, so in general an access hits when its stack distance plus the
+/// size of its item is at most the capacity; for unit-sized items that's the same thing. Items
+/// larger than the capacity bypass the cache without pushing anything out, which breaks the
+/// invariant, so every item should fit. This checks [`Lru`] and [`Trace::stack_distances`]
+/// against each other, so a bug in either shows up.
+///
+/// ```
+/// use cache_sim::generate::zipf_trace;
+/// use cache_sim::test_utils::assert_lru_matches_stack_distance;
+///
+/// assert_lru_matches_stack_distance(&zipf_trace(20, 0.8, 500, 0), 5);
+/// ```
+///
+/// # Panics
+///
+/// If any access disagrees, naming its position.
+pub fn assert_lru_matches_stack_distance<I: Item>(trace: &Trace<I>, capacity: u32) {
+    let hits = simulate(trace, capacity, Lru::default());
+    let distances = trace.stack_distances();
+
+    for (position, ((hit, distance), item)) in hits
+        .into_iter()
+        .zip(distances.inner())
+        .zip(trace)
+        .enumerate()
+    {
+        let expected = distance.is_some_and(|d| d + item.size() <= capacity);
+        assert_eq!(
+            hit,
+            expected,
+            "access {position} has stack distance {distance:?} with capacity {capacity}, but it \
+             was a {} in the LRU cache",
+            if hit { "hit" } else { "miss" }
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::zipf_trace;
+    use crate::GeneralModelGenerator;
+
+    #[test]
+    fn random_traces() {
+        for seed in 0..5 {
+            let trace = zipf_trace(60, 0.6 + 0.1 * f64::from(seed as u32), 1500, seed);
+            for capacity in [0, 1, 2, 7, 30, 100] {
+                assert_lru_matches_stack_distance(&trace, capacity);
+            }
+        }
+    }
+
+    #[test]
+    fn sized_items() {
+        let mut g = GeneralModelGenerator::new();
+        let items: Vec<_> = (1..=4).map(|size| g.item(1.0, size)).collect();
+        let trace: Trace<_> = [0, 1, 2, 0, 3, 1, 1, 2, 0, 3, 2]
+            .into_iter()
+            .map(|i| items[i])
+            .collect();
+
+        // the largest item has size 4, so smaller caches would bypass it
+        for capacity in 4..12 {
+            assert_lru_matches_stack_distance(&trace, capacity);
+        }
+    }
+}
@@ -2,15 +2,30 @@
 
 use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
+use std::hash::{BuildHasher, Hash};
 
 use itertools::Itertools;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
 
+use crate::condition::{Condition, NoCondition};
+use crate::hash::stable_hash;
+use crate::item::Cost;
 use crate::output::histogram_out;
 use crate::output::write_header;
-use crate::{condition::Condition, item::Item, stats::Stat};
+use crate::replacement_policy::ReplacementPolicy;
+use crate::{item::Item, stats::Stat, Cache};
 
 /// A trace.
+///
+/// With the `serde` feature, traces serialize as a sequence of their items, in order.
 #[derive(Debug, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 pub struct Trace<I: Item = u32> {
     inner: Vec<I>,
 }
@@ -22,6 +37,40 @@ impl<I: Item> From<Vec<I>> for Trace<I> {
 }
 
 impl<I: Item> Trace<I> {
+    /// Generate a trace with the given frequencies, in a random order determined by `seed`.
+    ///
+    /// Each item appears exactly as many times as its frequency, so the unconditional
+    /// [`Trace::frequency_histogram`] of the result is the input, minus any zero frequencies.
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use cache_sim::{NoCondition, Trace};
+    ///
+    /// let frequencies = HashMap::from([(0, 3), (1, 1), (2, 2)]);
+    /// let trace = Trace::from_frequencies(&frequencies, 0);
+    ///
+    /// assert_eq!(trace.len(), 6);
+    /// assert_eq!(trace.frequency_histogram(&NoCondition), frequencies);
+    /// ```
+    #[must_use]
+    pub fn from_frequencies<H>(frequencies: &HashMap<I, u32, H>, seed: u64) -> Self {
+        // hash maps iterate in an arbitrary order, so put the items in a deterministic one before
+        // shuffling, or the seed wouldn't determine the result
+        let mut items: Vec<(u64, I, u32)> = frequencies
+            .iter()
+            .map(|(&item, &frequency)| (stable_hash(&item), item, frequency))
+            .collect();
+        items.sort_unstable_by_key(|&(hash, _, _)| hash);
+
+        let mut inner: Vec<I> = items
+            .into_iter()
+            .flat_map(|(_, item, frequency)| std::iter::repeat_n(item, frequency as usize))
+            .collect();
+        inner.shuffle(&mut StdRng::seed_from_u64(seed));
+
+        Self { inner }
+    }
+
     /// Calculate the frequency historgram based on a given condition.
     ///
     /// ```
@@ -34,6 +83,31 @@ impl<I: Item> Trace<I> {
     /// ```
     #[must_use]
     pub fn frequency_histogram(&self, condition: &impl Condition<I>) -> HashMap<I, u32> {
+        self.frequency_histogram_with_hasher(condition)
+    }
+
+    /// Like [`Trace::frequency_histogram`], but building the map with a different hasher.
+    ///
+    /// The std hasher is DoS-resistant but slow; for huge traces, a faster hasher like `FxHash` or
+    /// `ahash` can be plugged in here. The counts are the same either way.
+    ///
+    /// ```
+    /// # use std::collections::hash_map::DefaultHasher;
+    /// # use std::collections::HashMap;
+    /// # use std::hash::BuildHasherDefault;
+    /// # use cache_sim::{Trace, NoCondition};
+    /// let trace = Trace::from(vec![0, 0, 1, 0, 3, 1]);
+    /// let frequencies: HashMap<_, _, BuildHasherDefault<DefaultHasher>> =
+    ///     trace.frequency_histogram_with_hasher(&NoCondition);
+    ///
+    /// assert_eq!(frequencies.get(&0), Some(&3));
+    /// assert!(frequencies.iter().all(|(i, f)| trace.frequency_histogram(&NoCondition)[i] == *f));
+    /// ```
+    #[must_use]
+    pub fn frequency_histogram_with_hasher<H: BuildHasher + Default>(
+        &self,
+        condition: &impl Condition<I>,
+    ) -> HashMap<I, u32, H> {
         let mut freqs = HashMap::default();
 
         for i in 0..self.inner.len() {
@@ -45,6 +119,102 @@ impl<I: Item> Trace<I> {
         freqs
     }
 
+    /// Extract the sub-trace of accesses which satisfy a given condition.
+    ///
+    /// Access i is kept if the condition holds at position i of the original trace. The kept
+    /// accesses are packed together, so positions in the sub-trace don't match positions in the
+    /// original, and anything measured in positions (like stack distances) will shrink.
+    ///
+    /// ```
+    /// use cache_sim::Trace;
+    ///
+    /// let trace = Trace::from(vec![0, 0, 1, 1, 1, 0, 2]);
+    /// let repeats = trace.filter(&|t: &Trace, i| i > 0 && t[i - 1] == t[i]);
+    ///
+    /// assert_eq!(repeats, Trace::from(vec![0, 1, 1]));
+    /// ```
+    #[must_use]
+    pub fn filter(&self, condition: &impl Condition<I>) -> Self {
+        (0..self.len())
+            .filter(|&i| condition.check(self, i))
+            .map(|i| self.inner[i])
+            .collect()
+    }
+
+    /// Collapse each run of identical consecutive accesses into its first access.
+    ///
+    /// Raw traces often record the same block several times in a row, e.g. because of instruction
+    /// prefetch; after the first, those accesses are guaranteed hits which only dilute the miss
+    /// ratio. Repeats which aren't adjacent are kept.
+    ///
+    /// ```
+    /// use cache_sim::Trace;
+    ///
+    /// let trace = Trace::from(vec![1, 1, 2, 2, 2, 1]);
+    /// assert_eq!(trace.dedupe_consecutive(), Trace::from(vec![1, 2, 1]));
+    /// ```
+    #[must_use]
+    pub fn dedupe_consecutive(&self) -> Self {
+        self.inner.iter().copied().dedup().collect()
+    }
+
+    /// Keep every `n`th access, starting with the first.
+    ///
+    /// Like any sampling, this roughly preserves the shape of the frequency distribution, but
+    /// distorts anything measured in positions, like stack distances, which shrink by about a
+    /// factor of `n`. Periodic traces whose period shares a factor with `n` will be sampled very
+    /// unevenly; [`Trace::random_sample`] doesn't have that problem.
+    ///
+    /// ```
+    /// use cache_sim::Trace;
+    ///
+    /// let trace = Trace::from(vec![0, 1, 2, 3, 4, 5, 6]);
+    /// assert_eq!(trace.stride_sample(3), Trace::from(vec![0, 3, 6]));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `n` is zero.
+    #[must_use]
+    pub fn stride_sample(&self, n: usize) -> Self {
+        assert!(n > 0, "The stride must be positive.");
+        self.iter().step_by(n).copied().collect()
+    }
+
+    /// Keep each access independently with probability `fraction`, using a random number
+    /// generator seeded with `seed`.
+    ///
+    /// As with [`Trace::stride_sample`], the frequency distribution is roughly preserved, but stack
+    /// distances are not. To sample items rather than accesses, which keeps the stack distances of
+    /// the sampled items in proportion, see [`Trace::stack_distances_shards`].
+    ///
+    /// ```
+    /// use cache_sim::Trace;
+    ///
+    /// let trace = Trace::from((0..1000).collect::<Vec<_>>());
+    /// let sample = trace.random_sample(0.1, 0);
+    ///
+    /// assert!(sample.len() > 50 && sample.len() < 150);
+    /// assert_eq!(sample, trace.random_sample(0.1, 0));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `fraction` isn't in `[0, 1]`.
+    #[must_use]
+    pub fn random_sample(&self, fraction: f64, seed: u64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&fraction),
+            "The fraction must be in [0, 1]."
+        );
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        self.iter()
+            .filter(|_| rng.gen_bool(fraction))
+            .copied()
+            .collect()
+    }
+
     /// Calculate the stack distances.
     ///
     /// ```
@@ -80,304 +250,2845 @@ impl<I: Item> Trace<I> {
         StackDistance { inner: distances }
     }
 
-    /// Write the conditional frequencies for each condition to the output stream.
+    /// Calculate the stack distances in `O(n log n)` time.
     ///
-    /// Writer is a function that can give us a writer; ideally it should return a handle to the
-    /// same underlying output stream each time.
+    /// This gives exactly the same result as [`Trace::stack_distances`], which takes `O(n^2)` time
+    /// in the worst case, but uses more memory, so it's better for long traces.
     ///
-    /// # Errors
-    /// If writing to the csv fails.
+    /// ```
+    /// use cache_sim::Trace;
     ///
-    /// TODO: figure out a non-boxed return type
-    pub fn write_conditional_frequencies<W: std::io::Write>(
-        &self,
-        conditions: HashMap<String, Box<dyn Condition<I>>>,
-        writer: impl Fn() -> anyhow::Result<W>,
-    ) -> anyhow::Result<()> {
-        // TODO: update this if we write a more efficient way to get frequencies for different
-        // conditions
-        let items = self.iter().unique().copied().collect::<Vec<_>>();
-		
-		//write header row
-		let mut labels = vec![String::from("Name"),String::from("Entropy")];
-		for item in &items{
-			labels.push(item.to_string());
-		}
-		write_header(&labels,writer()?)?;
-		
-        for (name, condition) in conditions {
-            let histogram = self.frequency_histogram(&condition);
-            histogram_out(&name, entropy(&histogram), &histogram, &items, writer()?)?;
-        }
+    /// let trace = Trace::from(vec![0, 0, 1, 0, 3, 0, 1]);
+    /// assert_eq!(
+    ///     trace.stack_distances_fast().inner(),
+    ///     trace.stack_distances().inner()
+    /// );
+    /// ```
+    #[must_use]
+    pub fn stack_distances_fast(&self) -> StackDistance {
+        let mut distances = vec![None; self.len()];
 
-        Ok(())
-    }
+        // the tree holds the size of each item at the position of its most recent access, so the
+        // stack distance is the sum of the sizes between two accesses to the same item
+        let mut tree = FenwickTree::new(self.len());
+        let mut last_seen = HashMap::new();
 
-    pub fn iter(&self) -> std::slice::Iter<I> {
-        self.inner.iter()
-    }
+        for (i, curr) in self.iter().enumerate() {
+            if let Some(last) = last_seen.insert(curr, i) {
+                distances[i] = Some(tree.prefix_sum(i) - tree.prefix_sum(last + 1));
+                tree.sub(last, curr.size());
+            }
+            tree.add(i, curr.size());
+        }
 
-    /// Get a reference to the inner vector of items.
-    #[must_use]
-    pub fn inner(&self) -> &[I] {
-        self.inner.as_ref()
+        StackDistance { inner: distances }
     }
 
-    /// Take ownership of the inner vector of items.
+    /// Approximate the stack distances with SHARDS, sampling items at a fixed rate.
     ///
-    /// The ith element of the vector is the ith access of the trace.
+    /// Detailed in this paper: <https://www.usenix.org/conference/fast15/technical-sessions/presentation/waldspurger>
+    ///
+    /// An item is sampled if its hash falls below `sampling_rate` of the hash space, so every
+    /// access to a sampled item is kept and the rest are discarded. The stack distances of the
+    /// sampled accesses are computed exactly, then scaled up by `1 / sampling_rate`.
+    ///
+    /// The result only has a distance for each _sampled_ access, so unlike
+    /// [`Trace::stack_distances`] its elements don't correspond to the accesses of the trace, and
+    /// its histogram counts are smaller by a factor of about `sampling_rate`. Ratios, like the
+    /// [`StackDistance::miss_ratio_curve`], are unaffected.
+    ///
+    /// The estimate is best when accesses are spread over many items, so that the sample is
+    /// representative. With a skewed workload it depends heavily on whether the few hottest items
+    /// happen to be sampled.
+    ///
+    /// ```
+    /// use cache_sim::generate::zipf_trace;
+    ///
+    /// for seed in 0..3 {
+    ///     let trace = zipf_trace(10_000, 0.0, 100_000, seed);
+    ///
+    ///     let exact = trace.stack_distances_fast().miss_ratio_curve();
+    ///     let approximate = trace.stack_distances_shards(0.1).miss_ratio_curve();
+    ///
+    ///     // the curves are flat past their ends
+    ///     let at = |curve: &[f64], size: usize| curve[size.min(curve.len() - 1)];
+    ///     assert!((0..=10_000).all(|size| (at(&exact, size) - at(&approximate, size)).abs() < 0.05));
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `sampling_rate` isn't in `(0, 1]`.
     #[must_use]
-    #[allow(clippy::missing_const_for_fn)] // false positive, destructors can't be const
-    pub fn into_inner(self) -> Vec<I> {
-        self.inner
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    pub fn stack_distances_shards(&self, sampling_rate: f64) -> StackDistance {
+        const MODULUS: u64 = 1 << 24;
+
+        assert!(
+            sampling_rate > 0.0 && sampling_rate <= 1.0,
+            "The sampling rate must be in (0, 1]."
+        );
+
+        let threshold = (sampling_rate * MODULUS as f64) as u64;
+        let sampled = self
+            .iter()
+            .filter(|item| {
+                // the hash is fixed, so the same items are always sampled
+                stable_hash(item) % MODULUS < threshold
+            })
+            .copied()
+            .collect::<Self>();
+
+        let inner = sampled
+            .stack_distances_fast()
+            .into_inner()
+            .into_iter()
+            .map(|distance| distance.map(|d| (f64::from(d) / sampling_rate).round() as u32))
+            .collect();
+
+        StackDistance { inner }
     }
 
-    /// Get the length of the trace.
+    /// Summarize the stack distances over consecutive, non-overlapping windows of accesses.
+    ///
+    /// Each element is the mean finite stack distance in a window of `window` accesses, and the
+    /// number of infinite distances (first accesses) in it, so that phase changes in the locality
+    /// of the trace show up as jumps between windows. The last window is shorter if `window`
+    /// doesn't divide the length of the trace. Windows with no finite distances have a mean of
+    /// zero.
+    ///
+    /// ```
+    /// use cache_sim::Trace;
+    ///
+    /// let trace = Trace::from(vec![0, 0, 1, 1, 2, 3, 2, 3]);
+    ///
+    /// assert_eq!(trace.windowed_stack_distance_stats(4), vec![(0.0, 2), (1.0, 2)]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `window` is zero.
     #[must_use]
-    pub fn len(&self) -> usize {
-        self.inner.len()
+    #[allow(clippy::cast_precision_loss)] // windows are much shorter than 2^52 accesses
+    pub fn windowed_stack_distance_stats(&self, window: usize) -> Vec<(f64, usize)> {
+        assert!(window > 0, "The window must be positive.");
+
+        self.stack_distances_fast()
+            .inner()
+            .chunks(window)
+            .map(|chunk| {
+                let (sum, finite) = chunk
+                    .iter()
+                    .flatten()
+                    .fold((0, 0_u32), |(sum, n), &d| (sum + u64::from(d), n + 1));
+                let mean = if finite == 0 {
+                    0.0
+                } else {
+                    sum as f64 / f64::from(finite)
+                };
+                (mean, chunk.len() - finite as usize)
+            })
+            .collect()
     }
 
-    /// Check whether the trace is empty.
+    /// Calculate the reuse distances.
+    ///
+    /// ```
+    /// use cache_sim::Trace;
+    ///
+    /// let distances = Trace::from(vec![0, 0, 1, 0, 3, 0, 1]).reuse_distances();
+    /// assert_eq!(
+    ///     distances.inner(),
+    ///     &[None, Some(0), None, Some(1), None, Some(1), Some(2)]
+    /// );
+    /// ```
+    ///
+    /// For more details, including how this differs from [`Trace::stack_distances`], see
+    /// [`ReuseDistance`].
     #[must_use]
-    pub fn is_empty(&self) -> bool {
-        self.inner.is_empty()
-    }
-}
+    pub fn reuse_distances(&self) -> ReuseDistance {
+        let mut distances = vec![None; self.len()];
 
-impl<I: Item> IntoIterator for Trace<I> {
-    type Item = I;
+        let mut stack: Vec<&I> = Vec::new();
 
-    type IntoIter = <Vec<I> as IntoIterator>::IntoIter;
+        for (i, curr) in self.iter().enumerate() {
+            if let Some(position) = stack.iter().position(|n| n == &curr) {
+                // everything above the item in the stack was accessed since its last reference,
+                // and each item only appears in the stack once
+                distances[i] = Some(stack.len() - position - 1);
+                stack.remove(position);
+            }
+            stack.push(curr);
+        }
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.inner.into_iter()
+        ReuseDistance { inner: distances }
     }
-}
 
-impl<'t, I: Item> IntoIterator for &'t Trace<I> {
-    type Item = &'t I;
+    /// Calculate the gaps between successive accesses to each item.
+    ///
+    /// Gaps are measured in positions in the trace, so an item accessed twice in a row has a gap
+    /// of one, no matter how many distinct items are in between. Every item in the trace is a key
+    /// of the result, so items accessed only once have no gaps.
+    ///
+    /// ```
+    /// use cache_sim::Trace;
+    ///
+    /// let gaps = Trace::from(vec![0, 1, 0, 2, 0]).inter_reference_gaps();
+    ///
+    /// assert_eq!(gaps[&0], vec![2, 2]);
+    /// assert!(gaps[&1].is_empty());
+    /// ```
+    #[must_use]
+    pub fn inter_reference_gaps(&self) -> HashMap<I, Vec<usize>> {
+        let mut gaps: HashMap<I, Vec<usize>> = HashMap::new();
+        let mut last_seen = HashMap::new();
 
-    type IntoIter = std::slice::Iter<'t, I>;
+        for (i, item) in self.iter().enumerate() {
+            let item_gaps = gaps.entry(*item).or_default();
+            if let Some(last) = last_seen.insert(item, i) {
+                item_gaps.push(i - last);
+            }
+        }
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.iter()
+        gaps
     }
-}
 
-impl<I: Item> FromIterator<I> for Trace<I> {
-    fn from_iter<T: IntoIterator<Item = I>>(iter: T) -> Self {
-        Self {
-            inner: Vec::from_iter(iter),
+    /// Calculate the survival curve of reuse times, for every time from 0 to `max_t`.
+    ///
+    /// The tth element is the fraction of references whose item is accessed again within t
+    /// positions, by the same measure as [`Trace::inter_reference_gaps`]. The curve is
+    /// non-decreasing, and its limit is the fraction of references which are reused at all; the
+    /// last reference to each item never is. It's all zeros if the trace is empty.
+    ///
+    /// ```
+    /// use cache_sim::Trace;
+    ///
+    /// // the references at 0, 1, and 2 are reused after 2, 4, and 2 positions
+    /// let curve = Trace::from(vec![0, 1, 0, 2, 0, 1]).reuse_survival(5);
+    /// assert_eq!(curve, vec![0.0, 0.0, 2.0 / 6.0, 2.0 / 6.0, 3.0 / 6.0, 3.0 / 6.0]);
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // traces are much shorter than 2^52 accesses
+    pub fn reuse_survival(&self, max_t: usize) -> Vec<f64> {
+        let mut counts = vec![0_usize; max_t + 1];
+        for gap in self.inter_reference_gaps().into_values().flatten() {
+            if gap <= max_t {
+                counts[gap] += 1;
+            }
         }
+
+        let len = self.len().max(1) as f64;
+        counts
+            .into_iter()
+            .scan(0, |reused, count| {
+                *reused += count;
+                Some(*reused as f64 / len)
+            })
+            .collect()
     }
-}
 
-// Allows indexing the trace with any type that could index the underlying vector, e.x. with usizes
-// or `Range`s from the standard library.
-impl<I: Item, Idx> std::ops::Index<Idx> for Trace<I>
-where
-    Idx: std::slice::SliceIndex<[I]>,
-{
-    type Output = Idx::Output;
+    /// Calculate the average working set size for each window size from 1 to `max_window`.
+    ///
+    /// The working set at time t for a window τ is the set of distinct items among the τ accesses
+    /// ending at t, or fewer near the start of the trace. The (τ - 1)th element of the result is
+    /// the size of the working set averaged over every t, so the curve is non-decreasing.
+    ///
+    /// ```
+    /// use cache_sim::Trace;
+    ///
+    /// let curve = Trace::from(vec![0, 1, 0, 2]).working_set_curve(3);
+    /// assert_eq!(curve, vec![1.0, 1.75, 2.0]);
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // traces are much shorter than 2^52 accesses
+    pub fn working_set_curve(&self, max_window: usize) -> Vec<f64> {
+        // an access is in the working set for every window which includes it but not the next
+        // access to the same item, so we count how many accesses stay in it for each duration
+        let mut lifetimes = vec![0; max_window + 1];
+        let mut next_access = HashMap::new();
 
-    fn index(&self, index: Idx) -> &Self::Output {
-        &self.inner[index]
+        for (i, item) in self.iter().enumerate().rev() {
+            let next = next_access.insert(item, i).unwrap_or(self.len());
+            lifetimes[(next - i).min(max_window)] += 1;
+        }
+
+        // the working set for window τ contains all the accesses which live at least τ
+        let mut alive = self.len();
+        let mut total = 0;
+        (1..=max_window)
+            .map(|window| {
+                total += alive;
+                alive -= lifetimes[window];
+                if self.is_empty() {
+                    0.0
+                } else {
+                    total as f64 / self.len() as f64
+                }
+            })
+            .collect()
     }
-}
 
-impl Trace<u32> {
-    /// If the elements in the trace are all smaller than 26, display them as letters instead.
+    /// Calculate the average footprint of windows of `window` accesses: the number of distinct
+    /// items in each window, averaged over every window of that length in the trace.
+    ///
+    /// Unlike the working set, which looks back from every access and so includes the shorter
+    /// windows at the start of the trace, only complete windows are counted. Windows longer than
+    /// the trace have the footprint of the whole trace. Xiang et al. show how to convert the
+    /// footprint into a miss ratio curve; see [`Trace::footprint_curve`].
     ///
     /// ```
-    /// # use cache_sim::Trace;
-    /// let trace = Trace::from(vec![0, 0, 2, 3, 1, 14]);
-    /// assert_eq!(&trace.pretty_print(), "A, A, C, D, B, O");
+    /// use cache_sim::Trace;
+    ///
+    /// // 01, 10, 02 have two distinct items each, and 010, 102 too
+    /// let trace = Trace::from(vec![0, 1, 0, 2]);
+    ///
+    /// assert_eq!(trace.footprint(2), 2.0);
+    /// assert_eq!(trace.footprint(3), 2.5);
+    /// assert_eq!(trace.footprint(4), 3.0);
     /// ```
+    #[must_use]
+    pub fn footprint(&self, window: usize) -> f64 {
+        if window == 0 {
+            0.0
+        } else {
+            self.footprint_curve(window)[window - 1]
+        }
+    }
+
+    /// Calculate the average footprint for every window length from 1 to `max_window`.
+    ///
+    /// The (w - 1)th element is [`Trace::footprint`] of w. This uses the linear-time formula from
+    /// Xiang et al.'s "All-Window Profiling and Composable Models of Cache Sharing": the footprint
+    /// is the number of distinct items, less the windows which miss each item, which can be
+    /// counted from the first access times, last access times, and reuse times of the items.
     ///
-    /// Note that this doesn't work for higher values of the item:
     /// ```
-    /// # use cache_sim::Trace;
-    /// let trace = Trace::from(vec![1, 2, 26]);
-    /// assert_eq!(&trace.pretty_print(), "1, 2, 26");
+    /// use cache_sim::Trace;
+    ///
+    /// let curve = Trace::from(vec![0, 1, 0, 2]).footprint_curve(4);
+    /// assert_eq!(curve, vec![1.0, 2.0, 2.5, 3.0]);
     /// ```
     #[must_use]
-    #[allow(unstable_name_collisions)] // needed here, the stdlib method will do the same as the
-                                       // itertools one when it's stabilized
-    pub fn pretty_print(&self) -> String {
-        if *self.inner.iter().max().unwrap_or(&0) < 26 {
-            self.inner
-                .iter()
-                .map(|i| {
-                    // treat the number as an ascii value; adding the ascii value of A so we get
-                    // capital letters
-                    char::from_u32(i + 'A' as u32)
-                        .expect("all elements of list are valid chars")
-                        .to_string()
-                })
-                .intersperse(", ".to_string())
-                .collect()
-        } else {
-            self.inner
+    #[allow(clippy::cast_precision_loss)] // traces are much shorter than 2^52 accesses
+    pub fn footprint_curve(&self, max_window: usize) -> Vec<f64> {
+        let len = self.len();
+
+        // an item is missing from every window which fits in one of its gaps: before its first
+        // access, after its last access, or between two accesses to it
+        let mut gaps = vec![0_usize; len + 1];
+        let mut last_access = HashMap::new();
+        for (i, item) in self.iter().enumerate() {
+            let gap = last_access.insert(item, i).map_or(i + 1, |last| i - last);
+            gaps[gap] += 1;
+        }
+        for last in last_access.values() {
+            gaps[len - last] += 1;
+        }
+
+        // the number of windows of length w which fit in a gap of length x is x - w, for x > w
+        let mut longer_gaps = 0;
+        let mut longer_gaps_total = 0;
+        let mut missing = vec![0; len + 1];
+        for w in (0..=len).rev() {
+            missing[w] = longer_gaps_total - w * longer_gaps;
+            longer_gaps += gaps[w];
+            longer_gaps_total += w * gaps[w];
+        }
+
+        let distinct = last_access.len() as f64;
+        (1..=max_window)
+            .map(|window| {
+                if window >= len {
+                    distinct
+                } else {
+                    distinct - missing[window] as f64 / (len - window + 1) as f64
+                }
+            })
+            .collect()
+    }
+
+    /// Estimate the hit ratio of an LRU cache using Che's approximation.
+    ///
+    /// This treats the trace as independent requests, with each item requested at rate
+    /// `frequency / len`, and finds the characteristic time `T` which solves
+    /// `sum_i size_i * (1 - exp(-rate_i * T)) = capacity`. An item is resident if it was requested
+    /// in the last `T` steps, so the hit ratio is `sum_i rate_i * (1 - exp(-rate_i * T))`. For
+    /// unit-size items the sizes drop out of the sum.
+    ///
+    /// The approximation ignores cold misses, so it's most accurate for long traces over stable
+    /// popularity distributions, and it's exactly 1 when the whole trace fits in the cache.
+    ///
+    /// ```
+    /// use cache_sim::Trace;
+    ///
+    /// let trace = Trace::from(vec![0, 1, 0, 2, 0, 1, 0, 3]);
+    ///
+    /// assert_eq!(trace.che_approximation(0), 0.0);
+    /// assert!(trace.che_approximation(1) < trace.che_approximation(2));
+    /// assert_eq!(trace.che_approximation(4), 1.0);
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // traces are much shorter than 2^52 accesses
+    pub fn che_approximation(&self, capacity: u32) -> f64 {
+        let len = self.len() as f64;
+        let items: Vec<(f64, f64)> = self
+            .frequency_histogram(&NoCondition)
+            .into_iter()
+            .map(|(item, freq)| (f64::from(item.size()), f64::from(freq) / len))
+            .collect();
+
+        let capacity = f64::from(capacity);
+        if capacity == 0.0 || items.is_empty() {
+            return 0.0;
+        }
+        if items.iter().map(|(size, _)| size).sum::<f64>() <= capacity {
+            return 1.0;
+        }
+
+        let occupancy = |t: f64| -> f64 {
+            items
                 .iter()
-                .map(u32::to_string)
-                .intersperse(", ".to_string())
-                .collect()
+                .map(|(size, rate)| size * -(-rate * t).exp_m1())
+                .sum()
+        };
+
+        // occupancy is increasing in `t` and approaches the total size, which is more than the
+        // capacity, so bracket the root and bisect; `hi` would be infinite after `f64::MAX_EXP`
+        // doublings
+        let (mut lo, mut hi) = (0.0_f64, 1.0);
+        for _ in 0..f64::MAX_EXP {
+            if occupancy(hi) >= capacity {
+                break;
+            }
+            lo = hi;
+            hi *= 2.0;
         }
+        for _ in 0..100 {
+            let mid = lo.midpoint(hi);
+            if occupancy(mid) < capacity {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+
+        let t = lo.midpoint(hi);
+        items
+            .iter()
+            .map(|(_, rate)| rate * -(-rate * t).exp_m1())
+            .sum()
     }
-}
 
-impl<I: Item> Display for Trace<I> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for i in &self.inner {
-            write!(f, "{} ", i)?;
+    /// Calculate the Gini coefficient of the items' popularity.
+    ///
+    /// This summarizes the skew of the frequency histogram in a single number: 0 when every item
+    /// is accessed equally often, and approaching 1 as a single item takes up more of the accesses.
+    /// With n distinct items the largest possible value is `(n - 1) / n`. Empty traces have a
+    /// coefficient of 0.
+    ///
+    /// ```
+    /// use cache_sim::Trace;
+    ///
+    /// assert_eq!(Trace::from(vec![0, 1, 2, 0, 1, 2]).popularity_gini(), 0.0);
+    /// assert_eq!(Trace::from(vec![0, 0, 0, 1]).popularity_gini(), 0.25);
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // there are much fewer than 2^52 items and accesses
+    pub fn popularity_gini(&self) -> f64 {
+        let mut frequencies: Vec<u32> = self
+            .frequency_histogram(&NoCondition)
+            .into_values()
+            .collect();
+        if frequencies.is_empty() {
+            return 0.0;
+        }
+        frequencies.sort_unstable();
+
+        // the mean absolute difference between every pair, from the frequencies in rank order
+        let n = frequencies.len() as f64;
+        let weighted: f64 = frequencies
+            .iter()
+            .enumerate()
+            .map(|(rank, &freq)| (rank + 1) as f64 * f64::from(freq))
+            .sum();
+        2.0 * weighted / (n * self.len() as f64) - (n + 1.0) / n
+    }
+
+    /// Estimate the exponent of the Zipf distribution the trace's items were drawn from.
+    ///
+    /// Items are ranked by frequency, most frequent first, and alpha is the negative slope of the
+    /// least-squares line through the points `(ln rank, ln frequency)`. Items which never appear
+    /// can't be ranked, so short traces over many items underestimate the tail. The slope isn't
+    /// defined with fewer than two distinct items, so then the estimate is NaN.
+    ///
+    /// ```
+    /// use cache_sim::generate::zipf_trace;
+    /// use cache_sim::Trace;
+    ///
+    /// let alpha = zipf_trace(100, 1.0, 100_000, 0).estimate_zipf_alpha();
+    /// assert!((alpha - 1.0).abs() < 0.05);
+    ///
+    /// assert!(Trace::from(vec![0, 0]).estimate_zipf_alpha().is_nan());
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // there are much fewer than 2^52 distinct items
+    pub fn estimate_zipf_alpha(&self) -> f64 {
+        let mut frequencies: Vec<u32> = self
+            .frequency_histogram(&NoCondition)
+            .into_values()
+            .collect();
+        if frequencies.len() < 2 {
+            return f64::NAN;
+        }
+        frequencies.sort_unstable_by(|a, b| b.cmp(a));
+
+        let points: Vec<(f64, f64)> = frequencies
+            .into_iter()
+            .enumerate()
+            .map(|(rank, freq)| (((rank + 1) as f64).ln(), f64::from(freq).ln()))
+            .collect();
+
+        let n = points.len() as f64;
+        let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+        let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+        let covariance: f64 = points
+            .iter()
+            .map(|(x, y)| (x - mean_x) * (y - mean_y))
+            .sum();
+        let variance: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+
+        -covariance / variance
+    }
+
+    /// Count the costs of references which end in an eviction, and of those which survive, when
+    /// the trace is run through a cache with the given capacity and policy.
+    ///
+    /// A reference survives if its item is still cached at the item's next reference, or at the
+    /// end of the trace; otherwise it was evicted, or bypassed the cache, in between. The first
+    /// histogram counts the evicted references of each cost and the second the surviving ones, so
+    /// together they count every reference once. Cost-aware policies should evict expensive items
+    /// less often.
+    ///
+    /// ```
+    /// use cache_sim::item::Cost;
+    /// use cache_sim::{GeneralModelGenerator, Landlord, Trace};
+    ///
+    /// let mut g = GeneralModelGenerator::new();
+    /// let (cheap, expensive, other) = (g.item(1.0, 1), g.item(5.0, 1), g.item(1.0, 1));
+    /// let trace = Trace::from(vec![cheap, expensive, other, cheap, expensive]);
+    ///
+    /// let (evicted, surviving) = trace.eviction_cost_histogram(2, <Landlord>::default());
+    ///
+    /// // other evicts cheap, and cheap evicts other, but expensive stays put
+    /// assert_eq!(evicted.get(&Cost(1.0)), Some(&2));
+    /// assert_eq!(evicted.get(&Cost(5.0)), None);
+    /// assert_eq!(surviving[&Cost(5.0)], 2);
+    /// assert_eq!(surviving[&Cost(1.0)], 1);
+    /// ```
+    pub fn eviction_cost_histogram(
+        &self,
+        capacity: u32,
+        policy: impl ReplacementPolicy<I>,
+    ) -> (HashMap<Cost, usize>, HashMap<Cost, usize>) {
+        let mut cache = Cache::<_, ReferenceFates<I>, I>::with_replacement_policy(policy, capacity);
+        cache.run_trace(self);
+
+        let (mut evicted, mut surviving) =
+            (cache.stat().evicted.clone(), cache.stat().surviving.clone());
+        // the last reference hasn't been checked for a bypass yet, and everything left survived
+        if let Some(last) = cache.stat().last {
+            if !cache.contains(&last) {
+                *evicted.entry(Cost(last.cost())).or_insert(0) += 1;
+            }
+        }
+        for item in &cache {
+            *surviving.entry(Cost(item.cost())).or_insert(0) += 1;
+        }
+
+        (evicted, surviving)
+    }
+
+    /// Calculate the entropy of the items weighted by cost, rather than by frequency alone.
+    ///
+    /// Each item's probability is proportional to its frequency times its cost, so this measures
+    /// how concentrated the total miss cost the trace could incur is. For unit-cost items it's the
+    /// same as the entropy of the frequency histogram. Traces whose costs are all zero have zero
+    /// cost-weighted entropy.
+    ///
+    /// ```
+    /// use cache_sim::{GeneralModelGenerator, Trace};
+    ///
+    /// let mut g = GeneralModelGenerator::new();
+    /// let (a, b) = (g.item(1.0, 1), g.item(3.0, 1));
+    ///
+    /// // three quarters of the cost is b's
+    /// let entropy = Trace::from(vec![a, b]).cost_weighted_entropy();
+    /// assert!((entropy - 0.811_278).abs() < 1e-6);
+    /// ```
+    #[must_use]
+    pub fn cost_weighted_entropy(&self) -> f64 {
+        let weights: Vec<f64> = self
+            .frequency_histogram(&NoCondition)
+            .into_iter()
+            .map(|(item, freq)| f64::from(freq) * item.cost())
+            .filter(|&weight| weight > 0.0)
+            .collect();
+
+        let total: f64 = weights.iter().sum();
+        -weights
+            .iter()
+            .map(|weight| (weight / total) * (weight / total).log2())
+            .sum::<f64>()
+    }
+
+    /// Calculate the entropy of an access conditioned on the access `lag` steps earlier.
+    ///
+    /// This is `H(X_t | X_{t - lag})`, computed from the joint distribution of every pair of
+    /// accesses `lag` steps apart, as `H(X_{t - lag}, X_t) - H(X_{t - lag})`. It's zero when each
+    /// access determines the one `lag` steps later, and at most the entropy of the frequency
+    /// histogram. Traces with no such pairs have zero conditional entropy.
+    ///
+    /// ```
+    /// use cache_sim::Trace;
+    ///
+    /// let trace = Trace::from(vec![0, 1, 0, 2, 0, 1, 0, 2]);
+    ///
+    /// // 1 and 2 are always followed by 0, but 0 is followed by 1 or 2 equally often
+    /// assert!((trace.conditional_entropy(1) - 4.0 / 7.0).abs() < 1e-10);
+    /// assert!(trace.conditional_entropy(4).abs() < 1e-10);
+    /// ```
+    #[must_use]
+    pub fn conditional_entropy(&self, lag: usize) -> f64 {
+        let mut joint = HashMap::new();
+        let mut previous = HashMap::new();
+
+        for (prev, curr) in self.iter().zip(self.iter().skip(lag)) {
+            *joint.entry((prev, curr)).or_insert(0) += 1;
+            *previous.entry(prev).or_insert(0) += 1;
+        }
+
+        if joint.is_empty() {
+            return 0.0;
+        }
+
+        // floating point error can take this slightly below zero
+        (entropy(&joint) - entropy(&previous)).max(0.0)
+    }
+
+    /// Estimate the entropy rate from the entropy of blocks of `block_len` consecutive accesses.
+    ///
+    /// This is the entropy of the distribution of every length-`block_len` window of the trace,
+    /// divided by `block_len`. For a stationary source it converges to the entropy rate as the
+    /// blocks grow, though long blocks need long traces to be sampled well. Traces shorter than
+    /// `block_len` have zero block entropy.
+    ///
+    /// ```
+    /// use cache_sim::Trace;
+    ///
+    /// let trace = Trace::from(vec![0, 1, 0, 1, 0, 1, 0, 1]);
+    ///
+    /// // two equally likely items, but each block of 4 is one of only two alternatives
+    /// assert!((trace.block_entropy(1) - 1.0).abs() < 1e-10);
+    /// assert!((trace.block_entropy(4) - 0.25).abs() < 0.01);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `block_len` is zero.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // block lengths are much shorter than 2^52
+    pub fn block_entropy(&self, block_len: usize) -> f64 {
+        assert!(block_len > 0, "The block length must be positive.");
+
+        let mut blocks = HashMap::new();
+        for block in self.inner.windows(block_len) {
+            *blocks.entry(block).or_insert(0) += 1;
+        }
+
+        if blocks.is_empty() {
+            return 0.0;
+        }
+
+        entropy(&blocks) / block_len as f64
+    }
+
+    /// Estimate the Markov order of the trace: how many previous accesses it takes to predict the
+    /// next one as well as possible.
+    ///
+    /// The entropy of an access conditioned on the k accesses before it is `H_{k+1} - H_k`, where
+    /// `H_n` is the total entropy of blocks of n accesses, as in [`Trace::block_entropy`].
+    /// Conditioning on more accesses never increases it, and the estimate is the largest order up
+    /// to `max_order` where conditioning on one more access lowered the entropy by at least
+    /// `threshold` bits, or zero if none did. Later orders are checked even after a small gain,
+    /// since some traces only become predictable with more history.
+    ///
+    /// Long blocks are sampled sparsely, which makes their entropy look smaller than it is, so
+    /// the threshold should be large enough to ignore that for the trace's length.
+    ///
+    /// ```
+    /// use cache_sim::Trace;
+    ///
+    /// // each access is determined by the two before it, but not by the one
+    /// let trace: Trace = [0, 0, 1, 1].into_iter().cycle().take(400).collect();
+    /// assert_eq!(trace.estimate_markov_order(4, 0.1), 2);
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // block lengths are much shorter than 2^52
+    pub fn estimate_markov_order(&self, max_order: usize, threshold: f64) -> usize {
+        let joint_entropy = |n: usize| {
+            if n == 0 {
+                0.0
+            } else {
+                n as f64 * self.block_entropy(n)
+            }
+        };
+
+        let conditional: Vec<f64> = (0..=max_order)
+            .map(|k| joint_entropy(k + 1) - joint_entropy(k))
+            .collect();
+
+        (1..=max_order)
+            .rev()
+            .find(|&k| conditional[k - 1] - conditional[k] >= threshold)
+            .unwrap_or(0)
+    }
+
+    /// Write the conditional frequencies for each condition to the output stream.
+    ///
+    /// Writer is a function that can give us a writer; ideally it should return a handle to the
+    /// same underlying output stream each time.
+    ///
+    /// # Errors
+    /// If writing to the csv fails.
+    ///
+    /// TODO: figure out a non-boxed return type
+    pub fn write_conditional_frequencies<W: std::io::Write>(
+        &self,
+        conditions: HashMap<String, Box<dyn Condition<I>>>,
+        writer: impl Fn() -> anyhow::Result<W>,
+    ) -> anyhow::Result<()> {
+        // TODO: update this if we write a more efficient way to get frequencies for different
+        // conditions
+        let items = self.iter().unique().copied().collect::<Vec<_>>();
+		
+		//write header row
+		let mut labels = vec![String::from("Name"),String::from("Entropy")];
+		for item in &items{
+			labels.push(item.to_string());
+		}
+		write_header(&labels,writer()?)?;
+		
+        for (name, condition) in conditions {
+            let histogram = self.frequency_histogram(&condition);
+            histogram_out(&name, entropy(&histogram), &histogram, &items, writer()?)?;
         }
+
         Ok(())
     }
-}
 
-impl<I: Item> Stat<I> for Trace<I> {
-    fn update(&mut self, _: &std::collections::HashSet<I>, next: I, _: &HashSet<I>) {
-        self.inner.push(next);
+    /// Append `other` to the end of the trace.
+    ///
+    /// ```
+    /// use cache_sim::Trace;
+    ///
+    /// let trace = Trace::from(vec![0, 1]).concat(Trace::from(vec![2, 0]));
+    /// assert_eq!(trace, Trace::from(vec![0, 1, 2, 0]));
+    /// ```
+    #[must_use]
+    pub fn concat(mut self, other: Self) -> Self {
+        self.inner.extend(other);
+        self
     }
-}
 
-/// The stack distances of each access in the trace.
-///
-/// Infinities are represented by `None`; finite distances by `Some(n)`.
-///
-/// ```
-/// use cache_sim::Trace;
-///
-/// let distances = Trace::from(vec![0, 0, 1, 0, 3, 0, 1]).stack_distances();
-/// assert_eq!(
-///     distances.inner(),
-///     &[None, Some(0), None, Some(1), None, Some(1), Some(2)]
-/// );
-/// ```
-pub struct StackDistance {
-    inner: Vec<Option<u32>>,
-}
+    /// Merge the trace with `other`, alternating between their accesses, starting with this trace.
+    ///
+    /// Once the shorter trace runs out, the rest of the longer one follows.
+    ///
+    /// ```
+    /// use cache_sim::Trace;
+    ///
+    /// let trace = Trace::from(vec![0, 1, 2, 3]).interleave(Trace::from(vec![10, 11]));
+    /// assert_eq!(trace, Trace::from(vec![0, 10, 1, 11, 2, 3]));
+    /// ```
+    #[must_use]
+    pub fn interleave(self, other: Self) -> Self {
+        self.into_iter().interleave(other).collect()
+    }
 
-impl StackDistance {
-    /// Calculate the stack distance histogram.
+    /// Reverse the order of the accesses.
     ///
-    /// Returns a vector of frequencies of stack distances, plus the count of intinities.
+    /// ```
+    /// use cache_sim::Trace;
+    ///
+    /// assert_eq!(Trace::from(vec![0, 1, 2]).reverse(), Trace::from(vec![2, 1, 0]));
+    /// ```
+    #[must_use]
+    pub fn reverse(&self) -> Self {
+        self.iter().rev().copied().collect()
+    }
+
+    /// Rotate the trace to start `n` accesses later, moving the skipped accesses to the end.
+    ///
+    /// Rotations wrap around, so rotating by the length of the trace leaves it unchanged.
     ///
     /// ```
     /// use cache_sim::Trace;
     ///
-    /// let distances = Trace::from(vec![0, 0, 1, 0, 3, 0, 1]).stack_distances();
-    /// let (distance_hist, infinities) = distances.histogram();
-    /// assert_eq!(distance_hist, vec![1, 2, 1]);
-    /// assert_eq!(infinities, 3);
+    /// let trace = Trace::from(vec![0, 1, 2, 3]);
+    /// assert_eq!(trace.rotate_left(1), Trace::from(vec![1, 2, 3, 0]));
+    /// assert_eq!(trace.rotate_left(6), Trace::from(vec![2, 3, 0, 1]));
     /// ```
-    pub fn histogram(&self) -> (Vec<usize>, usize) {
-        let max = self.inner.iter().flatten().max();
+    #[must_use]
+    pub fn rotate_left(&self, n: usize) -> Self {
+        let mut inner = self.inner.clone();
+        if !self.is_empty() {
+            inner.rotate_left(n % self.len());
+        }
+        Self { inner }
+    }
+
+    /// Create a trace from its run-length encoding, as `(item, count)` pairs, so each item is
+    /// accessed `count` times in a row.
+    ///
+    /// This is the inverse of [`Trace::to_rle`].
+    ///
+    /// ```
+    /// use cache_sim::Trace;
+    ///
+    /// assert_eq!(Trace::from_rle(&[(1, 3), (2, 1)]), Trace::from(vec![1, 1, 1, 2]));
+    /// ```
+    #[must_use]
+    pub fn from_rle(pairs: &[(I, usize)]) -> Self {
+        pairs
+            .iter()
+            .flat_map(|&(item, count)| std::iter::repeat_n(item, count))
+            .collect()
+    }
+
+    /// Get the run-length encoding of the trace, collapsing each run of consecutive accesses to
+    /// the same item into an `(item, count)` pair.
+    ///
+    /// Runs are maximal, so neighbouring pairs always have different items, and no count is zero.
+    ///
+    /// ```
+    /// use cache_sim::Trace;
+    ///
+    /// assert_eq!(Trace::from(vec![1, 1, 1, 2, 1]).to_rle(), vec![(1, 3), (2, 1), (1, 1)]);
+    /// ```
+    #[must_use]
+    pub fn to_rle(&self) -> Vec<(I, usize)> {
+        self.iter()
+            .copied()
+            .dedup_with_count()
+            .map(|(count, item)| (item, count))
+            .collect()
+    }
+
+    /// Split the trace into consecutive segments of `size` accesses, so any per-trace statistic
+    /// can be computed for each segment.
+    ///
+    /// The last segment is shorter if `size` doesn't divide the length of the trace, and an empty
+    /// trace has no segments.
+    ///
+    /// ```
+    /// use cache_sim::Trace;
+    ///
+    /// let trace = Trace::from(vec![0, 1, 0, 2, 2]);
+    /// let chunks: Vec<_> = trace.chunks(2).collect();
+    ///
+    /// assert_eq!(chunks[1], Trace::from(vec![0, 2]));
+    /// assert_eq!(chunks[2], Trace::from(vec![2]));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `size` is zero.
+    pub fn chunks(&self, size: usize) -> impl Iterator<Item = Self> + '_ {
+        assert!(size > 0, "The chunk size must be positive.");
+        self.inner
+            .chunks(size)
+            .map(|chunk| Self::from(chunk.to_vec()))
+    }
+
+    /// Relabel the items with `0..k`, in order of their first access.
+    ///
+    /// Returns the relabeled trace, and the map from each original item to its new label. The
+    /// order of accesses is untouched, so for unit-sized items this preserves all the locality
+    /// structure of the trace, like stack distances; the relabeled items always have unit size.
+    ///
+    /// ```
+    /// use cache_sim::Trace;
+    ///
+    /// let (trace, labels) = Trace::from(vec![7, 3, 7, 9]).relabel();
+    ///
+    /// assert_eq!(trace, Trace::from(vec![0, 1, 0, 2]));
+    /// assert_eq!(labels[&9], 2);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If there are more than `u32::MAX + 1` distinct items.
+    #[must_use]
+    pub fn relabel(&self) -> (Trace<u32>, HashMap<I, u32>) {
+        let mut labels = HashMap::new();
+
+        let trace = self
+            .iter()
+            .map(|item| {
+                let next = u32::try_from(labels.len()).expect("There are at most 2^32 items.");
+                *labels.entry(*item).or_insert(next)
+            })
+            .collect();
+
+        (trace, labels)
+    }
+
+    /// Display the trace like [`Trace::pretty_print`], after relabeling its items with
+    /// [`Trace::relabel`].
+    ///
+    /// This works for any kind of item, and traces with up to 26 distinct items are shown as
+    /// letters however large or sparse the original items are.
+    ///
+    /// ```
+    /// use cache_sim::Trace;
+    ///
+    /// let trace = Trace::from(vec![300, 100, 300, 200]);
+    /// assert_eq!(&trace.pretty_print_relabeled(), "A, B, A, C");
+    /// ```
+    #[must_use]
+    pub fn pretty_print_relabeled(&self) -> String {
+        self.relabel().0.pretty_print()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, I> {
+        self.inner.iter()
+    }
+
+    /// Get a reference to the inner vector of items.
+    #[must_use]
+    pub fn inner(&self) -> &[I] {
+        self.inner.as_ref()
+    }
+
+    /// Take ownership of the inner vector of items.
+    ///
+    /// The ith element of the vector is the ith access of the trace.
+    #[must_use]
+    #[allow(clippy::missing_const_for_fn)] // false positive, destructors can't be const
+    pub fn into_inner(self) -> Vec<I> {
+        self.inner
+    }
+
+    /// Get the length of the trace.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Check whether the trace is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+}
+
+impl<I: Item> IntoIterator for Trace<I> {
+    type Item = I;
+
+    type IntoIter = <Vec<I> as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.into_iter()
+    }
+}
+
+impl<'t, I: Item> IntoIterator for &'t Trace<I> {
+    type Item = &'t I;
+
+    type IntoIter = std::slice::Iter<'t, I>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<I: Item> FromIterator<I> for Trace<I> {
+    fn from_iter<T: IntoIterator<Item = I>>(iter: T) -> Self {
+        Self {
+            inner: Vec::from_iter(iter),
+        }
+    }
+}
+
+impl<I: Item> Extend<I> for Trace<I> {
+    fn extend<T: IntoIterator<Item = I>>(&mut self, iter: T) {
+        self.inner.extend(iter);
+    }
+}
+
+// Allows indexing the trace with any type that could index the underlying vector, e.x. with usizes
+// or `Range`s from the standard library.
+impl<I: Item, Idx> std::ops::Index<Idx> for Trace<I>
+where
+    Idx: std::slice::SliceIndex<[I]>,
+{
+    type Output = Idx::Output;
+
+    fn index(&self, index: Idx) -> &Self::Output {
+        &self.inner[index]
+    }
+}
+
+impl<I: Item + Ord> Trace<I> {
+    /// Summarize the trace, as a quick sanity check of a trace loaded from somewhere else.
+    ///
+    /// ```
+    /// use cache_sim::Trace;
+    ///
+    /// let summary = Trace::from(vec![4, 2, 4, 9, 4]).summary();
+    ///
+    /// assert_eq!(summary.distinct, 3);
+    /// assert_eq!(summary.most_frequent, Some((4, 3)));
+    /// assert_eq!(
+    ///     summary.to_string(),
+    ///     "5 accesses to 3 distinct items, from 2 to 9\n\
+    ///      most frequent item: 4 (3 accesses)\n\
+    ///      first references: 60.00%"
+    /// );
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // traces are much shorter than 2^52 accesses
+    pub fn summary(&self) -> TraceSummary<I> {
+        let frequencies = self.frequency_histogram(&NoCondition);
+        let distinct = frequencies.len();
+
+        TraceSummary {
+            len: self.len(),
+            distinct,
+            min: self.iter().min().copied(),
+            max: self.iter().max().copied(),
+            // ties go to the smallest item, so the summary doesn't depend on hash order
+            most_frequent: frequencies
+                .into_iter()
+                .max_by(|(a, a_count), (b, b_count)| a_count.cmp(b_count).then(b.cmp(a))),
+            first_reference_fraction: if self.is_empty() {
+                0.0
+            } else {
+                distinct as f64 / self.len() as f64
+            },
+        }
+    }
+}
+
+impl Trace<u32> {
+    /// If the elements in the trace are all smaller than 26, display them as letters instead.
+    ///
+    /// ```
+    /// # use cache_sim::Trace;
+    /// let trace = Trace::from(vec![0, 0, 2, 3, 1, 14]);
+    /// assert_eq!(&trace.pretty_print(), "A, A, C, D, B, O");
+    /// ```
+    ///
+    /// Note that this doesn't work for higher values of the item:
+    /// ```
+    /// # use cache_sim::Trace;
+    /// let trace = Trace::from(vec![1, 2, 26]);
+    /// assert_eq!(&trace.pretty_print(), "1, 2, 26");
+    /// ```
+    #[must_use]
+    #[allow(unstable_name_collisions)] // needed here, the stdlib method will do the same as the
+                                       // itertools one when it's stabilized
+    pub fn pretty_print(&self) -> String {
+        if *self.inner.iter().max().unwrap_or(&0) < 26 {
+            self.inner
+                .iter()
+                .map(|i| {
+                    // treat the number as an ascii value; adding the ascii value of A so we get
+                    // capital letters
+                    char::from_u32(i + 'A' as u32)
+                        .expect("all elements of list are valid chars")
+                        .to_string()
+                })
+                .intersperse(", ".to_string())
+                .collect()
+        } else {
+            self.inner
+                .iter()
+                .map(u32::to_string)
+                .intersperse(", ".to_string())
+                .collect()
+        }
+    }
+}
+
+impl<I: Item> Display for Trace<I> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for i in &self.inner {
+            write!(f, "{} ", i)?;
+        }
+        Ok(())
+    }
+}
+
+/// A summary of a trace, from [`Trace::summary`].
+///
+/// Its [`Display`] implementation gives a short human-readable report.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceSummary<I: Item> {
+    /// The number of accesses.
+    pub len: usize,
+    /// The number of distinct items.
+    pub distinct: usize,
+    /// The smallest item, or `None` if the trace is empty.
+    pub min: Option<I>,
+    /// The largest item, or `None` if the trace is empty.
+    pub max: Option<I>,
+    /// The most frequently accessed item and its frequency, or `None` if the trace is empty.
+    pub most_frequent: Option<(I, u32)>,
+    /// The fraction of accesses which are the first reference to their item.
+    pub first_reference_fraction: f64,
+}
+
+impl<I: Item> Display for TraceSummary<I> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} accesses to {} distinct items",
+            self.len, self.distinct
+        )?;
+        if let (Some(min), Some(max)) = (self.min, self.max) {
+            write!(f, ", from {min} to {max}")?;
+        }
+        if let Some((item, frequency)) = self.most_frequent {
+            write!(f, "\nmost frequent item: {item} ({frequency} accesses)")?;
+        }
+        write!(
+            f,
+            "\nfirst references: {:.2}%",
+            self.first_reference_fraction * 100.0
+        )
+    }
+}
+
+impl<I: Item, H: BuildHasher> Stat<I, H> for Trace<I> {
+    fn update(&mut self, _: &HashSet<I, H>, next: I, _: &HashSet<I>) {
+        self.inner.push(next);
+    }
+}
+
+/// Counts how references end, for [`Trace::eviction_cost_histogram`].
+struct ReferenceFates<I: Item> {
+    evicted: HashMap<Cost, usize>,
+    surviving: HashMap<Cost, usize>,
+    last: Option<I>,
+}
+
+impl<I: Item> Default for ReferenceFates<I> {
+    fn default() -> Self {
+        Self {
+            evicted: HashMap::new(),
+            surviving: HashMap::new(),
+            last: None,
+        }
+    }
+}
+
+impl<I: Item> Stat<I> for ReferenceFates<I> {
+    fn update(&mut self, set: &HashSet<I>, next: I, to_be_evicted: &HashSet<I>) {
+        // the previous access was admitted, unless it bypassed the cache
+        if let Some(last) = self.last.replace(next) {
+            if !set.contains(&last) {
+                *self.evicted.entry(Cost(last.cost())).or_insert(0) += 1;
+            }
+        }
+
+        if set.contains(&next) {
+            *self.surviving.entry(Cost(next.cost())).or_insert(0) += 1;
+        }
+        for evicted in to_be_evicted {
+            *self.evicted.entry(Cost(evicted.cost())).or_insert(0) += 1;
+        }
+    }
+}
+
+/// A Fenwick tree, for prefix sums in `O(log n)` time.
+struct FenwickTree {
+    tree: Vec<u32>,
+}
+
+impl FenwickTree {
+    fn new(len: usize) -> Self {
+        Self {
+            tree: vec![0; len + 1],
+        }
+    }
+
+    /// Add `value` at `index`.
+    fn add(&mut self, index: usize, value: u32) {
+        let mut i = index + 1;
+        while i < self.tree.len() {
+            self.tree[i] += value;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    /// Subtract `value` at `index`, which must have had at least `value` added to it.
+    fn sub(&mut self, index: usize, value: u32) {
+        let mut i = index + 1;
+        while i < self.tree.len() {
+            self.tree[i] -= value;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    /// Get the sum of the values before `index`.
+    fn prefix_sum(&self, index: usize) -> u32 {
+        let mut sum = 0;
+        let mut i = index;
+        while i > 0 {
+            sum += self.tree[i];
+            i -= i & i.wrapping_neg();
+        }
+        sum
+    }
+}
+
+/// The stack distances of each access in the trace.
+///
+/// Infinities are represented by `None`; finite distances by `Some(n)`.
+///
+/// ```
+/// use cache_sim::Trace;
+///
+/// let distances = Trace::from(vec![0, 0, 1, 0, 3, 0, 1]).stack_distances();
+/// assert_eq!(
+///     distances.inner(),
+///     &[None, Some(0), None, Some(1), None, Some(1), Some(2)]
+/// );
+/// ```
+///
+/// With the `serde` feature, stack distances serialize as a sequence, with infinities as nulls.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct StackDistance {
+    inner: Vec<Option<u32>>,
+}
+
+impl StackDistance {
+    /// Calculate the stack distance histogram.
+    ///
+    /// Returns a vector of frequencies of stack distances, plus the count of intinities.
+    ///
+    /// ```
+    /// use cache_sim::Trace;
+    ///
+    /// let distances = Trace::from(vec![0, 0, 1, 0, 3, 0, 1]).stack_distances();
+    /// let (distance_hist, infinities) = distances.histogram();
+    /// assert_eq!(distance_hist, vec![1, 2, 1]);
+    /// assert_eq!(infinities, 3);
+    /// ```
+    pub fn histogram(&self) -> (Vec<usize>, usize) {
+        let max = self.inner.iter().flatten().max();
+
+        let mut freqs = max.map_or_else(Vec::new, |max| vec![0; *max as usize + 1]);
+
+        let mut infinities = 0;
+
+        for &i in &self.inner {
+            #[allow(clippy::option_if_let_else)]
+            if let Some(i) = i {
+                freqs[i as usize] += 1;
+            } else {
+                infinities += 1;
+            }
+        }
+
+        (freqs, infinities)
+    }
+
+    /// Calculate the stack distance histogram in parallel.
+    ///
+    /// This gives exactly the same result as [`StackDistance::histogram`].
+    ///
+    /// ```
+    /// use cache_sim::Trace;
+    ///
+    /// let distances = Trace::from(vec![0, 0, 1, 0, 3, 0, 1]).stack_distances();
+    /// assert_eq!(distances.histogram_parallel(), distances.histogram());
+    /// ```
+    #[cfg(feature = "rayon")]
+    #[must_use]
+    pub fn histogram_parallel(&self) -> (Vec<usize>, usize) {
+        use rayon::prelude::*;
+
+        self.inner
+            .par_iter()
+            .fold(
+                || (Vec::new(), 0),
+                |(mut freqs, mut infinities), &i| {
+                    if let Some(i) = i {
+                        let i = i as usize;
+                        if freqs.len() <= i {
+                            freqs.resize(i + 1, 0);
+                        }
+                        freqs[i] += 1;
+                    } else {
+                        infinities += 1;
+                    }
+                    (freqs, infinities)
+                },
+            )
+            .reduce(
+                || (Vec::new(), 0),
+                |(mut freqs, infinities), (other, other_infinities)| {
+                    // each partial histogram is only as long as its own maximum distance
+                    if freqs.len() < other.len() {
+                        freqs.resize(other.len(), 0);
+                    }
+                    for (freq, other) in freqs.iter_mut().zip(other) {
+                        *freq += other;
+                    }
+                    (freqs, infinities + other_infinities)
+                },
+            )
+    }
+
+    /// Calculate the miss ratio curve of a fully-associative LRU cache.
+    ///
+    /// The cth element is the miss ratio of a cache with capacity c: the fraction of accesses
+    /// whose stack distance is at least c, plus the infinities, which always miss. The curve is
+    /// non-increasing, and its last element is the miss ratio of a cache big enough to hold every
+    /// item. It's empty if the trace is.
+    ///
+    /// ```
+    /// use cache_sim::Trace;
+    ///
+    /// let distances = Trace::from(vec![0, 0, 1, 0, 3, 0, 1]).stack_distances();
+    /// assert_eq!(
+    ///     distances.miss_ratio_curve(),
+    ///     vec![7.0 / 7.0, 6.0 / 7.0, 4.0 / 7.0, 3.0 / 7.0]
+    /// );
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // traces are much shorter than 2^52 accesses
+    pub fn miss_ratio_curve(&self) -> Vec<f64> {
+        if self.inner.is_empty() {
+            return Vec::new();
+        }
+
+        let (freqs, infinities) = self.histogram();
+
+        let total = self.inner.len() as f64;
+
+        // walk down from the largest capacity, where only the infinities miss
+        let mut misses = infinities;
+        let mut curve = vec![0.0; freqs.len() + 1];
+        for c in (0..curve.len()).rev() {
+            if c < freqs.len() {
+                misses += freqs[c];
+            }
+            curve[c] = misses as f64 / total;
+        }
+
+        curve
+    }
+
+    /// Calculate the cumulative distribution of the finite stack distances.
+    ///
+    /// The dth element is the fraction of accesses with finite stack distances whose distance is at
+    /// most d, so the last element is always 1. Since it's normalized, traces of different lengths
+    /// can be compared directly. The infinities aren't included; see
+    /// [`StackDistance::infinity_fraction`]. It's empty if there are no finite distances.
+    ///
+    /// ```
+    /// use cache_sim::Trace;
+    ///
+    /// let distances = Trace::from(vec![0, 0, 1, 0, 3, 0, 1]).stack_distances();
+    /// assert_eq!(distances.cdf(), vec![0.25, 0.75, 1.0]);
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // traces are much shorter than 2^52 accesses
+    pub fn cdf(&self) -> Vec<f64> {
+        let (freqs, infinities) = self.histogram();
+        let finite = (self.inner.len() - infinities) as f64;
+
+        freqs
+            .into_iter()
+            .scan(0, |cumulative, freq| {
+                *cumulative += freq;
+                Some(*cumulative as f64 / finite)
+            })
+            .collect()
+    }
+
+    /// Calculate the fraction of accesses with infinite stack distances, i.e. first accesses.
+    ///
+    /// This is zero if there are no accesses.
+    ///
+    /// ```
+    /// use cache_sim::Trace;
+    ///
+    /// let distances = Trace::from(vec![0, 0, 1, 0, 3, 0, 1]).stack_distances();
+    /// assert_eq!(distances.infinity_fraction(), 3.0 / 7.0);
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // traces are much shorter than 2^52 accesses
+    pub fn infinity_fraction(&self) -> f64 {
+        if self.inner.is_empty() {
+            0.0
+        } else {
+            self.inner.iter().filter(|d| d.is_none()).count() as f64 / self.inner.len() as f64
+        }
+    }
+
+    /// Get a reference to the inner vector of distances.
+    ///
+    /// The ith element of the vector is the ith access of the trace.
+    #[must_use]
+    pub fn inner(&self) -> &[Option<u32>] {
+        self.inner.as_ref()
+    }
+
+    /// Take ownership of the inner vector of distances.
+    ///
+    /// The ith element of the vector is the ith access of the trace.
+    #[must_use]
+    #[allow(clippy::missing_const_for_fn)] // false positive, destructors can't be const
+    pub fn into_inner(self) -> Vec<Option<u32>> {
+        self.inner
+    }
+}
+
+/// The LRU stack of a trace being built online, for computing stack distances one access at a
+/// time.
+///
+/// Pushing each access of a trace in turn gives exactly the distances of
+/// [`Trace::stack_distances`], without recomputing them from scratch after every append.
+///
+/// ```
+/// use cache_sim::trace::StackDistanceState;
+///
+/// let mut state = StackDistanceState::default();
+/// let distances = [0, 0, 1, 0, 3, 0, 1].map(|i| state.push(i));
+/// assert_eq!(distances, [None, Some(0), None, Some(1), None, Some(1), Some(2)]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct StackDistanceState<I: Item = u32> {
+    stack: Vec<I>,
+}
+
+impl<I: Item> Default for StackDistanceState<I> {
+    fn default() -> Self {
+        Self { stack: Vec::new() }
+    }
+}
+
+impl<I: Item> StackDistanceState<I> {
+    /// Access `item`, returning its stack distance, or `None` if it's never been seen.
+    ///
+    /// Like [`Trace::stack_distances`], the distance is the sum of the sizes of the items accessed
+    /// more recently than the last access to `item`.
+    pub fn push(&mut self, item: I) -> Option<usize> {
+        let distance = self.stack.iter().position(|i| i == &item).map(|position| {
+            let distance = self.stack[position + 1..]
+                .iter()
+                .map(|i| i.size() as usize)
+                .sum();
+            self.stack.remove(position);
+            distance
+        });
+        self.stack.push(item);
+        distance
+    }
+
+    /// Get the number of distinct items seen so far.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Check whether no items have been seen.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.stack.is_empty()
+    }
+}
+
+/// The reuse distances of each access in the trace.
+///
+/// The reuse distance of an access is the number of distinct items accessed since the previous
+/// reference to the same item; first references are represented by `None`. Unlike
+/// [`StackDistance`], which sums the sizes of the intervening items, this counts each item once
+/// regardless of its size, so the two agree exactly when every item has unit size:
+///
+/// ```
+/// use cache_sim::{GeneralModelGenerator, Trace};
+///
+/// let mut g = GeneralModelGenerator::new();
+/// let a = g.item(1.0, 1);
+/// let b = g.item(1.0, 4);
+///
+/// let trace = Trace::from(vec![a, a, b, a]);
+/// assert_eq!(trace.reuse_distances().inner(), &[None, Some(0), None, Some(1)]);
+/// assert_eq!(trace.stack_distances().inner(), &[None, Some(0), None, Some(4)]);
+/// ```
+pub struct ReuseDistance {
+    inner: Vec<Option<usize>>,
+}
+
+impl ReuseDistance {
+    /// Get a reference to the inner vector of distances.
+    ///
+    /// The ith element of the vector is the ith access of the trace.
+    #[must_use]
+    pub fn inner(&self) -> &[Option<usize>] {
+        self.inner.as_ref()
+    }
+
+    /// Take ownership of the inner vector of distances.
+    ///
+    /// The ith element of the vector is the ith access of the trace.
+    #[must_use]
+    #[allow(clippy::missing_const_for_fn)] // false positive, destructors can't be const
+    pub fn into_inner(self) -> Vec<Option<usize>> {
+        self.inner
+    }
+}
+
+/// Returns the entropy of a given distribution.
+#[must_use]
+pub fn entropy<K, H: std::hash::BuildHasher>(histogram: &HashMap<K, u32, H>) -> f64 {
+    let total = f64::from(histogram.values().sum::<u32>());
+    -histogram
+        .values()
+        .map(|&i| (f64::from(i) / total) * ((f64::from(i) / total).log2()))
+        .sum::<f64>()
+}
+
+/// An online estimate of the entropy of a stream of accesses, for when the stream is too long to
+/// keep as a [`Trace`].
+///
+/// Only the count of each item is kept, and the entropy of the counts is the same as
+/// [`entropy`] of the frequency histogram of the accesses so far. It's updated with each
+/// access, so getting the estimate takes constant time.
+///
+/// ```
+/// use cache_sim::trace::EntropyEstimator;
+///
+/// let mut estimator = EntropyEstimator::default();
+/// for i in [0, 1, 1, 0, 1, 0] {
+///     estimator.observe(i);
+/// }
+///
+/// assert!((estimator.estimate() - 1.0).abs() < 1e-10);
+/// ```
+#[derive(Debug, Clone)]
+pub struct EntropyEstimator<I: Item = u32> {
+    counts: HashMap<I, u64>,
+    total: u64,
+    /// The sum of `c log2 c` over the counts.
+    weighted_logs: f64,
+}
+
+impl<I: Item> Default for EntropyEstimator<I> {
+    fn default() -> Self {
+        Self {
+            counts: HashMap::new(),
+            total: 0,
+            weighted_logs: 0.0,
+        }
+    }
+}
+
+impl<I: Item> EntropyEstimator<I> {
+    /// Record an access to `item`.
+    #[allow(clippy::cast_precision_loss)] // streams are much shorter than 2^52 accesses
+    pub fn observe(&mut self, item: I) {
+        let count = self.counts.entry(item).or_insert(0);
+        let old = *count as f64;
+        *count += 1;
+        let new = *count as f64;
+
+        // 0 log 0 is 0
+        let old_term = if *count == 1 { 0.0 } else { old * old.log2() };
+        self.weighted_logs += new * new.log2() - old_term;
+        self.total += 1;
+    }
+
+    /// Get the entropy of the accesses so far, or zero if there haven't been any.
+    ///
+    /// With N accesses and counts c, this is `log2 N - sum(c log2 c) / N`.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // streams are much shorter than 2^52 accesses
+    pub fn estimate(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+
+        let total = self.total as f64;
+        // floating point error can take this slightly below zero
+        (total.log2() - self.weighted_logs / total).max(0.0)
+    }
+
+    /// Get the number of accesses so far.
+    #[must_use]
+    pub const fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Get the count of each item so far.
+    #[must_use]
+    pub const fn counts(&self) -> &HashMap<I, u64> {
+        &self.counts
+    }
+}
+
+/// Returns the Kullback-Leibler divergence `D_KL(P_a || P_b)` of the frequency distributions of two
+/// traces, in bits.
+///
+/// The distributions are the frequency histograms of the traces, normalized to probabilities. An
+/// item of `a` which never appears in `b` would make the divergence infinite, so both use additive
+/// smoothing: each item in either trace gets `smoothing` extra (pseudo-)accesses before
+/// normalizing. With zero smoothing, the divergence is infinite if any item of `a` is missing from
+/// `b`. Either way, a trace's divergence from itself is zero.
+///
+/// An empty trace has no distribution without smoothing. In that case, the divergence of an empty
+/// `a` is zero, like that of any trace from itself, and the divergence from an empty `b` is
+/// infinite, since `b` leaves out every item of `a`.
+///
+/// ```
+/// use cache_sim::trace::frequency_kl_divergence;
+/// use cache_sim::Trace;
+///
+/// let a = Trace::from(vec![0, 0, 1, 1]);
+/// let b = Trace::from(vec![0, 0, 0, 1]);
+///
+/// assert_eq!(frequency_kl_divergence(&a, &a, 0.0), 0.0);
+/// assert!(frequency_kl_divergence(&a, &b, 0.0) > 0.0);
+/// assert!(frequency_kl_divergence(&a, &Trace::from(vec![0]), 0.0).is_infinite());
+/// ```
+#[must_use]
+#[allow(clippy::cast_precision_loss)] // traces are much shorter than 2^52 accesses
+pub fn frequency_kl_divergence<I: Item>(a: &Trace<I>, b: &Trace<I>, smoothing: f64) -> f64 {
+    if smoothing == 0.0 {
+        if a.is_empty() {
+            return 0.0;
+        }
+        if b.is_empty() {
+            return f64::INFINITY;
+        }
+    }
+
+    let p = a.frequency_histogram(&NoCondition);
+    let q = b.frequency_histogram(&NoCondition);
+
+    let items = p.keys().chain(q.keys()).collect::<HashSet<_>>();
+    let probability = |histogram: &HashMap<I, u32>, len: usize, item| {
+        let total = smoothing.mul_add(items.len() as f64, len as f64);
+        (f64::from(histogram.get(item).copied().unwrap_or(0)) + smoothing) / total
+    };
+
+    items
+        .iter()
+        .map(|item| {
+            let p_item = probability(&p, a.len(), item);
+            if p_item == 0.0 {
+                0.0
+            } else {
+                p_item * (p_item / probability(&q, b.len(), item)).log2()
+            }
+        })
+        .sum()
+}
+
+/// Count the positions where two traces of the same length have different accesses.
+///
+/// Returns `None` if the lengths differ; see [`trace_lcs_length`] for a comparison which doesn't
+/// need them to match.
+///
+/// ```
+/// use cache_sim::trace::trace_hamming_distance;
+/// use cache_sim::Trace;
+///
+/// let a = Trace::from(vec![0, 1, 2, 3]);
+///
+/// assert_eq!(trace_hamming_distance(&a, &Trace::from(vec![0, 1, 5, 4])), Some(2));
+/// assert_eq!(trace_hamming_distance(&a, &Trace::from(vec![0, 1])), None);
+/// ```
+#[must_use]
+pub fn trace_hamming_distance<I: Item>(a: &Trace<I>, b: &Trace<I>) -> Option<usize> {
+    (a.len() == b.len()).then(|| a.iter().zip(b).filter(|(x, y)| x != y).count())
+}
+
+/// Get the length of the longest common subsequence of two traces.
+///
+/// A common subsequence is a sequence of accesses which appears in both traces in the same order,
+/// though not necessarily contiguously, so this tolerates insertions and deletions that would
+/// throw off [`trace_hamming_distance`]. It takes `O(len(a) * len(b))` time.
+///
+/// ```
+/// use cache_sim::trace::trace_lcs_length;
+/// use cache_sim::Trace;
+///
+/// let a = Trace::from(vec![0, 1, 2, 3, 4]);
+/// let b = Trace::from(vec![1, 2, 5, 4]);
+///
+/// assert_eq!(trace_lcs_length(&a, &b), 3);
+/// ```
+#[must_use]
+pub fn trace_lcs_length<I: Item>(a: &Trace<I>, b: &Trace<I>) -> usize {
+    // only the previous row of the table is needed
+    let mut previous = vec![0; b.len() + 1];
+    let mut current = vec![0; b.len() + 1];
+
+    for x in a {
+        for (j, y) in b.iter().enumerate() {
+            current[j + 1] = if x == y {
+                previous[j] + 1
+            } else {
+                previous[j + 1].max(current[j])
+            };
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod stack_distance {
+        use super::*;
+
+        macro_rules! stack_distance_test {
+            ($name:ident: $($in:expr),* => $($out:expr),*) => {
+                #[test]
+                fn $name() {
+                    assert_eq!(Trace::from(vec![$($in),*]).stack_distances().inner(), vec![$($out),*])
+                }
+            };
+        }
+
+        stack_distance_test!(basic: 1, 2, 3 => None, None, None);
+        stack_distance_test!(repeated: 1, 1, 1 => None, Some(0), Some(0));
+        stack_distance_test!(one_two: 1, 2, 1, 1, 1 => None, None, Some(1), Some(0), Some(0));
+        stack_distance_test!(one_repeated: 1, 2, 3, 1 => None, None, None, Some(2));
+        // stack_distance_test!(empty: => );
+
+        #[test]
+        fn with_sizes() {
+            use crate::item::GeneralModelGenerator;
+
+            let mut g = GeneralModelGenerator::new();
+
+            let a = g.item(1.0, 2);
+            let b = g.item(1.0, 4);
+            let c = g.item(1.0, 3);
+
+            let trace = Trace::from(vec![a, b, c, a]);
+            assert_eq!(
+                trace.stack_distances().inner(),
+                vec![None, None, None, Some(7)]
+            );
+        }
+    }
+
+    mod stack_distance_state {
+        use super::*;
+        use crate::generate::zipf_trace;
+        use crate::item::GeneralModelGenerator;
+
+        fn assert_matches_batch<I: Item>(trace: &Trace<I>) {
+            let mut state = StackDistanceState::default();
+            let incremental = trace.iter().map(|i| state.push(*i)).collect::<Vec<_>>();
+            let batch = trace
+                .stack_distances()
+                .inner()
+                .iter()
+                .map(|d| d.map(|d| d as usize))
+                .collect::<Vec<_>>();
+
+            assert_eq!(incremental, batch);
+            assert_eq!(state.len(), trace.iter().unique().count());
+        }
+
+        #[test]
+        fn matches_batch() {
+            assert_matches_batch(&Trace::from(vec![1, 2, 1, 1, 3, 2, 1]));
+            assert_matches_batch(&zipf_trace(50, 0.8, 2000, 3));
+            assert_matches_batch(&Trace::<u32>::default());
+        }
+
+        #[test]
+        fn with_sizes() {
+            let mut g = GeneralModelGenerator::new();
+            let items = [g.item(1.0, 2), g.item(1.0, 4), g.item(1.0, 3)];
+
+            assert_matches_batch(&Trace::from(
+                [0, 1, 2, 0, 2, 1, 1, 0].map(|i| items[i]).to_vec(),
+            ));
+        }
+    }
+
+    mod stack_distance_fast {
+        use super::*;
+        use crate::item::GeneralModelGenerator;
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        #[test]
+        fn matches_naive() {
+            let mut rng = StdRng::seed_from_u64(0);
+
+            for len in [0, 1, 10, 100, 1000] {
+                for items in [1, 5, 50] {
+                    let trace: Trace = (0..len).map(|_| rng.gen_range(0..items)).collect();
+                    assert_eq!(
+                        trace.stack_distances_fast().inner(),
+                        trace.stack_distances().inner()
+                    );
+                }
+            }
+        }
+
+        #[test]
+        fn matches_naive_with_sizes() {
+            let mut rng = StdRng::seed_from_u64(0);
+            let mut g = GeneralModelGenerator::new();
+            let items = (0..20)
+                .map(|_| g.item(1.0, rng.gen_range(1..10)))
+                .collect::<Vec<_>>();
+
+            let trace: Trace<_> = (0..1000)
+                .map(|_| items[rng.gen_range(0..items.len())])
+                .collect();
+            assert_eq!(
+                trace.stack_distances_fast().inner(),
+                trace.stack_distances().inner()
+            );
+        }
+    }
+
+    mod shards {
+        use super::*;
+        use crate::generate::zipf_trace;
+
+        #[test]
+        fn tracks_exact_miss_ratio_curve() {
+            for seed in 0..8 {
+                let trace = zipf_trace(10_000, 0.0, 100_000, seed);
+
+                let exact = trace.stack_distances_fast().miss_ratio_curve();
+                let approximate = trace.stack_distances_shards(0.1).miss_ratio_curve();
+
+                // the curves have different lengths, but both are flat past their ends
+                let at = |curve: &[f64], size: usize| curve[size.min(curve.len() - 1)];
+                for size in 0..=10_000 {
+                    let error = (at(&exact, size) - at(&approximate, size)).abs();
+                    assert!(error < 0.05, "seed {seed}, size {size}: error {error}");
+                }
+            }
+        }
+
+        #[test]
+        fn full_rate_is_exact() {
+            let trace = zipf_trace(100, 1.0, 1000, 1);
+            assert_eq!(
+                trace.stack_distances_shards(1.0).inner(),
+                trace.stack_distances().inner()
+            );
+        }
+
+        #[test]
+        #[should_panic]
+        fn zero_rate() {
+            let _ = Trace::from(vec![0, 1, 2]).stack_distances_shards(0.0);
+        }
+    }
+
+    mod windowed_stack_distance {
+        use super::*;
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        #[test]
+        fn locality_shift() {
+            // a tight loop over a few items, then uniform accesses over many
+            let mut rng = StdRng::seed_from_u64(0);
+            let trace: Trace = (0..1000)
+                .map(|i| i % 4)
+                .chain((0..1000).map(|_| rng.gen_range(100..300)))
+                .collect();
+
+            let stats = trace.windowed_stack_distance_stats(500);
+            assert_eq!(stats.len(), 4);
+
+            assert!((stats[0].0 - 3.0).abs() < 1e-10);
+            assert!((stats[1].0 - 3.0).abs() < 1e-10);
+            assert!(stats[2].0 > 10.0 * stats[1].0);
+            assert!(stats[3].0 > stats[1].0);
+
+            assert_eq!(stats[0].1, 4);
+            assert_eq!(stats[1].1, 0);
+            assert!(stats[2].1 > stats[3].1);
+        }
+
+        #[test]
+        fn partial_last_window() {
+            let stats = Trace::from(vec![0, 1, 0, 1, 2]).windowed_stack_distance_stats(2);
+            assert_eq!(stats, vec![(0.0, 2), (1.0, 0), (0.0, 1)]);
+        }
+
+        #[test]
+        fn matches_histogram_totals() {
+            let trace = Trace::from(vec![0, 0, 1, 0, 3, 0, 1]);
+            let (_, infinities) = trace.stack_distances().histogram();
+            let windowed: usize = trace
+                .windowed_stack_distance_stats(3)
+                .iter()
+                .map(|(_, n)| n)
+                .sum();
+            assert_eq!(windowed, infinities);
+        }
+
+        #[test]
+        fn empty() {
+            assert!(Trace::<u32>::from(vec![])
+                .windowed_stack_distance_stats(3)
+                .is_empty());
+        }
+
+        #[test]
+        #[should_panic]
+        fn zero_window() {
+            let _ = Trace::from(vec![0]).windowed_stack_distance_stats(0);
+        }
+    }
+
+    mod reuse_distance {
+        use super::*;
+
+        macro_rules! reuse_distance_test {
+            ($name:ident: $($in:expr),* => $($out:expr),*) => {
+                #[test]
+                fn $name() {
+                    assert_eq!(Trace::from(vec![$($in),*]).reuse_distances().inner(), vec![$($out),*])
+                }
+            };
+        }
+
+        reuse_distance_test!(basic: 1, 2, 3 => None, None, None);
+        reuse_distance_test!(repeated: 0, 0, 1, 0 => None, Some(0), None, Some(1));
+        reuse_distance_test!(repeats_in_interval: 1, 2, 2, 2, 1 => None, None, Some(0), Some(0), Some(1));
+        reuse_distance_test!(one_repeated: 1, 2, 3, 1 => None, None, None, Some(2));
+
+        #[test]
+        fn with_sizes() {
+            use crate::item::GeneralModelGenerator;
+
+            let mut g = GeneralModelGenerator::new();
+
+            let a = g.item(1.0, 1);
+            let b = g.item(1.0, 5);
+
+            // the stack distance of the last access is 5, but only one distinct item intervenes
+            let trace = Trace::from(vec![a, a, b, a]);
+            assert_eq!(
+                trace.reuse_distances().inner(),
+                vec![None, Some(0), None, Some(1)]
+            );
+            assert_ne!(
+                trace.stack_distances().inner(),
+                trace
+                    .reuse_distances()
+                    .inner()
+                    .iter()
+                    .map(|d| d.map(|d| d as u32))
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
+
+    mod stack_distance_histograms {
+        use super::*;
+
+        macro_rules! stack_distance_histogram_test {
+            ($name:ident: $($in:expr),* => $($out:expr),*; $infinities:expr) => {
+                #[test]
+                fn $name() {
+                    let (freqs, infinities) = Trace::from(vec![$($in),*]).stack_distances().histogram();
+                    assert_eq!(infinities, $infinities);
+                    assert_eq!(freqs, Vec::<usize>::from([$($out),*]));
+                }
+            };
+        }
+
+        stack_distance_histogram_test!(basic: 1, 2, 3 => ; 3);
+        stack_distance_histogram_test!(repeated: 1, 1, 1 => 2; 1);
+        stack_distance_histogram_test!(one_two: 1, 2, 1, 1, 1 => 2, 1; 2);
+        stack_distance_histogram_test!(one_repeated: 1, 2, 3, 1 => 0, 0, 1; 3);
+        // stack_distance_histogram_test!(empty: => ; 0);
+    }
+
+    mod miss_ratio_curve {
+        use super::*;
+
+        macro_rules! miss_ratio_curve_test {
+            ($name:ident: $($in:expr),* => $($out:expr),*) => {
+                #[test]
+                fn $name() {
+                    let expected: Vec<f64> = vec![$($out),*];
+                    assert_eq!(Trace::<u32>::from(vec![$($in),*]).stack_distances().miss_ratio_curve(), expected);
+                }
+            };
+        }
+
+        miss_ratio_curve_test!(doc_example: 0, 0, 1, 0, 3, 0, 1 => 1.0, 6.0 / 7.0, 4.0 / 7.0, 3.0 / 7.0);
+        miss_ratio_curve_test!(basic: 1, 2, 3 => 1.0);
+        miss_ratio_curve_test!(repeated: 1, 1, 1, 1 => 1.0, 0.25);
+        miss_ratio_curve_test!(empty: => );
+
+        #[test]
+        fn non_increasing() {
+            let curve = Trace::from(vec![0, 1, 2, 0, 1, 3, 2, 2, 0, 4, 1, 3])
+                .stack_distances()
+                .miss_ratio_curve();
+            assert!(curve.windows(2).all(|w| w[0] >= w[1]));
+        }
+    }
+
+    mod cdf {
+        use super::*;
+
+        macro_rules! cdf_test {
+            ($name:ident: $($in:expr),* => $($out:expr),*; $infinities:expr) => {
+                #[test]
+                fn $name() {
+                    let expected: Vec<f64> = vec![$($out),*];
+                    let distances = Trace::<u32>::from(vec![$($in),*]).stack_distances();
+                    assert_eq!(distances.cdf(), expected);
+                    assert_eq!(distances.infinity_fraction(), $infinities);
+                }
+            };
+        }
+
+        cdf_test!(doc_example: 0, 0, 1, 0, 3, 0, 1 => 0.25, 0.75, 1.0; 3.0 / 7.0);
+        cdf_test!(repeated: 1, 1, 1, 1, 1 => 1.0; 0.2);
+        cdf_test!(gap: 0, 1, 2, 0, 0 => 0.5, 0.5, 1.0; 0.6);
+        cdf_test!(no_reuse: 1, 2, 3 => ; 1.0);
+        cdf_test!(empty: => ; 0.0);
+
+        #[test]
+        fn ends_at_one() {
+            let cdf = crate::generate::zipf_trace(100, 0.7, 3000, 8)
+                .stack_distances()
+                .cdf();
+
+            assert_eq!(cdf.last(), Some(&1.0));
+            assert!(cdf.windows(2).all(|w| w[0] <= w[1]));
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    mod stack_distance_histogram_parallel {
+        use super::*;
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        #[test]
+        fn matches_sequential() {
+            let mut rng = StdRng::seed_from_u64(0);
+
+            for len in [0, 1, 1000, 1_000_000] {
+                let distances = StackDistance {
+                    inner: (0..len)
+                        .map(|_| rng.gen_bool(0.9).then(|| rng.gen_range(0..1000)))
+                        .collect(),
+                };
+                assert_eq!(distances.histogram_parallel(), distances.histogram());
+            }
+        }
+    }
+
+    mod conditional_entropy {
+        use super::*;
+
+        use crate::condition::NoCondition;
+
+        macro_rules! conditional_entropy_test {
+            ($name:ident ($lag:expr): $($in:expr),* => $out:expr) => {
+                #[test]
+                fn $name() {
+                    assert!((Trace::<u32>::from(vec![$($in),*]).conditional_entropy($lag) - $out).abs() <= 0.0001)
+                }
+            };
+        }
+
+        conditional_entropy_test!(periodic (3): 0, 1, 2, 0, 1, 2, 0, 1, 2, 0 => 0.0);
+        conditional_entropy_test!(periodic_successor (1): 0, 1, 2, 0, 1, 2, 0, 1, 2, 0 => 0.0);
+        conditional_entropy_test!(lag_zero (0): 0, 1, 1, 0, 1 => 0.0);
+        conditional_entropy_test!(coin_flips (1): 0, 0, 1, 1, 0 => 1.0);
+        conditional_entropy_test!(too_short (5): 0, 1, 2 => 0.0);
+        conditional_entropy_test!(empty (1): => 0.0);
+
+        #[test]
+        fn at_most_entropy() {
+            let trace = Trace::from(vec![0, 1, 2, 0, 2, 0, 0, 3, 1, 1, 2]);
+            let unconditional = entropy(&trace.frequency_histogram(&NoCondition));
+            for lag in 0..trace.len() {
+                assert!(trace.conditional_entropy(lag) <= unconditional);
+            }
+        }
+    }
+
+    mod estimate_markov_order {
+        use super::*;
+        use crate::generate::markov_trace;
+
+        #[test]
+        fn first_order() {
+            let transition = vec![
+                vec![0.1, 0.8, 0.1],
+                vec![0.1, 0.1, 0.8],
+                vec![0.8, 0.1, 0.1],
+            ];
+            let trace = markov_trace(&transition, 0, 20_000, 3).unwrap();
+            assert_eq!(trace.estimate_markov_order(4, 0.05), 1);
+        }
+
+        #[test]
+        fn independent() {
+            let trace = markov_trace(&vec![vec![0.25; 4]; 4], 0, 20_000, 1).unwrap();
+            assert_eq!(trace.estimate_markov_order(3, 0.05), 0);
+        }
+
+        #[test]
+        fn periodic() {
+            let trace: Trace = (0..5).cycle().take(500).collect();
+            assert_eq!(trace.estimate_markov_order(3, 0.1), 1);
+        }
+    }
+
+    mod block_entropy {
+        use super::*;
+
+        macro_rules! block_entropy_test {
+            ($name:ident ($block_len:expr): $($in:expr),* => $out:expr) => {
+                #[test]
+                fn $name() {
+                    assert!((Trace::<u32>::from(vec![$($in),*]).block_entropy($block_len) - $out).abs() <= 0.0001)
+                }
+            };
+        }
+
+        block_entropy_test!(constant (3): 0, 0, 0, 0, 0 => 0.0);
+        block_entropy_test!(unit_blocks (1): 0, 1, 2, 3 => 2.0);
+        block_entropy_test!(pairs (2): 0, 1, 1, 0, 0 => 1.0);
+        block_entropy_test!(too_short (4): 0, 1, 2 => 0.0);
+        block_entropy_test!(empty (1): => 0.0);
+
+        #[test]
+        fn unit_blocks_are_entropy() {
+            let trace = Trace::from(vec![0, 1, 2, 0, 2, 0, 0, 3, 1, 1, 2]);
+            let unconditional = entropy(&trace.frequency_histogram(&NoCondition));
+            assert!((trace.block_entropy(1) - unconditional).abs() < 1e-10);
+        }
+
+        #[test]
+        fn periodic_approaches_zero() {
+            let trace: Trace = (0..1200).map(|i| i % 5).collect();
+
+            // there are only ever five distinct blocks, so the estimate falls like 1 / block_len
+            let estimates: Vec<f64> = [1, 2, 5, 10, 50, 100]
+                .iter()
+                .map(|&k| trace.block_entropy(k))
+                .collect();
+            assert!(estimates.windows(2).all(|w| w[1] < w[0]));
+            assert!(estimates[5] < 0.05);
+        }
+
+        #[test]
+        #[should_panic]
+        fn zero_block_len() {
+            let _ = Trace::from(vec![0, 1]).block_entropy(0);
+        }
+    }
+
+    mod working_set {
+        use super::*;
+
+        macro_rules! working_set_test {
+            ($name:ident: $($in:expr),* => $($out:expr),*) => {
+                #[test]
+                fn $name() {
+                    let expected: Vec<f64> = vec![$($out),*];
+                    assert_eq!(Trace::<u32>::from(vec![$($in),*]).working_set_curve(expected.len()), expected);
+                }
+            };
+        }
+
+        working_set_test!(basic: 0, 1, 0, 2 => 1.0, 1.75, 2.0);
+        working_set_test!(repeated: 1, 1, 1, 1 => 1.0, 1.0, 1.0);
+        working_set_test!(distinct: 1, 2, 3 => 1.0, 5.0 / 3.0, 2.0, 2.0);
+        working_set_test!(empty: => 0.0, 0.0);
+
+        #[test]
+        fn non_decreasing() {
+            let curve = Trace::from(vec![0, 1, 2, 0, 1, 3, 2, 2, 0, 4, 1, 3]).working_set_curve(20);
+            assert!(curve.windows(2).all(|w| w[0] <= w[1]));
+        }
+    }
+
+    mod footprint {
+        use super::*;
+
+        fn brute_force(trace: &Trace, window: usize) -> f64 {
+            let windows = trace.inner().windows(window);
+            let count = windows.len() as f64;
+            windows
+                .map(|w| w.iter().collect::<HashSet<_>>().len() as f64)
+                .sum::<f64>()
+                / count
+        }
+
+        #[test]
+        fn hand_computed() {
+            // windows of 3: 012, 120, 201, 013 have 3 distinct items; 010, 101 have 2
+            let trace = Trace::from(vec![0, 1, 2, 0, 1, 0, 1, 3]);
+
+            assert_eq!(trace.footprint(1), 1.0);
+            assert_eq!(trace.footprint(2), 2.0);
+            assert!((trace.footprint(3) - 16.0 / 6.0).abs() < 1e-9);
+            assert_eq!(trace.footprint(8), 4.0);
+        }
+
+        #[test]
+        fn matches_brute_force() {
+            let trace = crate::generate::zipf_trace(15, 0.8, 200, 5);
+            let curve = trace.footprint_curve(trace.len());
+
+            for (window, footprint) in (1..).zip(curve) {
+                assert!((footprint - brute_force(&trace, window)).abs() < 1e-9);
+            }
+        }
+
+        #[test]
+        fn long_windows_see_everything() {
+            let trace = Trace::from(vec![0, 1, 0]);
+            assert_eq!(trace.footprint_curve(5), vec![1.0, 2.0, 2.0, 2.0, 2.0]);
+        }
+
+        #[test]
+        fn empty() {
+            assert_eq!(Trace::<u32>::default().footprint_curve(2), vec![0.0, 0.0]);
+            assert_eq!(Trace::from(vec![0, 1]).footprint(0), 0.0);
+        }
+    }
+
+    mod popularity_gini {
+        use super::*;
+        use crate::generate::zipf_trace;
+
+        #[test]
+        fn uniform() {
+            let trace: Trace = (0..50).cycle().take(1000).collect();
+            assert!(trace.popularity_gini().abs() < 1e-10);
+        }
+
+        #[test]
+        fn skewed() {
+            let mut trace = Trace::from(vec![0; 990]);
+            trace.extend(1..10);
+            assert!(trace.popularity_gini() > 0.85);
+        }
+
+        #[test]
+        fn increases_with_skew() {
+            let gini = |alpha| zipf_trace(100, alpha, 20_000, 5).popularity_gini();
+            assert!(gini(0.2) < gini(0.8));
+            assert!(gini(0.8) < gini(1.4));
+        }
+
+        #[test]
+        fn trivial() {
+            assert_eq!(Trace::<u32>::default().popularity_gini(), 0.0);
+            assert_eq!(Trace::from(vec![4, 4]).popularity_gini(), 0.0);
+        }
+    }
+
+    mod estimate_zipf_alpha {
+        use super::*;
+        use crate::generate::zipf_trace;
+
+        #[test]
+        fn recovers_alpha() {
+            for (alpha, seed) in [(0.6, 0), (0.9, 1), (1.2, 2)] {
+                let estimate = zipf_trace(200, alpha, 200_000, seed).estimate_zipf_alpha();
+                assert!(
+                    (estimate - alpha).abs() < 0.05,
+                    "alpha {alpha}: estimate {estimate}"
+                );
+            }
+        }
+
+        #[test]
+        fn uniform() {
+            let trace: Trace = (0..10).cycle().take(100).collect();
+            assert!(trace.estimate_zipf_alpha().abs() < 1e-10);
+        }
+
+        #[test]
+        fn too_few_items() {
+            assert!(Trace::<u32>::default().estimate_zipf_alpha().is_nan());
+            assert!(Trace::from(vec![3, 3, 3]).estimate_zipf_alpha().is_nan());
+        }
+    }
+
+    mod che_approximation {
+        use super::*;
+        use crate::generate::zipf_trace;
+        use crate::{Cache, GeneralModelGenerator, Lru};
+
+        #[test]
+        fn tracks_lru_simulation() {
+            let trace = zipf_trace(1000, 0.8, 50_000, 3);
+
+            for capacity in [10, 50, 100, 300] {
+                let mut cache: Cache<Lru> = Cache::new(capacity);
+                cache.run_trace(&trace);
+
+                let error = (trace.che_approximation(capacity) - cache.hit_rate()).abs();
+                assert!(error < 0.02, "capacity {capacity}: error {error}");
+            }
+        }
+
+        #[test]
+        fn non_decreasing() {
+            let trace = zipf_trace(100, 1.0, 5000, 4);
+            let estimates: Vec<f64> = (0..=100).map(|c| trace.che_approximation(c)).collect();
+            assert!(estimates.windows(2).all(|w| w[0] <= w[1]));
+        }
+
+        #[test]
+        fn sized_items() {
+            let mut g = GeneralModelGenerator::new();
+            let (a, b) = (g.item(1.0, 2), g.item(1.0, 2));
+            let trace = Trace::from(vec![a, b, a, b]);
+
+            // half the cache holds half the (equally popular) bytes
+            assert!((trace.che_approximation(2) - 0.5).abs() < 1e-10);
+            assert!(trace.che_approximation(3) < 1.0);
+            assert_eq!(trace.che_approximation(4), 1.0);
+        }
+
+        #[test]
+        fn empty() {
+            assert_eq!(Trace::<u32>::from(vec![]).che_approximation(10), 0.0);
+        }
+    }
+
+    mod eviction_cost_histogram {
+        use super::*;
+        use crate::{Fifo, GeneralModelGenerator, GeneralModelItem, Landlord, Lru};
+
+        fn two_cost_classes() -> Trace<GeneralModelItem> {
+            let mut g = GeneralModelGenerator::new();
+            let items: Vec<_> = (0..20)
+                .map(|i| g.item(if i % 2 == 0 { 1.0 } else { 10.0 }, 1))
+                .collect();
+
+            let mut rng = StdRng::seed_from_u64(3);
+            (0..2000)
+                .map(|_| items[rng.gen_range(0..items.len())])
+                .collect()
+        }
+
+        fn eviction_rate(
+            (evicted, surviving): &(HashMap<Cost, usize>, HashMap<Cost, usize>),
+            cost: f64,
+        ) -> f64 {
+            let evicted = evicted.get(&Cost(cost)).copied().unwrap_or(0) as f64;
+            evicted / (evicted + surviving[&Cost(cost)] as f64)
+        }
+
+        #[test]
+        fn landlord_keeps_expensive_items() {
+            let trace = two_cost_classes();
+            let landlord = trace.eviction_cost_histogram(6, <Landlord>::default());
+            let lru = trace.eviction_cost_histogram(6, Lru::default());
+
+            assert!(eviction_rate(&landlord, 10.0) < eviction_rate(&landlord, 1.0));
+            // LRU doesn't care about cost, so the classes are evicted about as often
+            assert!(eviction_rate(&landlord, 10.0) < eviction_rate(&lru, 10.0));
+        }
+
+        #[test]
+        fn counts_every_reference() {
+            let trace = two_cost_classes();
+            let (evicted, surviving) = trace.eviction_cost_histogram(6, Fifo::default());
+
+            assert_eq!(
+                evicted.values().sum::<usize>() + surviving.values().sum::<usize>(),
+                trace.len()
+            );
+        }
+
+        #[test]
+        fn bypasses_are_evictions() {
+            let mut g = GeneralModelGenerator::new();
+            let (small, big) = (g.item(1.0, 1), g.item(2.0, 3));
+            let trace = Trace::from(vec![small, big, small, big]);
+
+            let (evicted, surviving) = trace.eviction_cost_histogram(2, Lru::default());
+
+            assert_eq!(evicted, HashMap::from([(Cost(2.0), 2)]));
+            assert_eq!(surviving, HashMap::from([(Cost(1.0), 2)]));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    mod serde {
+        use super::*;
+        use crate::GeneralModelGenerator;
+
+        #[test]
+        fn trace_round_trip() -> serde_json::Result<()> {
+            let trace = Trace::from(vec![3, 0, 0, 2, 1, 3]);
+            let json = serde_json::to_string(&trace)?;
+
+            assert_eq!(json, "[3,0,0,2,1,3]");
+            assert_eq!(serde_json::from_str::<Trace>(&json)?, trace);
+            Ok(())
+        }
+
+        #[test]
+        fn general_model_trace_round_trip() -> serde_json::Result<()> {
+            let mut g = GeneralModelGenerator::new();
+            let (a, b) = (g.item(1.5, 2), g.item(0.5, 3));
+            let trace = Trace::from(vec![a, b, a]);
+
+            let json = serde_json::to_string(&trace)?;
+            let round_tripped = serde_json::from_str::<Trace<_>>(&json)?;
+
+            assert_eq!(round_tripped, trace);
+            assert_eq!(round_tripped.stack_distances(), trace.stack_distances());
+            Ok(())
+        }
+
+        #[test]
+        fn stack_distance_round_trip() -> serde_json::Result<()> {
+            let distances = Trace::from(vec![0, 0, 1, 0, 3, 0, 1]).stack_distances();
+            let json = serde_json::to_string(&distances)?;
+
+            assert_eq!(json, "[null,0,null,1,null,1,2]");
+            assert_eq!(serde_json::from_str::<StackDistance>(&json)?, distances);
+            Ok(())
+        }
+    }
+
+    mod collection {
+        use super::*;
+
+        #[test]
+        fn collect_matches_from() {
+            let trace: Trace = (0..5).chain([1, 1, 0]).collect();
+            assert_eq!(trace, Trace::from(vec![0, 1, 2, 3, 4, 1, 1, 0]));
+        }
+
+        #[test]
+        fn extend_appends_in_order() {
+            let mut trace = Trace::from(vec![3, 1]);
+            trace.extend([4, 1, 5]);
+            trace.extend(Vec::new());
+            trace.extend(Trace::from(vec![9, 2]));
+
+            assert_eq!(trace, Trace::from(vec![3, 1, 4, 1, 5, 9, 2]));
+        }
+
+        #[test]
+        fn iterates_in_access_order() {
+            let trace = Trace::from(vec![2, 0, 2, 1]);
+
+            let mut borrowed = Vec::new();
+            for item in &trace {
+                borrowed.push(*item);
+            }
+            assert_eq!(borrowed, vec![2, 0, 2, 1]);
+
+            let mut owned = Vec::new();
+            for item in trace {
+                owned.push(item);
+            }
+            assert_eq!(owned, vec![2, 0, 2, 1]);
+        }
+
+        #[test]
+        fn index() {
+            let trace = Trace::from(vec![2, 0, 2, 1]);
+            assert_eq!(trace[1], 0);
+            assert_eq!(trace[2..], [2, 1]);
+        }
+    }
+
+    mod combination {
+        use super::*;
+
+        macro_rules! combination_test {
+            ($name:ident $op:ident: [$($a:expr),*], [$($b:expr),*] => $($out:expr),*) => {
+                #[test]
+                fn $name() {
+                    assert_eq!(
+                        Trace::<u32>::from(vec![$($a),*]).$op(Trace::from(vec![$($b),*])),
+                        Trace::from(vec![$($out),*])
+                    );
+                }
+            };
+        }
+
+        combination_test!(concat concat: [0, 1], [2, 3, 4] => 0, 1, 2, 3, 4);
+        combination_test!(concat_empty concat: [], [2] => 2);
+        combination_test!(interleave interleave: [0, 1], [2, 3] => 0, 2, 1, 3);
+        combination_test!(interleave_longer_first interleave: [0, 1, 4, 5], [2, 3] => 0, 2, 1, 3, 4, 5);
+        combination_test!(interleave_longer_second interleave: [0], [2, 3, 6] => 0, 2, 3, 6);
+        combination_test!(interleave_empty interleave: [], [2, 3] => 2, 3);
+    }
+
+    mod rle {
+        use super::*;
+
+        #[test]
+        fn from_rle() {
+            assert_eq!(
+                Trace::from_rle(&[(1, 3), (2, 1)]),
+                Trace::from(vec![1, 1, 1, 2])
+            );
+            // empty runs are skipped
+            assert_eq!(
+                Trace::from_rle(&[(1, 0), (2, 2), (3, 0)]),
+                Trace::from(vec![2, 2])
+            );
+        }
+
+        #[test]
+        fn to_rle() {
+            assert_eq!(Trace::from(vec![1, 1, 1, 2]).to_rle(), vec![(1, 3), (2, 1)]);
+            assert!(Trace::<u32>::default().to_rle().is_empty());
+        }
+
+        #[test]
+        fn round_trip() {
+            let transition = [vec![0.8, 0.2], vec![0.3, 0.7]];
+            let trace = crate::generate::markov_trace(&transition, 0, 500, 4).unwrap();
+            assert_eq!(Trace::from_rle(&trace.to_rle()), trace);
+
+            let pairs = vec![(0, 2), (1, 5), (0, 1), (2, 3)];
+            assert_eq!(Trace::from_rle(&pairs).to_rle(), pairs);
+        }
+    }
+
+    mod reorder {
+        use super::*;
+
+        #[test]
+        fn reverse() {
+            assert_eq!(
+                Trace::from(vec![1, 2, 3]).reverse(),
+                Trace::from(vec![3, 2, 1])
+            );
+            assert!(Trace::<u32>::default().reverse().is_empty());
+        }
+
+        #[test]
+        fn rotate_left() {
+            let trace = Trace::from(vec![1, 2, 3]);
+
+            assert_eq!(trace.rotate_left(1), Trace::from(vec![2, 3, 1]));
+            assert_eq!(trace.rotate_left(0), trace);
+            assert_eq!(trace.rotate_left(3), trace);
+            assert_eq!(trace.rotate_left(4), Trace::from(vec![2, 3, 1]));
+            assert!(Trace::<u32>::default().rotate_left(2).is_empty());
+        }
+
+        #[test]
+        fn preserve_frequencies() {
+            let trace = crate::generate::zipf_trace(20, 1.0, 300, 2);
+            let frequencies = trace.frequency_histogram(&NoCondition);
+
+            assert_eq!(
+                trace.reverse().frequency_histogram(&NoCondition),
+                frequencies
+            );
+            assert_eq!(
+                trace.rotate_left(77).frequency_histogram(&NoCondition),
+                frequencies
+            );
+        }
+    }
+
+    mod chunks {
+        use super::*;
+
+        #[test]
+        fn lengths_and_concatenation() {
+            let trace: Trace = (0..10).collect();
+            let chunks: Vec<_> = trace.chunks(4).collect();
+
+            assert_eq!(
+                chunks.iter().map(Trace::len).collect::<Vec<_>>(),
+                vec![4, 4, 2]
+            );
+            assert_eq!(chunks.into_iter().reduce(Trace::concat).unwrap(), trace);
+        }
+
+        #[test]
+        fn per_chunk_statistics() {
+            let trace = Trace::from(vec![0, 0, 0, 0, 1, 2, 3, 4]);
+            let entropies: Vec<_> = trace
+                .chunks(4)
+                .map(|chunk| entropy(&chunk.frequency_histogram(&NoCondition)))
+                .collect();
+
+            assert_eq!(entropies, vec![0.0, 2.0]);
+        }
+
+        #[test]
+        fn empty() {
+            assert_eq!(Trace::<u32>::default().chunks(3).count(), 0);
+        }
+
+        #[test]
+        #[should_panic(expected = "The chunk size must be positive.")]
+        fn zero_size() {
+            let _ = Trace::from(vec![0]).chunks(0);
+        }
+    }
+
+    mod relabel {
+        use super::*;
+
+        #[test]
+        fn first_seen_order() {
+            let (trace, labels) = Trace::from(vec![40, 10, 40, 30, 10, 20]).relabel();
+
+            assert_eq!(trace, Trace::from(vec![0, 1, 0, 2, 1, 3]));
+            assert_eq!(labels, HashMap::from([(40, 0), (10, 1), (30, 2), (20, 3)]));
+        }
+
+        #[test]
+        fn preserves_stack_distances() {
+            let original = Trace::from(vec![9, 4, 9, 9, 2, 4, 7, 2, 9]);
+            let (relabeled, _) = original.relabel();
+
+            assert_eq!(
+                relabeled.stack_distances().inner(),
+                original.stack_distances().inner()
+            );
+        }
+
+        #[test]
+        fn empty() {
+            let (trace, labels) = Trace::<u32>::default().relabel();
+            assert!(trace.is_empty());
+            assert!(labels.is_empty());
+        }
+
+        #[test]
+        fn pretty_print_sparse() {
+            let trace = Trace::from(vec![100, 200, 300, 200, 100]);
+            assert_eq!(&trace.pretty_print_relabeled(), "A, B, C, B, A");
+            assert_eq!(&trace.pretty_print(), "100, 200, 300, 200, 100");
+        }
+
+        #[test]
+        fn pretty_print_general_items() {
+            let mut g = crate::GeneralModelGenerator::new();
+            let (a, b) = (g.item(1.0, 3), g.item(2.0, 1));
+            assert_eq!(
+                &Trace::from(vec![b, a, b]).pretty_print_relabeled(),
+                "A, B, A"
+            );
+        }
 
-        let mut freqs = max.map_or_else(Vec::new, |max| vec![0; *max as usize + 1]);
+        #[test]
+        fn pretty_print_too_many_items() {
+            let trace: Trace = (1000..1030).collect();
+            assert_eq!(
+                trace.pretty_print_relabeled(),
+                (0..30).map(|i| i.to_string()).join(", ")
+            );
+        }
+    }
 
-        let mut infinities = 0;
+    mod filter {
+        use super::*;
 
-        for &i in &self.inner {
-            #[allow(clippy::option_if_let_else)]
-            if let Some(i) = i {
-                freqs[i as usize] += 1;
-            } else {
-                infinities += 1;
-            }
+        use crate::condition::{LastNItems, NoCondition};
+
+        #[test]
+        fn equals_previous() {
+            let equals_previous = |t: &Trace, i| i > 0 && t[i - 1] == t[i];
+            let trace = Trace::from(vec![3, 3, 1, 2, 2, 2, 3, 1, 1]);
+
+            assert_eq!(
+                trace.filter(&equals_previous),
+                Trace::from(vec![3, 2, 2, 1])
+            );
         }
 
-        (freqs, infinities)
-    }
+        #[test]
+        fn last_n_items() {
+            let trace = Trace::from(vec![0, 1, 2, 0, 1, 3, 1]);
+            assert_eq!(
+                trace.filter(&LastNItems::new(vec![0, 1])),
+                Trace::from(vec![2, 3])
+            );
+        }
 
-    /// Get a reference to the inner vector of distances.
-    ///
-    /// The ith element of the vector is the ith access of the trace.
-    #[must_use]
-    pub fn inner(&self) -> &[Option<u32>] {
-        self.inner.as_ref()
+        #[test]
+        fn no_condition() {
+            let trace = Trace::from(vec![0, 1, 2, 0]);
+            assert_eq!(trace.filter(&NoCondition), trace);
+        }
     }
 
-    /// Take ownership of the inner vector of distances.
-    ///
-    /// The ith element of the vector is the ith access of the trace.
-    #[must_use]
-    #[allow(clippy::missing_const_for_fn)] // false positive, destructors can't be const
-    pub fn into_inner(self) -> Vec<Option<u32>> {
-        self.inner
-    }
-}
+    mod dedupe_consecutive {
+        use super::*;
 
-/// Returns the entropy of a given distribution.
-#[must_use]
-pub fn entropy<I: Item, H: std::hash::BuildHasher>(histogram: &HashMap<I, u32, H>) -> f64 {
-    let total = f64::from(histogram.values().sum::<u32>());
-    -histogram
-        .values()
-        .map(|&i| (f64::from(i) / total) * ((f64::from(i) / total).log2()))
-        .sum::<f64>()
-}
+        #[test]
+        fn collapses_runs() {
+            let trace = Trace::from(vec![1, 1, 2, 2, 2, 1]);
+            assert_eq!(trace.dedupe_consecutive(), Trace::from(vec![1, 2, 1]));
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        #[test]
+        fn keeps_non_adjacent_repeats() {
+            let trace = Trace::from(vec![0, 1, 0, 1, 2, 0]);
+            assert_eq!(trace.dedupe_consecutive(), trace);
+        }
 
-    mod stack_distance {
+        #[test]
+        fn only_changes_hits() {
+            let trace = Trace::from(vec![3, 3, 3, 1, 2, 2, 3, 1, 1, 0]);
+            let deduped = trace.dedupe_consecutive();
+
+            let mut raw = Cache::<crate::Lru>::new(2);
+            raw.run_trace(&trace);
+            let mut collapsed = Cache::<crate::Lru>::new(2);
+            collapsed.run_trace(&deduped);
+
+            assert_eq!(raw.misses(), collapsed.misses());
+            assert_eq!(raw.hits() - collapsed.hits(), 4);
+        }
+
+        #[test]
+        fn empty() {
+            assert!(Trace::<u32>::default().dedupe_consecutive().is_empty());
+        }
+    }
+
+    mod sampling {
         use super::*;
 
-        macro_rules! stack_distance_test {
-            ($name:ident: $($in:expr),* => $($out:expr),*) => {
-                #[test]
-                fn $name() {
-                    assert_eq!(Trace::from(vec![$($in),*]).stack_distances().inner(), vec![$($out),*])
-                }
-            };
+        #[test]
+        fn stride_keeps_every_nth() {
+            let trace = Trace::from((0..10).collect::<Vec<_>>());
+            assert_eq!(trace.stride_sample(2), Trace::from(vec![0, 2, 4, 6, 8]));
         }
 
-        stack_distance_test!(basic: 1, 2, 3 => None, None, None);
-        stack_distance_test!(repeated: 1, 1, 1 => None, Some(0), Some(0));
-        stack_distance_test!(one_two: 1, 2, 1, 1, 1 => None, None, Some(1), Some(0), Some(0));
-        stack_distance_test!(one_repeated: 1, 2, 3, 1 => None, None, None, Some(2));
-        // stack_distance_test!(empty: => );
+        #[test]
+        fn stride_one_is_identity() {
+            let trace = Trace::from(vec![3, 1, 3, 2]);
+            assert_eq!(trace.stride_sample(1), trace);
+        }
 
         #[test]
-        fn with_sizes() {
-            use crate::item::GeneralModelGenerator;
+        #[should_panic(expected = "The stride must be positive.")]
+        fn stride_zero() {
+            let _ = Trace::from(vec![0, 1]).stride_sample(0);
+        }
 
-            let mut g = GeneralModelGenerator::new();
+        #[test]
+        fn random_full_fraction_is_identity() {
+            let trace = Trace::from(vec![0, 1, 2, 0, 1, 3]);
+            assert_eq!(trace.random_sample(1.0, 7), trace);
+        }
 
-            let a = g.item(1.0, 2);
-            let b = g.item(1.0, 4);
-            let c = g.item(1.0, 3);
+        #[test]
+        fn random_zero_fraction_is_empty() {
+            assert!(Trace::from(vec![0, 1, 2]).random_sample(0.0, 7).is_empty());
+        }
 
-            let trace = Trace::from(vec![a, b, c, a]);
+        #[test]
+        fn random_keeps_order() {
+            let sample = Trace::from((0..500).collect::<Vec<_>>()).random_sample(0.5, 3);
+            assert!(sample.inner().windows(2).all(|w| w[0] < w[1]));
+        }
+
+        #[test]
+        #[should_panic(expected = "The fraction must be in [0, 1].")]
+        fn random_bad_fraction() {
+            let _ = Trace::from(vec![0, 1]).random_sample(1.5, 0);
+        }
+    }
+
+    mod reuse_survival {
+        use super::*;
+
+        #[test]
+        fn hand_computed() {
+            // reused after 1, 3, 3, 4, and 1 positions; the last references to 0, 1, and 2 aren't
+            let trace = Trace::from(vec![0, 0, 1, 2, 0, 1, 1, 2]);
             assert_eq!(
-                trace.stack_distances().inner(),
-                vec![None, None, None, Some(7)]
+                trace.reuse_survival(6),
+                vec![0.0, 0.25, 0.25, 0.5, 0.625, 0.625, 0.625]
             );
         }
+
+        #[test]
+        fn approaches_reused_fraction() {
+            let trace = crate::generate::zipf_trace(30, 0.9, 1000, 3);
+            let curve = trace.reuse_survival(trace.len());
+
+            assert!(curve.windows(2).all(|w| w[0] <= w[1]));
+            #[allow(clippy::cast_precision_loss)]
+            let reused = (trace.len() - trace.iter().unique().count()) as f64 / trace.len() as f64;
+            assert!((curve[trace.len()] - reused).abs() < 1e-12);
+        }
+
+        #[test]
+        fn empty() {
+            assert_eq!(Trace::<u32>::default().reuse_survival(2), vec![0.0; 3]);
+        }
     }
 
-    mod stack_distance_histograms {
+    mod inter_reference_gaps {
         use super::*;
 
-        macro_rules! stack_distance_histogram_test {
-            ($name:ident: $($in:expr),* => $($out:expr),*; $infinities:expr) => {
-                #[test]
-                fn $name() {
-                    let (freqs, infinities) = Trace::from(vec![$($in),*]).stack_distances().histogram();
-                    assert_eq!(infinities, $infinities);
-                    assert_eq!(freqs, vec![$($out),*]);
-                }
-            };
+        #[test]
+        fn basic() {
+            let gaps = Trace::from(vec![0, 1, 0, 2, 0]).inter_reference_gaps();
+            assert_eq!(
+                gaps,
+                HashMap::from([(0, vec![2, 2]), (1, vec![]), (2, vec![])])
+            );
         }
 
-        stack_distance_histogram_test!(basic: 1, 2, 3 => ; 3);
-        stack_distance_histogram_test!(repeated: 1, 1, 1 => 2; 1);
-        stack_distance_histogram_test!(one_two: 1, 2, 1, 1, 1 => 2, 1; 2);
-        stack_distance_histogram_test!(one_repeated: 1, 2, 3, 1 => 0, 0, 1; 3);
-        // stack_distance_histogram_test!(empty: => ; 0);
+        #[test]
+        fn repeats() {
+            let gaps = Trace::from(vec![1, 1, 2, 1, 2, 2]).inter_reference_gaps();
+            assert_eq!(gaps[&1], vec![1, 2]);
+            assert_eq!(gaps[&2], vec![2, 1]);
+        }
     }
 
     mod frequency {
@@ -399,6 +3110,202 @@ mod tests {
         frequency_test!(one_two: 1, 2, 1, 1, 1 => (1, 4), (2, 1));
         frequency_test!(one_repeated: 1, 2, 3, 1 => (1, 2), (2, 1), (3, 1));
         // frequency_test!(empty: => );
+
+        #[test]
+        fn from_frequencies_round_trip() {
+            let histogram =
+                crate::generate::zipf_trace(100, 1.0, 5000, 0).frequency_histogram(&NoCondition);
+            let trace = Trace::from_frequencies(&histogram, 1);
+
+            assert_eq!(trace.len(), 5000);
+            assert_eq!(trace.frequency_histogram(&NoCondition), histogram);
+        }
+
+        #[test]
+        fn from_frequencies_is_seeded() {
+            let frequencies = HashMap::from([(0, 5), (1, 5), (2, 5), (3, 5)]);
+
+            // a separately built map has its own iteration order, which shouldn't matter
+            let reordered = HashMap::from([(3, 5), (2, 5), (1, 5), (0, 5)]);
+
+            assert_eq!(
+                Trace::from_frequencies(&frequencies, 7),
+                Trace::from_frequencies(&reordered, 7)
+            );
+            assert_ne!(
+                Trace::from_frequencies(&frequencies, 7),
+                Trace::from_frequencies(&frequencies, 8)
+            );
+        }
+
+        #[test]
+        fn from_frequencies_skips_zeros() {
+            let trace = Trace::from_frequencies(&HashMap::from([(0, 0), (1, 2)]), 0);
+            assert_eq!(trace, Trace::from(vec![1, 1]));
+        }
+    }
+
+    mod summary {
+        use super::*;
+
+        #[test]
+        fn fields() {
+            let summary = Trace::from(vec![7, 3, 7, 12, 3, 7, 5, 3]).summary();
+
+            assert_eq!(summary.len, 8);
+            assert_eq!(summary.distinct, 4);
+            assert_eq!(summary.min, Some(3));
+            assert_eq!(summary.max, Some(12));
+            // 3 and 7 are tied, so the smaller one wins
+            assert_eq!(summary.most_frequent, Some((3, 3)));
+            assert_eq!(summary.first_reference_fraction, 0.5);
+        }
+
+        #[test]
+        fn matches_stack_distances() {
+            let trace = crate::generate::zipf_trace(40, 1.0, 500, 11);
+            assert_eq!(
+                trace.summary().first_reference_fraction,
+                trace.stack_distances().infinity_fraction()
+            );
+        }
+
+        #[test]
+        fn empty() {
+            let summary = Trace::<u32>::default().summary();
+
+            assert_eq!(
+                summary,
+                TraceSummary {
+                    len: 0,
+                    distinct: 0,
+                    min: None,
+                    max: None,
+                    most_frequent: None,
+                    first_reference_fraction: 0.0,
+                }
+            );
+            assert_eq!(
+                summary.to_string(),
+                "0 accesses to 0 distinct items\nfirst references: 0.00%"
+            );
+        }
+
+        #[test]
+        fn general_model_items() {
+            use crate::GeneralModelItem;
+
+            let trace = Trace::from(vec![
+                GeneralModelItem::new(5, 1.0, 2),
+                GeneralModelItem::new(1, 2.0, 1),
+                GeneralModelItem::new(5, 1.0, 2),
+            ]);
+            let summary = trace.summary();
+
+            assert_eq!(summary.min, Some(GeneralModelItem::new(1, 2.0, 1)));
+            assert_eq!(
+                summary.most_frequent,
+                Some((GeneralModelItem::new(5, 1.0, 2), 2))
+            );
+        }
+    }
+
+    mod trace_distance {
+        use super::*;
+
+        #[test]
+        fn identical() {
+            let trace = crate::generate::zipf_trace(20, 0.8, 200, 1);
+
+            assert_eq!(trace_hamming_distance(&trace, &trace), Some(0));
+            assert_eq!(trace_lcs_length(&trace, &trace), trace.len());
+        }
+
+        #[test]
+        fn one_position() {
+            let a = Trace::from(vec![0, 1, 2, 1, 0]);
+            let b = Trace::from(vec![0, 1, 3, 1, 0]);
+
+            assert_eq!(trace_hamming_distance(&a, &b), Some(1));
+            assert_eq!(trace_lcs_length(&a, &b), 4);
+        }
+
+        #[test]
+        fn shifted() {
+            // one insertion moves every later access, which Hamming distance can't see past
+            let a = Trace::from(vec![0, 1, 2, 3]);
+            let b = Trace::from(vec![9, 0, 1, 2]);
+
+            assert_eq!(trace_hamming_distance(&a, &b), Some(4));
+            assert_eq!(trace_lcs_length(&a, &b), 3);
+        }
+
+        #[test]
+        fn different_lengths() {
+            let a = Trace::from(vec![0, 1, 2]);
+
+            assert_eq!(trace_hamming_distance(&a, &Trace::from(vec![0, 1])), None);
+            assert_eq!(trace_lcs_length(&a, &Trace::from(vec![2, 0])), 1);
+            assert_eq!(trace_lcs_length(&a, &Trace::default()), 0);
+        }
+    }
+
+    mod kl_divergence {
+        use super::*;
+
+        #[test]
+        fn self_divergence_is_zero() {
+            let trace = crate::generate::zipf_trace(30, 0.9, 500, 9);
+
+            assert_eq!(frequency_kl_divergence(&trace, &trace, 0.0), 0.0);
+            assert!(frequency_kl_divergence(&trace, &trace, 1.0).abs() < 1e-12);
+        }
+
+        #[test]
+        fn hand_computed() {
+            // P_a = (1/2, 1/2) and P_b = (3/4, 1/4)
+            let a = Trace::from(vec![0, 1]);
+            let b = Trace::from(vec![0, 0, 0, 1]);
+            let expected = 0.5 * (0.5_f64 / 0.75).log2() + 0.5 * (0.5_f64 / 0.25).log2();
+
+            assert!((frequency_kl_divergence(&a, &b, 0.0) - expected).abs() < 1e-12);
+        }
+
+        #[test]
+        fn asymmetric() {
+            let a = Trace::from(vec![0, 1, 1, 1]);
+            let b = Trace::from(vec![0, 0, 0, 1]);
+            let c = Trace::from(vec![0, 1, 2, 2]);
+
+            assert!(frequency_kl_divergence(&a, &b, 0.0) > 0.0);
+            assert_ne!(
+                frequency_kl_divergence(&a, &c, 0.5),
+                frequency_kl_divergence(&c, &a, 0.5)
+            );
+        }
+
+        #[test]
+        fn smoothing_keeps_it_finite() {
+            let a = Trace::from(vec![0, 1, 2]);
+            let b = Trace::from(vec![0, 1]);
+
+            assert!(frequency_kl_divergence(&a, &b, 0.0).is_infinite());
+            assert!(frequency_kl_divergence(&a, &b, 0.5).is_finite());
+        }
+
+        #[test]
+        fn empty_traces() {
+            let empty = Trace::<u32>::from(vec![]);
+            let a = Trace::from(vec![0, 1]);
+
+            assert_eq!(frequency_kl_divergence(&empty, &empty, 0.0), 0.0);
+            assert_eq!(frequency_kl_divergence(&empty, &a, 0.0), 0.0);
+            assert!(frequency_kl_divergence(&a, &empty, 0.0).is_infinite());
+
+            assert_eq!(frequency_kl_divergence(&empty, &empty, 1.0), 0.0);
+            assert!(frequency_kl_divergence(&empty, &a, 1.0).abs() < 1e-12);
+            assert!(frequency_kl_divergence(&a, &empty, 1.0).abs() < 1e-12);
+        }
     }
 
     mod entropy {
@@ -420,4 +3327,85 @@ mod tests {
         entropy_test!(unbalanced: 0,1,2,0,2,0,0,3 => 1.75);
         entropy_test!(precise_value: 0,1,2,0,2,0,0 => 1.37878);
     }
+
+    mod cost_weighted_entropy {
+        use super::*;
+        use crate::GeneralModelGenerator;
+
+        #[test]
+        fn concentrated_cost() {
+            let mut g = GeneralModelGenerator::new();
+            let even: Vec<_> = (0..4).map(|_| g.item(1.0, 1)).collect();
+            let skewed: Vec<_> = [1.0, 1.0, 1.0, 10.0]
+                .into_iter()
+                .map(|cost| g.item(cost, 1))
+                .collect();
+
+            let order = [0, 1, 2, 3, 3, 2, 1, 0];
+            let even = Trace::from(order.map(|i| even[i]).to_vec());
+            let skewed = Trace::from(order.map(|i| skewed[i]).to_vec());
+
+            // the frequencies are the same, so only the costs differ
+            let frequency_entropy = |t: &Trace<_>| entropy(&t.frequency_histogram(&NoCondition));
+            assert_eq!(frequency_entropy(&even), frequency_entropy(&skewed));
+
+            assert!((even.cost_weighted_entropy() - 2.0).abs() < 1e-10);
+            assert!(skewed.cost_weighted_entropy() < even.cost_weighted_entropy());
+        }
+
+        #[test]
+        fn unit_costs_match_frequency_entropy() {
+            let trace = Trace::from(vec![0, 1, 2, 0, 2, 0, 0, 3, 1]);
+            let frequency = entropy(&trace.frequency_histogram(&NoCondition));
+            assert!((trace.cost_weighted_entropy() - frequency).abs() < 1e-10);
+        }
+
+        #[test]
+        fn zero_cost() {
+            let mut g = GeneralModelGenerator::new();
+            let (a, b) = (g.item(0.0, 1), g.item(0.0, 1));
+
+            assert_eq!(Trace::from(vec![a, b]).cost_weighted_entropy(), 0.0);
+            assert_eq!(Trace::<u32>::default().cost_weighted_entropy(), 0.0);
+        }
+    }
+
+    mod entropy_estimator {
+        use super::*;
+        use crate::generate::zipf_trace;
+
+        #[test]
+        fn matches_batch() {
+            let trace = zipf_trace(200, 0.9, 20_000, 4);
+            let mut estimator = EntropyEstimator::default();
+
+            for (i, item) in trace.iter().enumerate() {
+                estimator.observe(*item);
+
+                if i % 1000 == 999 {
+                    let prefix = Trace::from(trace.inner()[..=i].to_vec());
+                    let batch = entropy(&prefix.frequency_histogram(&NoCondition));
+                    assert!((estimator.estimate() - batch).abs() < 1e-9);
+                }
+            }
+
+            let frequencies: HashMap<_, _> = trace
+                .frequency_histogram(&NoCondition)
+                .into_iter()
+                .map(|(item, frequency)| (item, u64::from(frequency)))
+                .collect();
+            assert_eq!(estimator.counts(), &frequencies);
+            assert_eq!(estimator.total(), trace.len() as u64);
+        }
+
+        #[test]
+        fn trivial() {
+            let mut estimator = EntropyEstimator::default();
+            assert_eq!(estimator.estimate(), 0.0);
+
+            estimator.observe(3);
+            estimator.observe(3);
+            assert_eq!(estimator.estimate(), 0.0);
+        }
+    }
 }
@@ -0,0 +1,147 @@
+//! Utilities for parsing binary vscsi traces.
+//!
+//! These are the block traces collected with `vscsiStats` on ESX hosts, used in much of the
+//! published cache research. A trace is a sequence of fixed-size, little-endian records, each laid
+//! out as:
+//!
+//! | bytes  | type  | field                                 |
+//! |--------|-------|---------------------------------------|
+//! | 0..4   | `u32` | sequence number                       |
+//! | 4..8   | `u32` | request length, in bytes              |
+//! | 8..12  | `u32` | number of scatter-gather elements     |
+//! | 12..14 | `u16` | SCSI command                          |
+//! | 14..16 | `u16` | version                               |
+//! | 16..24 | `u64` | logical block number                  |
+//! | 24..32 | `u64` | timestamp, in microseconds            |
+//!
+//! Each record becomes an item identified by its logical block number, with the request length as
+//! its size and unit cost.
+
+use anyhow::{anyhow, Context};
+
+use crate::GeneralModelItem;
+
+/// The length of a record, in bytes.
+const RECORD_LEN: usize = 32;
+
+/// Parse a file-like object of vscsi records into a vector of items.
+///
+/// # Errors
+/// If reading fails, the input ends partway through a record, or a logical block number doesn't
+/// fit in an item identifier. The error says which record was malformed.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> anyhow::Result<()> {
+/// use cache_sim::{vscsi::parse, GeneralModelItem};
+///
+/// let mut record = Vec::new();
+/// record.extend(0_u32.to_le_bytes()); // sequence number
+/// record.extend(4096_u32.to_le_bytes()); // length
+/// record.extend(1_u32.to_le_bytes()); // scatter-gather elements
+/// record.extend(0x28_u16.to_le_bytes()); // READ(10)
+/// record.extend(1_u16.to_le_bytes()); // version
+/// record.extend(77_u64.to_le_bytes()); // logical block number
+/// record.extend(1000_u64.to_le_bytes()); // timestamp
+///
+/// assert_eq!(parse(record.as_slice())?, vec![GeneralModelItem::new(77, 1.0, 4096)]);
+/// # Ok(())}
+/// ```
+pub fn parse<R: std::io::Read>(mut input: R) -> anyhow::Result<Vec<GeneralModelItem>> {
+    let mut bytes = Vec::new();
+    input
+        .read_to_end(&mut bytes)
+        .context("couldn't read the trace")?;
+
+    let (records, remainder) = bytes.as_chunks::<RECORD_LEN>();
+    if !remainder.is_empty() {
+        return Err(anyhow!(
+            "record {}: expected {RECORD_LEN} bytes, but the trace ends after {}",
+            records.len(),
+            remainder.len()
+        ));
+    }
+
+    records
+        .iter()
+        .enumerate()
+        .map(|(i, record)| {
+            let len = u32::from_le_bytes(std::array::from_fn(|j| record[4 + j]));
+            let lbn = u64::from_le_bytes(std::array::from_fn(|j| record[16 + j]));
+
+            let uid = u32::try_from(lbn).with_context(|| {
+                format!("record {i}: block number {lbn} is too large for an identifier")
+            })?;
+            Ok(GeneralModelItem::new(uid, 1.0, len))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::Item;
+
+    fn record(lbn: u64, len: u32) -> Vec<u8> {
+        let mut record = Vec::with_capacity(RECORD_LEN);
+        record.extend(7_u32.to_le_bytes());
+        record.extend(len.to_le_bytes());
+        record.extend(1_u32.to_le_bytes());
+        record.extend(0x2a_u16.to_le_bytes());
+        record.extend(1_u16.to_le_bytes());
+        record.extend(lbn.to_le_bytes());
+        record.extend(123_456_u64.to_le_bytes());
+        record
+    }
+
+    #[test]
+    fn ids_and_sizes() -> anyhow::Result<()> {
+        let data = [record(10, 512), record(3, 4096), record(10, 8192)].concat();
+        let items = parse(data.as_slice())?;
+
+        assert_eq!(
+            items.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec!["10", "3", "10"]
+        );
+        assert_eq!(
+            items.iter().map(Item::size).collect::<Vec<_>>(),
+            vec![512, 4096, 8192]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn little_endian() -> anyhow::Result<()> {
+        let mut data = record(0, 0);
+        data[4..8].copy_from_slice(&[0x00, 0x10, 0x00, 0x00]);
+        data[16..24].copy_from_slice(&[0x01, 0x02, 0, 0, 0, 0, 0, 0]);
+
+        let items = parse(data.as_slice())?;
+        assert_eq!(items, vec![GeneralModelItem::new(0x0201, 1.0, 0x1000)]);
+        Ok(())
+    }
+
+    #[test]
+    fn empty() -> anyhow::Result<()> {
+        assert!(parse([].as_slice())?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn truncated() {
+        let mut data = [record(1, 512), record(2, 512)].concat();
+        data.truncate(RECORD_LEN + 10);
+
+        let err = parse(data.as_slice()).expect_err("the second record is cut off");
+        assert!(err.to_string().contains("record 1"), "{err}");
+    }
+
+    #[test]
+    fn block_number_too_large() {
+        let data = [record(1, 512), record(u64::from(u32::MAX) + 1, 512)].concat();
+
+        let err = parse(data.as_slice()).expect_err("the block number doesn't fit in a u32");
+        assert!(err.to_string().contains("record 1"), "{err}");
+    }
+}